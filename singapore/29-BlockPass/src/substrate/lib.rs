@@ -1,280 +1,8716 @@
-#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
 
-use ink_lang as ink;
+use ink::prelude::{ string::String, vec::Vec };
+use ink::primitives::{ AccountId, Balance };
+
+/// The part of the PSP22 fungible token standard used to take ticket payments in an asset.
+/// Selectors derive from the `PSP22::` prefix, so they match any standard token.
+#[ink::trait_definition]
+pub trait PSP22 {
+    #[ink(message)]
+    fn transfer(
+        &mut self,
+        to: AccountId,
+        value: Balance,
+        data: Vec<u8>
+    ) -> core::result::Result<(), PSP22Error>;
+
+    /// Moves `value` from `from` to `to` out of the allowance `from` gave the caller.
+    #[ink(message)]
+    fn transfer_from(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        value: Balance,
+        data: Vec<u8>
+    ) -> core::result::Result<(), PSP22Error>;
+}
+
+/// A feed quoting the native currency in US dollars, e.g. an adapter over DIA's oracle.
+#[ink::trait_definition]
+pub trait PriceOracle {
+    /// Latest `(price, updated_at)` for `pair`, the price scaled by the feed's decimals and
+    /// `updated_at` in milliseconds like block timestamps.
+    #[ink(message)]
+    fn latest_price(&self, pair: String) -> Option<(u128, u64)>;
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum PSP22Error {
+    Custom(String),
+    InsufficientBalance,
+    InsufficientAllowance,
+    SafeTransferCheckFailed(String),
+}
 
 #[ink::contract]
 mod event_manager {
-    use ink_env::call::FromAccountId;
-    use ink_storage::collections::{ HashMap, Vec as StorageVec };
-    use ticket_nft::TicketNFT;
+    use ink::prelude::{ string::String, vec::Vec };
+    use ink::storage::{ Lazy, Mapping };
+    use crate::PSP22Error;
+    use ink::ToAccountId;
+    use ticket_nft::{
+        EventCollection,
+        PSP34Error,
+        TicketData,
+        TicketNFTRef,
+        TicketNftError,
+        TicketObserver,
+    };
+
+    #[ink(storage)]
+    pub struct EventManager {
+        /// Layout version of this storage; bumped by `migrate` after an upgrade.
+        storage_version: u32,
+        /// Primary `DEFAULT_ADMIN`; `None` once ownership has been renounced.
+        owner: Option<AccountId>,
+        pending_owner: Option<AccountId>,
+        /// Announced `emergency_withdraw`, executable once its timelock runs out.
+        emergency_withdrawal: Option<EmergencyWithdrawal>,
+        /// Code hash announced by `announce_upgrade` and when `upgrade` may install it.
+        pending_upgrade: Option<(Hash, Timestamp)>,
+        roles: Mapping<(RoleType, AccountId), ()>,
+        /// Circuit breaker: blocks new events and ticket sales, never refunds or withdrawals.
+        paused: bool,
+        /// Set while a purchase is in flight, so a callee can't re-enter it.
+        locked: bool,
+        next_event_id: u64,
+        events: Mapping<u64, Event>,
+        /// What's left of each event `archive_event` cleared out of `events`.
+        archived_events: Mapping<u64, ArchivedEvent>,
+        /// Next attendee index `process_refunds` looks at for each cancelled event.
+        refund_cursors: Mapping<u64, u64>,
+        /// Events backed by each ticket collection, to route its transfer notifications.
+        collection_events: Mapping<AccountId, Vec<u64>>,
+        /// Collections the admin opened to many events, each in its own scope.
+        shared_collections: Mapping<AccountId, ()>,
+        /// One entry per ticket sold, keyed by `(event_id, index)` below the event's
+        /// `attendee_count`.
+        attendees: Mapping<(u64, u64), AccountId>,
+        /// Token behind each `attendees` entry.
+        attendee_tickets: Mapping<(u64, u64), u64>,
+        ticket_sales: Mapping<(u64, u64), TicketSale>,
+        /// One entry per ticket bought, keyed by `(account, index)` below
+        /// `registration_counts[account]`.
+        user_registered_events: Mapping<(AccountId, u64), u64>,
+        registration_counts: Mapping<AccountId, u64>,
+        total_registrations: u64,
+        /// Running totals for `platform_stats`; amounts are in the native currency only.
+        stats: PlatformStats,
+        currency: CurrencyInfo,
+        ticket_nft_code_hash: Option<Hash>,
+        purchases: Mapping<(u64, AccountId), Purchase>,
+        invalidated_tickets: Mapping<(u64, u64), ()>,
+        platform_fee_bps: u16,
+        platform_fees: Balance,
+        price_oracle: Option<OracleConfig>,
+        /// Released platform fees per PSP22 asset, kept apart from the native `platform_fees`.
+        token_fees: Mapping<AccountId, Balance>,
+        purchase_counts: Mapping<(u64, AccountId), u64>,
+        /// Valid tickets each account currently holds per event, for O(1) door checks.
+        ticket_counts: Mapping<(u64, AccountId), u32>,
+        presales: Mapping<u64, Presale>,
+        promo_codes: Mapping<(u64, [u8; 32]), PromoCode>,
+        /// Account each referral code, stored as the blake2x256 hash of its text, pays.
+        referral_codes: Mapping<[u8; 32], AccountId>,
+        /// Commission each referrer has earned per event, withdrawable once it's over.
+        referral_earnings: Mapping<(u64, AccountId), Balance>,
+        /// Tickets each referrer has sold per event.
+        referral_counts: Mapping<(u64, AccountId), u64>,
+        /// `invite_payload` of every voucher that has been redeemed.
+        used_invites: Mapping<[u8; 32], ()>,
+        /// `voucher_payload` of every ticket voucher that has been redeemed.
+        used_vouchers: Mapping<[u8; 32], ()>,
+        /// Tickets sold per event per day, as `(day start, count)` in ascending order;
+        /// days without sales have no entry.
+        sales_buckets: Mapping<u64, Vec<(Timestamp, u64)>>,
+        lotteries: Mapping<u64, Lottery>,
+        lottery_entrants: Mapping<u64, Vec<AccountId>>,
+        lottery_entries: Mapping<(u64, AccountId), LotteryEntry>,
+        waitlists: Mapping<u64, Vec<AccountId>>,
+        held_slots: Mapping<u64, Vec<HeldSlot>>,
+        host_events: Mapping<AccountId, Vec<u64>>,
+        /// Proposed new host per event, pending their acceptance.
+        pending_hosts: Mapping<u64, AccountId>,
+        event_operators: Mapping<(u64, AccountId), ()>,
+        /// Door staff allowed to check attendees in, and nothing else.
+        event_verifiers: Mapping<(u64, AccountId), ()>,
+        /// Accounts an admin banned from every event.
+        platform_bans: Mapping<AccountId, ()>,
+        /// Accounts a host banned from their event.
+        event_bans: Mapping<(u64, AccountId), ()>,
+        /// When each ticket, keyed by `(event_id, token_id)`, was checked in.
+        check_ins: Mapping<(u64, u64), Timestamp>,
+        checked_in_counts: Mapping<u64, u64>,
+        /// Badge token id per `(event_id, account)` that claimed one.
+        attendance_badges: Mapping<(u64, AccountId), u64>,
+        /// Events each account holds a badge for, keyed by `(account, index)` below
+        /// `badge_counts[account]`.
+        account_badges: Mapping<(AccountId, u64), u64>,
+        badge_counts: Mapping<AccountId, u64>,
+        /// Bumped on every signed check-in, so each signature works once.
+        check_in_nonces: Mapping<(u64, AccountId), u64>,
+        category_events: Mapping<u8, Vec<u64>>,
+        tag_events: Mapping<[u8; 32], Vec<u64>>,
+        /// Events that are active and not sold out, so discovery doesn't scan every event.
+        /// Lazy so that messages which never touch discovery don't pay to load it.
+        active_events: Lazy<Vec<u64>>,
+    }
+
+    /// Storage layout version this code expects. Bump it together with a new branch in
+    /// `migrate` whenever an upgrade changes how existing state must be interpreted.
+    const STORAGE_VERSION: u32 = 1;
+
+    /// Identifier of a platform role.
+    pub type RoleType = u32;
+
+    /// Can grant and revoke every role, withdraw platform fees and set the NFT code hash.
+    pub const DEFAULT_ADMIN: RoleType = 0;
+
+    /// Can pause and unpause the platform.
+    pub const PAUSER: RoleType = 1;
+
+    /// Can change the platform fee.
+    pub const FEE_MANAGER: RoleType = 2;
+
+    /// Decimals of USD tier prices, i.e. they are in cents.
+    const USD_DECIMALS: u32 = 2;
+
+    /// Basis points denominator, i.e. 100%.
+    const MAX_BPS: u16 = 10_000;
+
+    /// Most steps a refund policy may have.
+    const MAX_REFUND_TIERS: usize = 8;
+
+    /// Tier recorded on comp tickets, which don't belong to any of the event's tiers.
+    pub const COMP_TIER: u32 = u32::MAX;
+
+    /// Most payees an event's revenue may be split between.
+    const MAX_PAYEES: usize = 8;
+
+    /// Most tags an event may carry.
+    const MAX_TAGS: usize = 8;
+
+    /// Upper bound on the number of entries any paginated query returns.
+    const MAX_PAGE_SIZE: u32 = 100;
+
+    /// How long a freed slot stays reserved for the next waitlisted account (24 hours).
+    const WAITLIST_CLAIM_WINDOW: Timestamp = 24 * 60 * 60 * 1000;
+
+    /// How long after the event its tickets stay valid for late check-ins (24 hours).
+    const TICKET_EXPIRY_GRACE: Timestamp = 24 * 60 * 60 * 1000;
+
+    /// Width of one `sales_history` bucket (24 hours).
+    const SALES_BUCKET: Timestamp = 24 * 60 * 60 * 1000;
+
+    /// How long after an event refunds and claims stay open before it may be archived
+    /// (30 days).
+    const ARCHIVE_DELAY: Timestamp = 30 * 24 * 60 * 60 * 1000;
+
+    /// How long an announced emergency withdrawal waits before it can run (7 days).
+    const EMERGENCY_TIMELOCK: Timestamp = 7 * 24 * 60 * 60 * 1000;
+
+    /// How long an announced code upgrade waits before it can be installed (7 days).
+    const UPGRADE_TIMELOCK: Timestamp = 7 * 24 * 60 * 60 * 1000;
+
+    /// Longest memo `gift_ticket` carries, in bytes.
+    const MAX_MEMO_LEN: usize = 128;
+
+    /// How long after registration closes the host has to reveal the lottery seed (3 days).
+    const LOTTERY_REVEAL_WINDOW: Timestamp = 3 * 24 * 60 * 60 * 1000;
+
+    /// Display metadata for the currency prices and revenue are denominated in.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct CurrencyInfo {
+        symbol: String,
+        decimals: u8,
+    }
+
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct EventDetails {
+        title: String,
+        date: Timestamp,
+        location: String,
+        tiers: Vec<TicketTier>,
+        /// Tickets a single account may buy for this event; `0` means no limit.
+        max_tickets_per_account: u64,
+        sales_start: Timestamp,
+        sales_end: Timestamp,
+        /// Early-bird style price steps; empty means every ticket sells at its tier price.
+        price_schedule: Vec<PriceBreakpoint>,
+        /// Dutch auction over the sales window; cannot be combined with `price_schedule`.
+        dutch_auction: Option<DutchAuction>,
+        /// Discovery category, e.g. concert, conference or meetup; the mapping of values to
+        /// labels is left to the frontend.
+        category: u8,
+        /// blake2x256 hashes of lowercase tag strings.
+        tags: Vec<[u8; 32]>,
+        /// Tier prices are in US cents and converted to the native currency at purchase
+        /// time through the platform's price oracle.
+        usd_priced: bool,
+        /// Share of a ticket's price `request_refund` pays back, depending on how long
+        /// before the event it's asked for. Empty means tickets aren't refundable.
+        refund_policy: Vec<RefundTier>,
+        /// Crowdfunding mode: the event only goes ahead if enough tickets sell in time.
+        funding_goal: Option<FundingGoal>,
+        /// Accounts `claim_revenue` splits the takings between. Empty pays it all to the host.
+        payees: Vec<Payee>,
+    }
+
+    /// A revenue share of `share_bps` basis points; an event's shares add up to `MAX_BPS`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Payee {
+        account: AccountId,
+        share_bps: u16,
+    }
+
+    /// Until `min_tickets` have sold the event is unconfirmed and its revenue stays in
+    /// escrow. If `deadline` passes first, `settle_funding` cancels it and every buyer can
+    /// claim a full refund.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct FundingGoal {
+        min_tickets: u64,
+        deadline: Timestamp,
+    }
+
+    /// Refund `refund_bps` of the price when asked at least `min_notice` before the event.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct RefundTier {
+        min_notice: Timestamp,
+        refund_bps: u16,
+    }
+
+    /// What a single ticket sold for, so it can be refunded on its own.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct TicketSale {
+        tier: u32,
+        price: Balance,
+        fee: Balance,
+        /// Where the ticket sits in the event's `attendees`.
+        attendee_index: u64,
+        referrer: Option<AccountId>,
+        /// The referrer's cut of this sale, included in `price`.
+        commission: Balance,
+        /// Issued free by the host rather than sold; never refundable.
+        comp: bool,
+    }
+
+    /// Where USD prices come from and how much drift a purchase tolerates.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct OracleConfig {
+        oracle: AccountId,
+        /// Feed key, e.g. "DOT/USD".
+        pair: String,
+        /// Decimals of the quoted price.
+        decimals: u8,
+        /// Oldest quote, relative to the block timestamp, that purchases will accept.
+        max_age: Timestamp,
+        /// How far a payment may fall short of the converted price, e.g. when the rate moved
+        /// between the buyer's quote and their transaction.
+        slippage_bps: u16,
+    }
+
+    /// Ticket prices start at `start_bps` of the tier price when sales open and decay
+    /// linearly to `floor_bps` by `sales_end`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct DutchAuction {
+        start_bps: u32,
+        floor_bps: u32,
+    }
+
+    /// Tickets sold while fewer than `until_sold` have gone (across all tiers) are priced
+    /// at `multiplier_bps` of the tier price. Past the last breakpoint the tier price applies.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PriceBreakpoint {
+        until_sold: u64,
+        multiplier_bps: u32,
+    }
+
+    /// A class of ticket (e.g. GA or VIP) with its own price and capacity.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct TicketTier {
+        name: String,
+        price: Balance,
+        max: u64,
+        sold: u64,
+    }
+
+    impl EventDetails {
+        /// Total capacity across all tiers.
+        pub fn max_tickets(&self) -> u64 {
+            self.tiers.iter().map(|tier| tier.max).sum()
+        }
+    }
+
+    /// Lifecycle of an event. `Inactive` pauses sales and can be undone by the host;
+    /// `Cancelled` is final and opens refunds.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum EventStatus {
+        Active,
+        Inactive,
+        Cancelled,
+    }
+
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Event {
+        event_id: u64,
+        details: EventDetails,
+        ticket_nft_address: AccountId,
+        attendee_count: u64,
+        tickets_sold: u64,
+        status: EventStatus,
+        host: AccountId,
+        revenue: Balance,
+        total_revenue: Balance,
+        platform_fees: Balance,
+        /// PSP22 asset tickets are priced, paid and refunded in; `None` means native.
+        payment_token: Option<AccountId>,
+        /// Display metadata for `payment_token`, set along with it.
+        payment_currency: Option<CurrencyInfo>,
+        /// Set once a crowdfunded event reaches its `funding_goal`.
+        funding_confirmed: bool,
+        /// Share of a referred sale's net price owed to the referrer.
+        referral_bps: u16,
+        /// Commission still owed to referrers; part of `revenue` but not the host's.
+        referral_owed: Balance,
+        referred_tickets: u64,
+        /// Host-issued free tickets allowed on top of the tiers' capacity.
+        comp_allocation: u64,
+        /// Comps issued so far. They count as attendees but not as `tickets_sold`.
+        comps_issued: u64,
+        /// Purchases need an invite voucher signed by the host.
+        invite_only: bool,
+        /// Highest resale price as basis points of face value, enforced by the collection.
+        resale_cap_bps: Option<u16>,
+        /// Share of every resale paid to the host, through the collection's `royalty_info`.
+        royalty_bps: u16,
+        /// `false` binds tickets to the account they were minted to, on the collection.
+        transferable: bool,
+        /// Tickets can't change hands until this long after the event, e.g. for stubs.
+        transfer_lock: Option<Timestamp>,
+    }
+
+    /// Tombstone of an archived event: its headline numbers and nothing per attendee.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct ArchivedEvent {
+        host: AccountId,
+        date: Timestamp,
+        status: EventStatus,
+        tickets_sold: u64,
+        comps_issued: u64,
+        attendee_count: u64,
+        total_revenue: Balance,
+        archived_at: Timestamp,
+        /// Attendee entries still in storage, cleared by further `archive_event` calls.
+        uncleared: u64,
+    }
+
+    /// Allowlist pre-sale: until `public_sale_start`, only accounts whose leaf is in the
+    /// Merkle tree with root `merkle_root` may buy. Leaves are `blake2x256(account)`, and
+    /// each pair of nodes is hashed in sorted order.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Presale {
+        merkle_root: [u8; 32],
+        public_sale_start: Timestamp,
+    }
+
+    /// A discount code, stored only as the blake2x256 hash of its text.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PromoCode {
+        discount_bps: u16,
+        max_redemptions: u32,
+        redemptions: u32,
+        revoked: bool,
+    }
+
+    /// Register-then-draw sale for oversubscribed events. Accounts register (paying
+    /// `deposit`) until `registration_end`; after the host draws, only winners may buy for
+    /// `claim_window` milliseconds, then sales open to everyone.
+    ///
+    /// The draw is commit-reveal: the host commits to `blake2x256(seed)` before anyone
+    /// registers and reveals `seed` to draw, mixed with every registration. They can't
+    /// pick a seed once they've seen who entered.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Lottery {
+        registration_end: Timestamp,
+        claim_window: Timestamp,
+        winners: u32,
+        deposit: Balance,
+        drawn_at: Option<Timestamp>,
+        seed_commitment: [u8; 32],
+        /// Running hash over the entrants, in registration order.
+        entropy: [u8; 32],
+    }
+
+    impl Lottery {
+        /// Unrevealed past `LOTTERY_REVEAL_WINDOW`: deposits come back and sales open.
+        fn forfeited(&self, now: Timestamp) -> bool {
+            self.drawn_at.is_none() &&
+                now >= self.registration_end.saturating_add(LOTTERY_REVEAL_WINDOW)
+        }
+    }
+
+    #[derive(Clone, Default, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct LotteryEntry {
+        deposit: Balance,
+        won: bool,
+        deposit_returned: bool,
+    }
+
+    /// A freed ticket slot in `tier` that only `account` may buy until `expires_at`.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct HeldSlot {
+        account: AccountId,
+        tier: u32,
+        expires_at: Timestamp,
+    }
+
+    /// A ticket the host sold off-chain, to be minted when the buyer redeems it. The host
+    /// signs `voucher_payload`; whoever presents the signature first gets the ticket.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct TicketVoucher {
+        pub event_id: u64,
+        pub tier: u32,
+        /// In the event's payment asset, replacing the tier's price and any discounts.
+        pub price: Balance,
+        /// Tells apart otherwise identical vouchers.
+        pub nonce: u64,
+        pub expiry: Timestamp,
+    }
+
+    /// Optional inputs that only some purchase entry points provide.
+    #[derive(Default)]
+    struct PurchaseOptions {
+        proof: Vec<[u8; 32]>,
+        promo_code: Option<[u8; 32]>,
+        /// Pay the event's PSP22 asset via `transfer_from` instead of native value.
+        pay_with_token: bool,
+        referral_code: Option<[u8; 32]>,
+        /// Who the tickets are for, when not the caller. They become the attendee and
+        /// holder, and purchase limits apply to them.
+        recipient: Option<AccountId>,
+        /// Expiry and host signature of an invite voucher.
+        invite: Option<(Timestamp, [u8; 65])>,
+        /// Payload and price of a verified ticket voucher.
+        voucher: Option<([u8; 32], Balance)>,
+    }
+
+    /// What a buyer paid for an event and which tokens that bought, used for exact refunds.
+    #[derive(Clone, Default, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Purchase {
+        paid: Balance,
+        fee: Balance,
+        token_ids: Vec<u64>,
+        refunded: bool,
+    }
+
+    /// Flattened, read-only view of an event for frontends.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct EventInfo {
+        event_id: u64,
+        title: String,
+        date: Timestamp,
+        location: String,
+        category: u8,
+        tiers: Vec<TicketTier>,
+        sales_start: Timestamp,
+        sales_end: Timestamp,
+        ticket_nft_address: AccountId,
+        host: AccountId,
+        tickets_sold: u64,
+        tickets_remaining: u64,
+        status: EventStatus,
+        /// What prices are in: the PSP22 asset's metadata when `payment_token` is set,
+        /// the platform currency otherwise.
+        currency: CurrencyInfo,
+        /// Set when prices are in this PSP22 asset rather than the native currency.
+        payment_token: Option<AccountId>,
+    }
+
+    /// Platform-wide totals kept up as they happen, for landing pages. PSP22 sales only add
+    /// to `tickets_sold`.
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PlatformStats {
+        events_created: u64,
+        /// Tickets ever sold, comps excluded; refunds don't take them back off.
+        tickets_sold: u64,
+        /// Paid for tickets in the native currency.
+        native_volume: Balance,
+        /// Refunded in the native currency.
+        native_refunded: Balance,
+    }
+
+    /// Funds the owner means to pull out past the bookkeeping, e.g. when a bug strands
+    /// them. `token` is a PSP22 asset, `None` the native currency.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct EmergencyWithdrawal {
+        token: Option<AccountId>,
+        to: AccountId,
+        amount: Balance,
+        executable_at: Timestamp,
+    }
+
+    /// Result of recomputing the contract's bookkeeping invariants.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct AuditReport {
+        events_checked: u64,
+        total_tickets_sold: u64,
+        total_comp_tickets: u64,
+        total_attendees: u64,
+        total_registrations: u64,
+        oversold_events: u64,
+        total_revenue_held: Balance,
+        platform_fees_held: Balance,
+        /// Native lottery deposits not yet handed back to their entrants.
+        lottery_deposits_held: Balance,
+        tickets_match_attendees: bool,
+        registrations_match_attendees: bool,
+        balance_covers_revenue: bool,
+    }
+
+    impl AuditReport {
+        /// True when every invariant holds.
+        pub fn is_healthy(&self) -> bool {
+            self.oversold_events == 0
+                && self.tickets_match_attendees
+                && self.registrations_match_attendees
+                && self.balance_covers_revenue
+        }
+    }
+
+    #[ink(event)]
+    pub struct EventCreated {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        host: AccountId,
+        ticket_nft_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TicketPurchased {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+        token_id: u64,
+        tier: u32,
+        price: Balance,
+    }
+
+    /// Emitted with `TicketPurchased` when the buyer bought for somebody else.
+    #[ink(event)]
+    pub struct TicketPurchasedFor {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+        #[ink(topic)]
+        recipient: AccountId,
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct InviteOnlySet {
+        #[ink(topic)]
+        event_id: u64,
+        invite_only: bool,
+    }
+
+    #[ink(event)]
+    pub struct InviteRedeemed {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        invitee: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct VoucherRedeemed {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+        nonce: u64,
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct ResaleCapSet {
+        #[ink(topic)]
+        event_id: u64,
+        cap_bps: Option<u16>,
+    }
+
+    #[ink(event)]
+    pub struct TicketRevoked {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        holder: AccountId,
+        token_id: u64,
+        reason_code: u8,
+    }
+
+    #[ink(event)]
+    pub struct ExpiredTicketsBurned {
+        #[ink(topic)]
+        event_id: u64,
+        token_ids: Vec<u64>,
+    }
+
+    #[ink(event)]
+    pub struct TransferLockSet {
+        #[ink(topic)]
+        event_id: u64,
+        delay: Option<Timestamp>,
+    }
+
+    #[ink(event)]
+    pub struct TransferableSet {
+        #[ink(topic)]
+        event_id: u64,
+        transferable: bool,
+    }
+
+    #[ink(event)]
+    pub struct RoyaltySet {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        royalty_bps: u16,
+    }
+
+    #[ink(event)]
+    pub struct CompTicketIssued {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        recipient: AccountId,
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct SoldOut {
+        #[ink(topic)]
+        event_id: u64,
+        tickets_sold: u64,
+    }
+
+    #[ink(event)]
+    pub struct EventUpdated {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        updated_by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OperatorAdded {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        operator: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OperatorRemoved {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        operator: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct VerifierAdded {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        verifier: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct VerifierRemoved {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        verifier: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct CheckedIn {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        attendee: AccountId,
+        token_id: u64,
+        checked_in_by: AccountId,
+        timestamp: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct AttendanceBadgeClaimed {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        attendee: AccountId,
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct TicketRefunded {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+        token_id: u64,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ReferralCodeRegistered {
+        #[ink(topic)]
+        code_hash: [u8; 32],
+        #[ink(topic)]
+        referrer: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ReferralCommissionSet {
+        #[ink(topic)]
+        event_id: u64,
+        commission_bps: u16,
+    }
+
+    #[ink(event)]
+    pub struct ReferralEarningsWithdrawn {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        referrer: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct FundingConfirmed {
+        #[ink(topic)]
+        event_id: u64,
+        tickets_sold: u64,
+    }
+
+    /// The funding deadline passed short of the goal; the event is now cancelled.
+    #[ink(event)]
+    pub struct FundingFailed {
+        #[ink(topic)]
+        event_id: u64,
+        tickets_sold: u64,
+    }
+
+    #[ink(event)]
+    pub struct EventDeactivated {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        host: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct EventReactivated {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        host: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct HostTransferProposed {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        host: AccountId,
+        #[ink(topic)]
+        new_host: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct HostTransferred {
+        #[ink(topic)]
+        event_id: u64,
+        previous_host: AccountId,
+        #[ink(topic)]
+        new_host: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct PaymentTokenSet {
+        #[ink(topic)]
+        event_id: u64,
+        token: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct TokenFeesWithdrawn {
+        #[ink(topic)]
+        token: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RevenueClaimed {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        host: AccountId,
+        amount: Balance,
+    }
+
+    /// One payee's share of a split `RevenueClaimed`.
+    #[ink(event)]
+    pub struct RevenuePaid {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        payee: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct EventCancelled {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        host: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TicketGifted {
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        memo: String,
+    }
+
+    #[ink(event)]
+    pub struct TicketRecovered {
+        #[ink(topic)]
+        event_id: u64,
+        old_token_id: u64,
+        new_token_id: u64,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct EventArchived {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        archived_by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RefundClaimed {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PriceOracleUpdated {
+        #[ink(topic)]
+        oracle: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct PlatformFeeUpdated {
+        old_fee_bps: u16,
+        new_fee_bps: u16,
+    }
+
+    #[ink(event)]
+    pub struct PlatformFeesWithdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: Option<AccountId>,
+        #[ink(topic)]
+        new_owner: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct EmergencyWithdrawalAnnounced {
+        token: Option<AccountId>,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+        executable_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct EmergencyWithdrawalCancelled {
+        token: Option<AccountId>,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct EmergencyWithdrawalExecuted {
+        token: Option<AccountId>,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct UpgradeAnnounced {
+        code_hash: Hash,
+        executable_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct UpgradeCancelled {
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct Upgraded {
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// `event_id` is `None` for a platform-wide ban.
+    #[ink(event)]
+    pub struct AccountBanned {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        event_id: Option<u64>,
+        sender: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AccountUnbanned {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        event_id: Option<u64>,
+        sender: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct LotteryDrawn {
+        #[ink(topic)]
+        event_id: u64,
+        winners: u32,
+        entrants: u32,
+    }
+
+    #[ink(event)]
+    pub struct WaitlistSlotOffered {
+        #[ink(topic)]
+        event_id: u64,
+        #[ink(topic)]
+        account: AccountId,
+        tier: u32,
+        expires_at: Timestamp,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        EventNotFound,
+        EventInactive,
+        EventActive,
+        SoldOut,
+        InsufficientPayment,
+        NotHost,
+        MissingRole,
+        RoleAlreadyGranted,
+        NotOwner,
+        NotPendingOwner,
+        Paused,
+        NotPaused,
+        UpgradeFailed,
+        AlreadyMigrated,
+        Overflow,
+        Reentrancy,
+        CodeHashNotSet,
+        InstantiationFailed,
+        /// The ticket collection refused to mint, with its reason.
+        MintFailed(TicketNftError),
+        EventNotEnded,
+        EventCancelled,
+        EventNotCancelled,
+        NothingToWithdraw,
+        NothingToRefund,
+        InvalidFee,
+        InvalidQuantity,
+        PurchaseLimitExceeded,
+        NoTiers,
+        TierNotFound,
+        InvalidSalesWindow,
+        EventInPast,
+        EventEnded,
+        SalesNotStarted,
+        SalesEnded,
+        NotAllowlisted,
+        InvalidPriceSchedule,
+        InvalidAuction,
+        TooManyTags,
+        InvalidPromoCode,
+        PromoCodeExhausted,
+        ReferralCodeTaken,
+        InvalidReferralCode,
+        /// Buyers can't earn commission on their own purchases.
+        SelfReferral,
+        InvalidCommission,
+        InvalidRoyalty,
+        /// Issuing the comps would exceed the event's `comp_allocation`.
+        CompAllocationExceeded,
+        /// The event is invite-only and no voucher was given.
+        InviteRequired,
+        InviteExpired,
+        InviteAlreadyUsed,
+        VoucherExpired,
+        VoucherAlreadyUsed,
+        LotteryNotFound,
+        LotteryClosed,
+        LotteryNotDrawn,
+        LotteryAlreadyDrawn,
+        /// The revealed seed doesn't hash to the lottery's commitment.
+        InvalidSeed,
+        /// The host didn't reveal the seed in time, so the lottery is void.
+        LotteryForfeited,
+        AlreadyRegistered,
+        NotLotteryWinner,
+        NotSoldOut,
+        AlreadyWaitlisted,
+        NotWaitlisted,
+        NoPendingHostTransfer,
+        NotPendingHost,
+        NotOperator,
+        SalesStarted,
+        TransferFailed,
+        /// Native value sent to a PSP22-priced event, or the other way round.
+        WrongPaymentAsset,
+        TokenTransferFailed,
+        NotVerifier,
+        NoValidTicket,
+        /// Every valid ticket the attendee holds has already been used.
+        AlreadyCheckedIn,
+        /// The signature wasn't made by the attendee over their current challenge.
+        InvalidSignature,
+        RedeemFailed(TicketNftError),
+        NotCheckedIn,
+        BadgeAlreadyClaimed,
+        InvalidRefundPolicy,
+        /// The policy pays nothing back at this point, or the ticket predates refund
+        /// tracking.
+        RefundNotAvailable,
+        BurnFailed(TicketNftError),
+        /// The ticket is still within `TICKET_EXPIRY_GRACE` of the event.
+        TicketNotExpired,
+        NotSharedCollection,
+        /// The collection doesn't declare the interface the manager needs.
+        UnsupportedCollection,
+        /// Collection-wide settings would reach the other events on a shared collection.
+        SharedCollection,
+        /// The collection refused a configuration change, e.g. the resale cap.
+        CollectionUpdateFailed(TicketNftError),
+        InvalidFundingGoal,
+        /// Payee shares must be non-zero, unique per account and add up to `MAX_BPS`.
+        InvalidPayees,
+        NotCrowdfunded,
+        FundingAlreadyConfirmed,
+        /// The funding deadline is still ahead and the goal not yet met.
+        FundingPending,
+        /// The funding deadline passed short of the goal.
+        FundingDeadlinePassed,
+        FundingNotConfirmed,
+        OracleNotSet,
+        /// The oracle has no quote, or its latest one is older than `max_age`.
+        StalePrice,
+        InvalidSlippage,
+        /// The event ended less than `ARCHIVE_DELAY` ago.
+        ArchiveTooEarly,
+        /// Revenue, fees or lottery deposits are still escrowed for the event.
+        FundsOutstanding,
+        AlreadyArchived,
+        /// The collection refused to reissue the ticket, e.g. because it was redeemed.
+        RecoveryFailed(TicketNftError),
+        /// Gift memos are at most `MAX_MEMO_LEN` bytes.
+        MemoTooLong,
+        NotTicketHolder,
+        /// The collection refused to move the ticket, e.g. because the manager isn't
+        /// approved for it.
+        TicketTransferFailed(PSP34Error),
+        NoEmergencyWithdrawal,
+        /// The emergency withdrawal's or upgrade's timelock hasn't run out yet.
+        TimelockActive,
+        NoPendingUpgrade,
+        /// The account is banned from the event or the whole platform.
+        Banned,
+        AlreadyBanned,
+        NotBanned,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl EventManager {
+        #[ink(constructor)]
+        pub fn new(currency: CurrencyInfo) -> Self {
+            let caller = Self::env().caller();
+            let mut roles = Mapping::default();
+            for role in [DEFAULT_ADMIN, PAUSER, FEE_MANAGER].iter() {
+                roles.insert((*role, caller), &());
+            }
+            Self {
+                storage_version: STORAGE_VERSION,
+                owner: Some(caller),
+                pending_owner: None,
+                emergency_withdrawal: None,
+                pending_upgrade: None,
+                roles,
+                paused: false,
+                locked: false,
+                next_event_id: 1,
+                events: Mapping::default(),
+                archived_events: Mapping::default(),
+                refund_cursors: Mapping::default(),
+                collection_events: Mapping::default(),
+                shared_collections: Mapping::default(),
+                attendees: Mapping::default(),
+                attendee_tickets: Mapping::default(),
+                ticket_sales: Mapping::default(),
+                user_registered_events: Mapping::default(),
+                registration_counts: Mapping::default(),
+                total_registrations: 0,
+                stats: PlatformStats::default(),
+                currency,
+                ticket_nft_code_hash: None,
+                price_oracle: None,
+                purchases: Mapping::default(),
+                invalidated_tickets: Mapping::default(),
+                platform_fee_bps: 0,
+                platform_fees: 0,
+                purchase_counts: Mapping::default(),
+                ticket_counts: Mapping::default(),
+                presales: Mapping::default(),
+                promo_codes: Mapping::default(),
+                referral_codes: Mapping::default(),
+                referral_earnings: Mapping::default(),
+                referral_counts: Mapping::default(),
+                used_invites: Mapping::default(),
+                used_vouchers: Mapping::default(),
+                sales_buckets: Mapping::default(),
+                lotteries: Mapping::default(),
+                lottery_entrants: Mapping::default(),
+                lottery_entries: Mapping::default(),
+                waitlists: Mapping::default(),
+                held_slots: Mapping::default(),
+                host_events: Mapping::default(),
+                pending_hosts: Mapping::default(),
+                event_operators: Mapping::default(),
+                event_verifiers: Mapping::default(),
+                platform_bans: Mapping::default(),
+                event_bans: Mapping::default(),
+                check_ins: Mapping::default(),
+                checked_in_counts: Mapping::default(),
+                check_in_nonces: Mapping::default(),
+                attendance_badges: Mapping::default(),
+                account_badges: Mapping::default(),
+                badge_counts: Mapping::default(),
+                category_events: Mapping::default(),
+                tag_events: Mapping::default(),
+                active_events: Lazy::default(),
+                token_fees: Mapping::default(),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_owner(&self) -> Option<AccountId> {
+            self.owner
+        }
+
+        #[ink(message)]
+        pub fn get_pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Starts handing the contract over to `new_owner`, who must call
+        /// `accept_ownership` to complete it. A mistyped address can simply be replaced.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            let owner = self.env().caller();
+            if self.owner != Some(owner) {
+                return Err(Error::NotOwner);
+            }
+            self.pending_owner = Some(new_owner);
+
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_owner: owner,
+                new_owner,
+            });
+            Ok(())
+        }
+
+        /// Completes a pending transfer: the caller becomes owner and takes over the
+        /// previous owner's `DEFAULT_ADMIN` role.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(Error::NotPendingOwner);
+            }
+            let previous_owner = self.owner;
+            if let Some(previous) = previous_owner {
+                self.roles.remove((DEFAULT_ADMIN, previous));
+            }
+            self.roles.insert((DEFAULT_ADMIN, caller), &());
+            self.owner = Some(caller);
+            self.pending_owner = None;
+
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner: Some(caller),
+            });
+            Ok(())
+        }
+
+        /// Gives up ownership and the owner's `DEFAULT_ADMIN` role for good. Other admins,
+        /// if any were granted, keep theirs.
+        #[ink(message)]
+        pub fn renounce_ownership(&mut self) -> Result<()> {
+            let owner = self.env().caller();
+            if self.owner != Some(owner) {
+                return Err(Error::NotOwner);
+            }
+            self.roles.remove((DEFAULT_ADMIN, owner));
+            self.owner = None;
+            self.pending_owner = None;
+
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner: Some(owner),
+                new_owner: None,
+            });
+            Ok(())
+        }
+
+        /// Announces that the owner will swap the contract's code for `code_hash` once
+        /// `UPGRADE_TIMELOCK` has passed, so users can leave before new code can touch
+        /// their funds. A new announcement replaces the pending one and restarts the clock.
+        #[ink(message)]
+        pub fn announce_upgrade(&mut self, code_hash: Hash) -> Result<()> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotOwner);
+            }
+            let executable_at = self.env()
+                .block_timestamp()
+                .checked_add(UPGRADE_TIMELOCK)
+                .ok_or(Error::Overflow)?;
+            self.pending_upgrade = Some((code_hash, executable_at));
+
+            self.env().emit_event(UpgradeAnnounced {
+                code_hash,
+                executable_at,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_upgrade(&mut self) -> Result<()> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotOwner);
+            }
+            let (code_hash, _) = self.pending_upgrade.take().ok_or(Error::NoPendingUpgrade)?;
+
+            self.env().emit_event(UpgradeCancelled { code_hash });
+            Ok(())
+        }
+
+        /// Installs the announced code once its timelock has run out, keeping all storage.
+        /// Call `migrate` right after if the new code bumps `STORAGE_VERSION`.
+        #[ink(message)]
+        pub fn upgrade(&mut self) -> Result<()> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotOwner);
+            }
+            let (code_hash, executable_at) = self.pending_upgrade.ok_or(Error::NoPendingUpgrade)?;
+            if self.env().block_timestamp() < executable_at {
+                return Err(Error::TimelockActive);
+            }
+            self.pending_upgrade = None;
+            self.env().set_code_hash(&code_hash).map_err(|_| Error::UpgradeFailed)?;
+
+            self.env().emit_event(Upgraded { code_hash });
+            Ok(())
+        }
+
+        /// Brings storage written by older code up to `STORAGE_VERSION`, one version at a
+        /// time.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<()> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotOwner);
+            }
+            let from_version = self.storage_version;
+            if from_version >= STORAGE_VERSION {
+                return Err(Error::AlreadyMigrated);
+            }
+            // Version-specific steps go here, e.g. `if self.storage_version == 1 { ... }`
+            self.storage_version = STORAGE_VERSION;
+
+            self.env().emit_event(Migrated {
+                from_version,
+                to_version: STORAGE_VERSION,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_pending_upgrade(&self) -> Option<(Hash, Timestamp)> {
+            self.pending_upgrade
+        }
+
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            self.storage_version
+        }
+
+        /// Announces that the owner will send `amount` of `token` (native when `None`) to
+        /// `to` once `EMERGENCY_TIMELOCK` has passed, giving users a week's warning. A new
+        /// announcement replaces the pending one and restarts the clock.
+        #[ink(message)]
+        pub fn announce_emergency_withdraw(
+            &mut self,
+            token: Option<AccountId>,
+            to: AccountId,
+            amount: Balance
+        ) -> Result<()> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotOwner);
+            }
+            let executable_at = self.env()
+                .block_timestamp()
+                .checked_add(EMERGENCY_TIMELOCK)
+                .ok_or(Error::Overflow)?;
+            self.emergency_withdrawal = Some(EmergencyWithdrawal {
+                token,
+                to,
+                amount,
+                executable_at,
+            });
+
+            self.env().emit_event(EmergencyWithdrawalAnnounced {
+                token,
+                to,
+                amount,
+                executable_at,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_emergency_withdraw(&mut self) -> Result<()> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotOwner);
+            }
+            let pending = self.emergency_withdrawal.take().ok_or(Error::NoEmergencyWithdrawal)?;
+
+            self.env().emit_event(EmergencyWithdrawalCancelled {
+                token: pending.token,
+                to: pending.to,
+                amount: pending.amount,
+            });
+            Ok(())
+        }
+
+        /// Carries out the announced withdrawal once its timelock has run out. It moves
+        /// funds without touching any event's books, so `audit` may report drift after.
+        #[ink(message)]
+        pub fn emergency_withdraw(&mut self) -> Result<Balance> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotOwner);
+            }
+            let pending = self.emergency_withdrawal.clone().ok_or(Error::NoEmergencyWithdrawal)?;
+            if self.env().block_timestamp() < pending.executable_at {
+                return Err(Error::TimelockActive);
+            }
+            self.emergency_withdrawal = None;
+            self.pay_out(pending.token, pending.to, pending.amount)?;
+
+            self.env().emit_event(EmergencyWithdrawalExecuted {
+                token: pending.token,
+                to: pending.to,
+                amount: pending.amount,
+            });
+            Ok(pending.amount)
+        }
+
+        #[ink(message)]
+        pub fn get_emergency_withdrawal(&self) -> Option<EmergencyWithdrawal> {
+            self.emergency_withdrawal.clone()
+        }
+
+        #[ink(message)]
+        pub fn has_role(&self, role: RoleType, account: AccountId) -> bool {
+            self.roles.contains((role, account))
+        }
+
+        /// Grants `role` to `account`. Only `DEFAULT_ADMIN` holders may grant roles.
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: RoleType, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            self.ensure_role(DEFAULT_ADMIN, sender)?;
+            if self.has_role(role, account) {
+                return Err(Error::RoleAlreadyGranted);
+            }
+            self.roles.insert((role, account), &());
+
+            self.env().emit_event(RoleGranted {
+                role,
+                account,
+                sender,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: RoleType, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            self.ensure_role(DEFAULT_ADMIN, sender)?;
+            self.remove_role(role, account, sender)
+        }
+
+        /// Gives up one of the caller's own roles.
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: RoleType) -> Result<()> {
+            let account = self.env().caller();
+            self.remove_role(role, account, account)
+        }
+
+        fn remove_role(
+            &mut self,
+            role: RoleType,
+            account: AccountId,
+            sender: AccountId
+        ) -> Result<()> {
+            if !self.roles.contains((role, account)) {
+                return Err(Error::MissingRole);
+            }
+            self.roles.remove((role, account));
+
+            self.env().emit_event(RoleRevoked {
+                role,
+                account,
+                sender,
+            });
+            Ok(())
+        }
+
+        fn ensure_role(&self, role: RoleType, account: AccountId) -> Result<()> {
+            if !self.has_role(role, account) {
+                return Err(Error::MissingRole);
+            }
+            Ok(())
+        }
+
+        /// Stops event creation and ticket sales. Refunds, revenue claims and fee
+        /// withdrawals keep working so no funds get stuck while an incident is handled.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            let account = self.env().caller();
+            self.ensure_pauser(account)?;
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            self.paused = true;
+
+            self.env().emit_event(Paused { account });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            let account = self.env().caller();
+            self.ensure_pauser(account)?;
+            if !self.paused {
+                return Err(Error::NotPaused);
+            }
+            self.paused = false;
+
+            self.env().emit_event(Unpaused { account });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        fn ensure_pauser(&self, account: AccountId) -> Result<()> {
+            if self.owner == Some(account) {
+                return Ok(());
+            }
+            self.ensure_role(PAUSER, account)
+        }
+
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn set_platform_fee(&mut self, fee_bps: u16) -> Result<()> {
+            self.ensure_role(FEE_MANAGER, self.env().caller())?;
+            if fee_bps > MAX_BPS {
+                return Err(Error::InvalidFee);
+            }
+            let old_fee_bps = self.platform_fee_bps;
+            self.platform_fee_bps = fee_bps;
+
+            self.env().emit_event(PlatformFeeUpdated {
+                old_fee_bps,
+                new_fee_bps: fee_bps,
+            });
+            Ok(())
+        }
+
+        /// Points USD-priced events at `oracle`'s `pair` feed.
+        #[ink(message)]
+        pub fn set_price_oracle(&mut self, config: OracleConfig) -> Result<()> {
+            self.ensure_role(DEFAULT_ADMIN, self.env().caller())?;
+            if config.slippage_bps > MAX_BPS {
+                return Err(Error::InvalidSlippage);
+            }
+            let oracle = config.oracle;
+            self.price_oracle = Some(config);
+
+            self.env().emit_event(PriceOracleUpdated { oracle });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_price_oracle(&self) -> Option<OracleConfig> {
+            self.price_oracle.clone()
+        }
+
+        #[ink(message)]
+        pub fn get_platform_fee(&self) -> u16 {
+            self.platform_fee_bps
+        }
+
+        /// Fees collected from events whose revenue has been released to their hosts.
+        #[ink(message)]
+        pub fn get_platform_fees(&self) -> Balance {
+            self.platform_fees
+        }
+
+        #[ink(message)]
+        pub fn withdraw_platform_fees(&mut self) -> Result<Balance> {
+            let owner = self.env().caller();
+            self.ensure_role(DEFAULT_ADMIN, owner)?;
+            let amount = self.platform_fees;
+            if amount == 0 {
+                return Err(Error::NothingToWithdraw);
+            }
+
+            self.platform_fees = 0;
+            self.env().transfer(owner, amount).map_err(|_| Error::TransferFailed)?;
+
+            self.env().emit_event(PlatformFeesWithdrawn { to: owner, amount });
+            Ok(amount)
+        }
+
+        #[ink(message)]
+        pub fn get_token_fees(&self, token: AccountId) -> Balance {
+            self.token_fees.get(token).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn withdraw_token_fees(&mut self, token: AccountId) -> Result<Balance> {
+            let owner = self.env().caller();
+            self.ensure_role(DEFAULT_ADMIN, owner)?;
+            let amount = self.get_token_fees(token);
+            if amount == 0 {
+                return Err(Error::NothingToWithdraw);
+            }
+
+            self.token_fees.remove(token);
+            self.transfer_tokens(token, owner, amount).map_err(|_| Error::TokenTransferFailed)?;
+
+            self.env().emit_event(TokenFeesWithdrawn { token, to: owner, amount });
+            Ok(amount)
+        }
+
+        #[ink(message)]
+        pub fn set_ticket_nft_code_hash(&mut self, code_hash: Hash) -> Result<()> {
+            self.ensure_role(DEFAULT_ADMIN, self.env().caller())?;
+            self.ticket_nft_code_hash = Some(code_hash);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_ticket_nft_code_hash(&self) -> Option<Hash> {
+            self.ticket_nft_code_hash
+        }
+
+        /// Opens a collection the manager mints on to hosts as a home for their events, so
+        /// they needn't each deploy one. It must declare the BlockPass ticket interface and
+        /// have been offered to the manager with `transfer_ownership`: the manager adopts
+        /// it as owner and observer, as refunds burn tickets and transfers move records.
+        #[ink(message)]
+        pub fn add_shared_collection(&mut self, collection: AccountId) -> Result<()> {
+            self.ensure_role(DEFAULT_ADMIN, self.env().caller())?;
+            if !Self::supports_interface(collection, ticket_nft::TICKET_MINTER_INTERFACE_ID) {
+                return Err(Error::UnsupportedCollection);
+            }
+            Self::adopt_collection(collection).map_err(Error::CollectionUpdateFailed)?;
+            self.shared_collections.insert(collection, &());
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_shared_collection(&self, collection: AccountId) -> bool {
+            self.shared_collections.contains(collection)
+        }
+
+        /// Creates the event in its own scope of a shared collection rather than a fresh
+        /// one. Settings that apply to the whole collection, like soulbinding or resale
+        /// caps, aren't available to such events.
+        #[ink(message)]
+        pub fn create_shared_event(
+            &mut self,
+            details: EventDetails,
+            collection: AccountId
+        ) -> Result<u64> {
+            if !self.is_shared_collection(collection) {
+                return Err(Error::NotSharedCollection);
+            }
+            let name = details.title.clone();
+            let event_id = self.register_event(details, collection)?;
+            Self::create_event_collection(collection, event_id, name)
+                .map_err(Error::CollectionUpdateFailed)?;
+            Ok(event_id)
+        }
+
+        fn ensure_own_collection(&self, event: &Event) -> Result<()> {
+            if self.is_shared_collection(event.ticket_nft_address) {
+                return Err(Error::SharedCollection);
+            }
+            Ok(())
+        }
+
+        /// Instantiates a fresh `TicketNFT` from the stored code hash and creates the event
+        /// against it. The manager is the instantiator, so it becomes the collection's owner
+        /// and the only account able to mint tickets.
+        #[ink(message)]
+        pub fn create_event(
+            &mut self,
+            details: EventDetails,
+            name: String,
+            symbol: String
+        ) -> Result<(u64, AccountId)> {
+            self.ensure_not_paused()?;
+            let code_hash = self.ticket_nft_code_hash.ok_or(Error::CodeHashNotSet)?;
+            // Don't leave an orphaned collection behind for details we'd reject anyway
+            Self::validate_details(&details, self.env().block_timestamp())?;
+
+            let ticket_nft = match TicketNFTRef::new(name, symbol)
+                .code_hash(code_hash)
+                .endowment(0)
+                .salt_bytes(self.next_event_id.to_le_bytes())
+                .try_instantiate()
+            {
+                Ok(Ok(ticket_nft)) => ticket_nft,
+                _ => return Err(Error::InstantiationFailed),
+            };
+            let ticket_nft_address = ticket_nft.to_account_id();
+
+            let event_id = self.register_event(details, ticket_nft_address)?;
+            Ok((event_id, ticket_nft_address))
+        }
+
+        /// Records a new event backed by the collection at `ticket_nft_address`.
+        fn register_event(
+            &mut self,
+            mut details: EventDetails,
+            ticket_nft_address: AccountId
+        ) -> Result<u64> {
+            self.ensure_not_paused()?;
+            Self::validate_details(&details, self.env().block_timestamp())?;
+            for tier in details.tiers.iter_mut() {
+                tier.sold = 0;
+            }
+
+            let event_id = self.next_event_id;
+            self.next_event_id = event_id.checked_add(1).ok_or(Error::Overflow)?;
+            let host = self.env().caller();
+
+            self.index_discovery(event_id, &details);
+            self.stats.events_created = self.stats.events_created.saturating_add(1);
+            let mut collection_events = self.collection_events
+                .get(ticket_nft_address)
+                .unwrap_or_default();
+            collection_events.push(event_id);
+            self.collection_events.insert(ticket_nft_address, &collection_events);
+            let event = Event {
+                event_id,
+                details,
+                ticket_nft_address,
+                attendee_count: 0,
+                tickets_sold: 0,
+                status: EventStatus::Active,
+                host,
+                revenue: 0,
+                total_revenue: 0,
+                platform_fees: 0,
+                payment_token: None,
+                payment_currency: None,
+                funding_confirmed: false,
+                referral_bps: 0,
+                referral_owed: 0,
+                referred_tickets: 0,
+                comp_allocation: 0,
+                comps_issued: 0,
+                invite_only: false,
+                resale_cap_bps: None,
+                royalty_bps: 0,
+                transferable: true,
+                transfer_lock: None,
+            };
+
+            self.events.insert(event_id, &event);
+            let mut hosted = self.host_events.get(host).unwrap_or_default();
+            hosted.push(event_id);
+            self.host_events.insert(host, &hosted);
+            self.list_event(event_id);
+            self.env().emit_event(EventCreated {
+                event_id,
+                host,
+                ticket_nft_address,
+            });
+            Ok(event_id)
+        }
+
+        /// Lets the host correct an event. Pricing and capacity are frozen once the first
+        /// ticket has sold; everything else can change until the sales window closes.
+        /// Operators may edit the non-financial fields only.
+        #[ink(message)]
+        pub fn update_event_details(
+            &mut self,
+            event_id: u64,
+            mut new_details: EventDetails
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+            Self::validate_details(&new_details, now)?;
+
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            let is_host = event.host == caller;
+            if !is_host && !self.is_event_operator(event_id, caller) {
+                return Err(Error::NotHost);
+            }
+            if now > event.details.sales_end {
+                return Err(Error::SalesEnded);
+            }
+
+            let old = &event.details;
+            let same_tiers = old.tiers.len() == new_details.tiers.len() &&
+                old.tiers
+                    .iter()
+                    .zip(new_details.tiers.iter())
+                    .all(|(a, b)| a.price == b.price && a.max == b.max);
+            let pricing_changed = !same_tiers ||
+                old.price_schedule != new_details.price_schedule ||
+                old.dutch_auction != new_details.dutch_auction ||
+                old.max_tickets_per_account != new_details.max_tickets_per_account ||
+                old.usd_priced != new_details.usd_priced ||
+                old.refund_policy != new_details.refund_policy ||
+                old.funding_goal != new_details.funding_goal ||
+                old.payees != new_details.payees;
+            if !is_host && pricing_changed {
+                return Err(Error::NotHost);
+            }
+            if event.tickets_sold > 0 && pricing_changed {
+                return Err(Error::SalesStarted);
+            }
+
+            // Keep sold counts; pricing is unchanged whenever any tier has sales
+            for (tier, old_tier) in new_details.tiers.iter_mut().zip(old.tiers.iter()) {
+                tier.sold = old_tier.sold;
+            }
+            for tier in new_details.tiers.iter_mut().skip(old.tiers.len()) {
+                tier.sold = 0;
+            }
+
+            let old_details = old.clone();
+            self.unindex_discovery(event_id, &old_details);
+            self.index_discovery(event_id, &new_details);
+            event.details = new_details;
+            self.events.insert(event_id, &event);
+            // Keep the collection's face values in line with the new tier prices
+            if !same_tiers && event.resale_cap_bps.is_some() {
+                self.sync_resale_cap(&event)?;
+            }
+
+            self.env().emit_event(EventUpdated {
+                event_id,
+                updated_by: caller,
+            });
+            Ok(())
+        }
+
+        /// Grants `operator` staff rights on the event: checking in attendees and editing
+        /// non-financial details. Funds and cancellation remain with the host.
+        #[ink(message)]
+        pub fn add_event_operator(&mut self, event_id: u64, operator: AccountId) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            self.event_operators.insert((event_id, operator), &());
+
+            self.env().emit_event(OperatorAdded { event_id, operator });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_event_operator(&mut self, event_id: u64, operator: AccountId) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if !self.is_event_operator(event_id, operator) {
+                return Err(Error::NotOperator);
+            }
+            self.event_operators.remove((event_id, operator));
+
+            self.env().emit_event(OperatorRemoved { event_id, operator });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_event_operator(&self, event_id: u64, account: AccountId) -> bool {
+            self.event_operators.contains((event_id, account))
+        }
+
+        /// Lets `verifier` check attendees in at the door.
+        #[ink(message)]
+        pub fn add_event_verifier(&mut self, event_id: u64, verifier: AccountId) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            self.event_verifiers.insert((event_id, verifier), &());
+
+            self.env().emit_event(VerifierAdded { event_id, verifier });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_event_verifier(&mut self, event_id: u64, verifier: AccountId) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if !self.is_event_verifier(event_id, verifier) {
+                return Err(Error::NotVerifier);
+            }
+            self.event_verifiers.remove((event_id, verifier));
+
+            self.env().emit_event(VerifierRemoved { event_id, verifier });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_event_verifier(&self, event_id: u64, account: AccountId) -> bool {
+            self.event_verifiers.contains((event_id, account))
+        }
+
+        /// Uses one of `attendee`'s valid tickets for entry and returns its token id. The
+        /// host, operators and verifiers of the event may check attendees in.
+        #[ink(message)]
+        pub fn check_in(&mut self, event_id: u64, attendee: AccountId) -> Result<u64> {
+            self.ensure_door_staff(event_id)?;
+            self.admit(event_id, attendee)
+        }
+
+        /// Checks in the attendee who showed `signature` at the gate, e.g. as a QR code. The
+        /// attendee signs `check_in_challenge` with the ECDSA key behind their account, so a
+        /// copied code is useless to anyone else and stops working once it has been used.
+        #[ink(message)]
+        pub fn check_in_with_signature(
+            &mut self,
+            event_id: u64,
+            attendee: AccountId,
+            signature: [u8; 65]
+        ) -> Result<u64> {
+            self.ensure_door_staff(event_id)?;
+            let challenge = self.check_in_challenge(event_id, attendee);
+            let public_key = self
+                .env()
+                .ecdsa_recover(&signature, &challenge)
+                .map_err(|_| Error::InvalidSignature)?;
+            // Substrate derives ECDSA account ids as the blake2x256 of the compressed key
+            if AccountId::from(Self::hash(&public_key)) != attendee {
+                return Err(Error::InvalidSignature);
+            }
+
+            let token_id = self.admit(event_id, attendee)?;
+            let nonce = self.check_in_nonce(event_id, attendee);
+            let next_nonce = nonce.checked_add(1).ok_or(Error::Overflow)?;
+            self.check_in_nonces.insert((event_id, attendee), &next_nonce);
+            Ok(token_id)
+        }
+
+        /// The 32-byte hash `attendee` signs for their next signed check-in. ink! can't read
+        /// block hashes, so freshness comes from the nonce rather than a recent block.
+        #[ink(message)]
+        pub fn check_in_challenge(&self, event_id: u64, attendee: AccountId) -> [u8; 32] {
+            use ink::scale::Encode;
+            let nonce = self.check_in_nonce(event_id, attendee);
+            Self::hash(&(self.env().account_id(), event_id, attendee, nonce).encode())
+        }
+
+        #[ink(message)]
+        pub fn check_in_nonce(&self, event_id: u64, attendee: AccountId) -> u64 {
+            self.check_in_nonces.get((event_id, attendee)).unwrap_or(0)
+        }
+
+        fn ensure_door_staff(&self, event_id: u64) -> Result<()> {
+            let staff = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            let is_staff = event.host == staff ||
+            self.is_event_operator(event_id, staff) ||
+            self.is_event_verifier(event_id, staff);
+            if !is_staff {
+                return Err(Error::NotVerifier);
+            }
+            Ok(())
+        }
+
+        /// Marks `attendee`'s first valid, unused ticket as checked in.
+        fn admit(&mut self, event_id: u64, attendee: AccountId) -> Result<u64> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+
+            let token_ids = self.purchases
+                .get((event_id, attendee))
+                .map(|purchase| purchase.token_ids)
+                .unwrap_or_default();
+            let mut valid = token_ids
+                .into_iter()
+                .filter(|token_id| !self.is_ticket_invalidated(event_id, *token_id))
+                .peekable();
+            if valid.peek().is_none() {
+                return Err(Error::NoValidTicket);
+            }
+            let token_id = valid
+                .find(|token_id| !self.check_ins.contains((event_id, *token_id)))
+                .ok_or(Error::AlreadyCheckedIn)?;
+
+            let timestamp = self.env().block_timestamp();
+            let checked_in = self.checked_in_count(event_id)
+                .checked_add(1)
+                .ok_or(Error::Overflow)?;
+            // The collection keeps its own record, so the NFT can't get in twice either. It
+            // goes first because it's the check that can refuse an expired ticket.
+            Self::redeem_ticket(event.ticket_nft_address, token_id).map_err(
+                Error::RedeemFailed
+            )?;
+            self.check_ins.insert((event_id, token_id), &timestamp);
+            self.checked_in_counts.insert(event_id, &checked_in);
+
+            self.env().emit_event(CheckedIn {
+                event_id,
+                attendee,
+                token_id,
+                checked_in_by: self.env().caller(),
+                timestamp,
+            });
+            Ok(token_id)
+        }
+
+        /// Whether `account` has been admitted with at least one of their tickets.
+        #[ink(message)]
+        pub fn is_checked_in(&self, event_id: u64, account: AccountId) -> bool {
+            self.purchases.get((event_id, account)).map_or(false, |purchase| {
+                purchase.token_ids
+                    .iter()
+                    .any(|token_id| self.check_ins.contains((event_id, *token_id)))
+            })
+        }
+
+        #[ink(message)]
+        pub fn get_check_in_time(&self, event_id: u64, token_id: u64) -> Option<Timestamp> {
+            self.check_ins.get((event_id, token_id))
+        }
+
+        #[ink(message)]
+        pub fn checked_in_count(&self, event_id: u64) -> u64 {
+            self.checked_in_counts.get(event_id).unwrap_or(0)
+        }
+
+        /// Mints the caller a non-transferable proof-of-attendance badge on the event's
+        /// collection, once they have been checked in. One badge per account and event.
+        #[ink(message)]
+        pub fn claim_attendance_badge(&mut self, event_id: u64) -> Result<u64> {
+            let attendee = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if !self.is_checked_in(event_id, attendee) {
+                return Err(Error::NotCheckedIn);
+            }
+            if self.attendance_badges.contains((event_id, attendee)) {
+                return Err(Error::BadgeAlreadyClaimed);
+            }
+            let count = self.badge_counts.get(attendee).unwrap_or(0);
+            let count_after = count.checked_add(1).ok_or(Error::Overflow)?;
+
+            let token_id = Self::mint_badge(event.ticket_nft_address, attendee)
+                .map_err(Error::MintFailed)?;
+            self.attendance_badges.insert((event_id, attendee), &token_id);
+            self.account_badges.insert((attendee, count), &event_id);
+            self.badge_counts.insert(attendee, &count_after);
+
+            self.env().emit_event(AttendanceBadgeClaimed { event_id, attendee, token_id });
+            Ok(token_id)
+        }
+
+        #[ink(message)]
+        pub fn get_attendance_badge(&self, event_id: u64, account: AccountId) -> Option<u64> {
+            self.attendance_badges.get((event_id, account))
+        }
+
+        /// Events `account` holds an attendance badge for, in the order they were claimed,
+        /// `limit` at a time (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_attended_events(&self, account: AccountId, offset: u64, limit: u32) -> Vec<u64> {
+            let count = self.badge_counts.get(account).unwrap_or(0);
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE) as u64).min(count);
+            (offset..end).filter_map(|index| self.account_badges.get((account, index))).collect()
+        }
+
+        fn validate_details(details: &EventDetails, now: Timestamp) -> Result<()> {
+            if details.tiers.is_empty() {
+                return Err(Error::NoTiers);
+            }
+            if details.sales_start > details.sales_end {
+                return Err(Error::InvalidSalesWindow);
+            }
+            if details.date <= now {
+                return Err(Error::EventInPast);
+            }
+            let schedule = &details.price_schedule;
+            if schedule.windows(2).any(|pair| pair[0].until_sold >= pair[1].until_sold) {
+                return Err(Error::InvalidPriceSchedule);
+            }
+            if let Some(auction) = details.dutch_auction {
+                if auction.start_bps < auction.floor_bps || !schedule.is_empty() {
+                    return Err(Error::InvalidAuction);
+                }
+            }
+            if details.tags.len() > MAX_TAGS {
+                return Err(Error::TooManyTags);
+            }
+            if let Some(goal) = details.funding_goal {
+                let reachable = goal.min_tickets > 0 && goal.min_tickets <= details.max_tickets();
+                if !reachable || goal.deadline > details.date {
+                    return Err(Error::InvalidFundingGoal);
+                }
+            }
+            let payees = &details.payees;
+            if !payees.is_empty() {
+                let total: u32 = payees.iter().map(|p| p.share_bps as u32).sum();
+                let duplicate = payees
+                    .iter()
+                    .enumerate()
+                    .any(|(i, p)| payees[..i].iter().any(|q| q.account == p.account));
+                let zero_share = payees.iter().any(|p| p.share_bps == 0);
+                if payees.len() > MAX_PAYEES || total != MAX_BPS as u32 || duplicate || zero_share {
+                    return Err(Error::InvalidPayees);
+                }
+            }
+            let policy = &details.refund_policy;
+            if policy.len() > MAX_REFUND_TIERS || policy.iter().any(|t| t.refund_bps > MAX_BPS) {
+                return Err(Error::InvalidRefundPolicy);
+            }
+            Ok(())
+        }
+
+        fn index_discovery(&mut self, event_id: u64, details: &EventDetails) {
+            let mut in_category = self.category_events.get(details.category).unwrap_or_default();
+            in_category.push(event_id);
+            self.category_events.insert(details.category, &in_category);
+            for tag in details.tags.iter() {
+                let mut tagged = self.tag_events.get(tag).unwrap_or_default();
+                if !tagged.contains(&event_id) {
+                    tagged.push(event_id);
+                    self.tag_events.insert(tag, &tagged);
+                }
+            }
+        }
+
+        fn unindex_discovery(&mut self, event_id: u64, details: &EventDetails) {
+            if let Some(mut ids) = self.category_events.get(details.category) {
+                ids.retain(|id| *id != event_id);
+                self.category_events.insert(details.category, &ids);
+            }
+            for tag in details.tags.iter() {
+                if let Some(mut ids) = self.tag_events.get(tag) {
+                    ids.retain(|id| *id != event_id);
+                    self.tag_events.insert(tag, &ids);
+                }
+            }
+        }
+
+        /// Adds the event to the discovery list of events with tickets on sale.
+        fn list_event(&mut self, event_id: u64) {
+            let mut active = self.active_events.get().unwrap_or_default();
+            if !active.contains(&event_id) {
+                active.push(event_id);
+                self.active_events.set(&active);
+            }
+        }
+
+        fn unlist_event(&mut self, event_id: u64) {
+            let mut active = self.active_events.get().unwrap_or_default();
+            active.retain(|id| *id != event_id);
+            self.active_events.set(&active);
+        }
+
+        /// Buys one ticket of `tier`. The collection records the event and tier on-chain,
+        /// so `token_uri` is only for off-chain artwork and may be empty.
+        #[ink(message, payable)]
+        pub fn purchase_ticket(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            token_uri: String
+        ) -> Result<()> {
+            self.purchase(event_id, tier, vec![token_uri], PurchaseOptions::default()).map(|_| ())
+        }
+
+        /// Buys a ticket for an event priced in a PSP22 asset. The caller must have approved
+        /// this contract for at least the price, which is pulled with `transfer_from`.
+        #[ink(message)]
+        pub fn purchase_ticket_with_token(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            token_uri: String
+        ) -> Result<()> {
+            let options = PurchaseOptions {
+                pay_with_token: true,
+                ..Default::default()
+            };
+            self.purchase(event_id, tier, vec![token_uri], options).map(|_| ())
+        }
+
+        /// Prices the event in a PSP22 token, described by its `CurrencyInfo` for
+        /// frontends, or back in the native currency with `None`. Only possible before the
+        /// first sale, since refunds go out in the same asset.
+        #[ink(message)]
+        pub fn set_payment_token(
+            &mut self,
+            event_id: u64,
+            payment: Option<(AccountId, CurrencyInfo)>
+        ) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if event.tickets_sold > 0 || event.total_revenue > 0 {
+                return Err(Error::SalesStarted);
+            }
+            let token = payment.as_ref().map(|(token, _)| *token);
+            event.payment_token = token;
+            event.payment_currency = payment.map(|(_, currency)| currency);
+            self.events.insert(event_id, &event);
+
+            self.env().emit_event(PaymentTokenSet { event_id, token });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_payment_token(&self, event_id: u64) -> Option<AccountId> {
+            self.events.get(event_id).and_then(|e| e.payment_token)
+        }
+
+        /// Buys a ticket during the event's allowlist pre-sale, proving membership with the
+        /// Merkle `proof` for the caller's account.
+        #[ink(message, payable)]
+        pub fn purchase_ticket_with_proof(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            token_uri: String,
+            proof: Vec<[u8; 32]>
+        ) -> Result<()> {
+            let options = PurchaseOptions {
+                proof,
+                ..Default::default()
+            };
+            self.purchase(event_id, tier, vec![token_uri], options).map(|_| ())
+        }
+
+        /// Buys a ticket at the discount granted by the promo `code`.
+        #[ink(message, payable)]
+        pub fn purchase_ticket_with_code(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            token_uri: String,
+            code: String
+        ) -> Result<()> {
+            let options = PurchaseOptions {
+                promo_code: Some(Self::hash(code.as_bytes())),
+                ..Default::default()
+            };
+            self.purchase(event_id, tier, vec![token_uri], options).map(|_| ())
+        }
+
+        /// Buys a ticket through a referral `code`, crediting its owner with the event's
+        /// referral commission.
+        #[ink(message, payable)]
+        pub fn purchase_ticket_with_referral(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            token_uri: String,
+            code: String
+        ) -> Result<()> {
+            let options = PurchaseOptions {
+                referral_code: Some(Self::hash(code.as_bytes())),
+                ..Default::default()
+            };
+            self.purchase(event_id, tier, vec![token_uri], options).map(|_| ())
+        }
+
+        /// Buys a ticket as a gift: the caller pays, `recipient` gets the NFT and is the
+        /// attendee. Refunds go to the ticket's holder, as with any other ticket.
+        #[ink(message, payable)]
+        pub fn purchase_ticket_for(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            token_uri: String,
+            recipient: AccountId
+        ) -> Result<()> {
+            let options = PurchaseOptions {
+                recipient: Some(recipient),
+                ..Default::default()
+            };
+            self.purchase(event_id, tier, vec![token_uri], options).map(|_| ())
+        }
+
+        /// Buys a ticket for an invite-only event. `signature` is the host's ECDSA signature
+        /// over `invite_payload(event_id, caller, expiry)`, handed to the invitee off-chain.
+        #[ink(message, payable)]
+        pub fn purchase_ticket_with_invite(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            token_uri: String,
+            expiry: Timestamp,
+            signature: [u8; 65]
+        ) -> Result<()> {
+            let options = PurchaseOptions {
+                invite: Some((expiry, signature)),
+                ..Default::default()
+            };
+            self.purchase(event_id, tier, vec![token_uri], options).map(|_| ())
+        }
+
+        /// Makes purchases require a host-signed invite voucher, or lifts the requirement.
+        #[ink(message)]
+        pub fn set_invite_only(&mut self, event_id: u64, invite_only: bool) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            event.invite_only = invite_only;
+            self.events.insert(event_id, &event);
+
+            self.env().emit_event(InviteOnlySet { event_id, invite_only });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_invite_only(&self, event_id: u64) -> Option<bool> {
+            self.events.get(event_id).map(|e| e.invite_only)
+        }
+
+        /// Bans `account` from buying or trading tickets to any event. Trading is blocked
+        /// on the collection, so this also keeps them out of the marketplace.
+        #[ink(message)]
+        pub fn ban_account(&mut self, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            self.ensure_role(DEFAULT_ADMIN, sender)?;
+            if self.platform_bans.contains(account) {
+                return Err(Error::AlreadyBanned);
+            }
+            self.platform_bans.insert(account, &());
+
+            self.env().emit_event(AccountBanned { account, event_id: None, sender });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unban_account(&mut self, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            self.ensure_role(DEFAULT_ADMIN, sender)?;
+            if !self.platform_bans.contains(account) {
+                return Err(Error::NotBanned);
+            }
+            self.platform_bans.remove(account);
+
+            self.env().emit_event(AccountUnbanned { account, event_id: None, sender });
+            Ok(())
+        }
+
+        /// Bans `account` from buying or trading the event's tickets. Tickets they already
+        /// hold stay valid at the door, but can't change hands.
+        #[ink(message)]
+        pub fn ban_from_event(&mut self, event_id: u64, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != sender {
+                return Err(Error::NotHost);
+            }
+            if self.event_bans.contains((event_id, account)) {
+                return Err(Error::AlreadyBanned);
+            }
+            self.event_bans.insert((event_id, account), &());
+
+            self.env().emit_event(AccountBanned { account, event_id: Some(event_id), sender });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unban_from_event(&mut self, event_id: u64, account: AccountId) -> Result<()> {
+            let sender = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != sender {
+                return Err(Error::NotHost);
+            }
+            if !self.event_bans.contains((event_id, account)) {
+                return Err(Error::NotBanned);
+            }
+            self.event_bans.remove((event_id, account));
+
+            self.env().emit_event(AccountUnbanned { account, event_id: Some(event_id), sender });
+            Ok(())
+        }
+
+        /// Whether `account` is banned from the event, by its host or platform-wide.
+        #[ink(message)]
+        pub fn is_banned(&self, event_id: u64, account: AccountId) -> bool {
+            self.platform_bans.contains(account) || self.event_bans.contains((event_id, account))
+        }
+
+        /// The 32-byte hash the host signs to invite `invitee` until `expiry`. It also keys
+        /// the voucher's one-time redemption.
+        #[ink(message)]
+        pub fn invite_payload(
+            &self,
+            event_id: u64,
+            invitee: AccountId,
+            expiry: Timestamp
+        ) -> [u8; 32] {
+            use ink::scale::Encode;
+            Self::hash(&(self.env().account_id(), event_id, invitee, expiry).encode())
+        }
+
+        #[ink(message)]
+        pub fn is_invite_used(&self, payload: [u8; 32]) -> bool {
+            self.used_invites.contains(payload)
+        }
+
+        /// Checks an invite voucher for `invitee` and returns its payload, to be marked used
+        /// once the purchase goes through.
+        fn verify_invite(
+            &self,
+            event: &Event,
+            invitee: AccountId,
+            invite: Option<(Timestamp, [u8; 65])>,
+            now: Timestamp
+        ) -> Result<[u8; 32]> {
+            let (expiry, signature) = invite.ok_or(Error::InviteRequired)?;
+            if now > expiry {
+                return Err(Error::InviteExpired);
+            }
+            let payload = self.invite_payload(event.event_id, invitee, expiry);
+            if self.used_invites.contains(payload) {
+                return Err(Error::InviteAlreadyUsed);
+            }
+            let public_key = self
+                .env()
+                .ecdsa_recover(&signature, &payload)
+                .map_err(|_| Error::InvalidSignature)?;
+            if AccountId::from(Self::hash(&public_key)) != event.host {
+                return Err(Error::InvalidSignature);
+            }
+            Ok(payload)
+        }
+
+        /// Mints the ticket a host-signed `voucher` stands for to the caller, for the
+        /// voucher's price in the event's payment asset. `signature` is the host's ECDSA
+        /// signature over `voucher_payload(voucher)`. Vouchers stand in for an invite.
+        #[ink(message, payable)]
+        pub fn redeem_voucher(
+            &mut self,
+            voucher: TicketVoucher,
+            token_uri: String,
+            signature: [u8; 65]
+        ) -> Result<u64> {
+            let event = self.events.get(voucher.event_id).ok_or(Error::EventNotFound)?;
+            if self.env().block_timestamp() > voucher.expiry {
+                return Err(Error::VoucherExpired);
+            }
+            let payload = self.voucher_payload(voucher.clone());
+            if self.used_vouchers.contains(payload) {
+                return Err(Error::VoucherAlreadyUsed);
+            }
+            let public_key = self
+                .env()
+                .ecdsa_recover(&signature, &payload)
+                .map_err(|_| Error::InvalidSignature)?;
+            if AccountId::from(Self::hash(&public_key)) != event.host {
+                return Err(Error::InvalidSignature);
+            }
+
+            let options = PurchaseOptions {
+                pay_with_token: event.payment_token.is_some(),
+                voucher: Some((payload, voucher.price)),
+                ..Default::default()
+            };
+            let token_uris = vec![token_uri];
+            let token_ids = self.purchase(voucher.event_id, voucher.tier, token_uris, options)?;
+            self.env().emit_event(VoucherRedeemed {
+                event_id: voucher.event_id,
+                buyer: self.env().caller(),
+                nonce: voucher.nonce,
+                token_id: token_ids[0],
+            });
+            Ok(token_ids[0])
+        }
+
+        /// The 32-byte hash the host signs to issue `voucher`. It also keys the voucher's
+        /// one-time redemption.
+        #[ink(message)]
+        pub fn voucher_payload(&self, voucher: TicketVoucher) -> [u8; 32] {
+            use ink::scale::Encode;
+            Self::hash(&(self.env().account_id(), voucher).encode())
+        }
+
+        #[ink(message)]
+        pub fn is_voucher_used(&self, payload: [u8; 32]) -> bool {
+            self.used_vouchers.contains(payload)
+        }
+
+        /// Buys `quantity` tickets in one call, minting one NFT per entry of `token_uris`.
+        #[ink(message, payable)]
+        pub fn purchase_tickets(
+            &mut self,
+            event_id: u64,
+            tier: u32,
+            quantity: u32,
+            token_uris: Vec<String>
+        ) -> Result<Vec<u64>> {
+            if quantity == 0 || quantity as usize != token_uris.len() {
+                return Err(Error::InvalidQuantity);
+            }
+            self.purchase(event_id, tier, token_uris, PurchaseOptions::default())
+        }
+
+        /// Caps resales of the event's tickets at `cap_bps` of their tier's face value, e.g.
+        /// 11_000 for 110%, or lifts the cap with `None`. The collection enforces it on
+        /// every sale that declares its price, and markets can read `max_resale_price`.
+        #[ink(message)]
+        pub fn set_resale_cap(&mut self, event_id: u64, cap_bps: Option<u16>) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            self.ensure_own_collection(&event)?;
+            event.resale_cap_bps = cap_bps;
+            self.events.insert(event_id, &event);
+            self.sync_resale_cap(&event)?;
+
+            self.env().emit_event(ResaleCapSet { event_id, cap_bps });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_resale_cap(&self, event_id: u64) -> Option<u16> {
+            self.events.get(event_id).and_then(|e| e.resale_cap_bps)
+        }
+
+        fn sync_resale_cap(&self, event: &Event) -> Result<()> {
+            let face_values = event.details.tiers.iter().map(|tier| tier.price).collect();
+            Self::set_collection_resale_cap(
+                event.ticket_nft_address,
+                event.resale_cap_bps,
+                face_values
+            ).map_err(Error::CollectionUpdateFailed)
+        }
+
+        /// Pays the host `royalty_bps` of every resale of the event's tickets, on any
+        /// marketplace that honours the collection's `royalty_info`. 0 turns it off.
+        #[ink(message)]
+        pub fn set_royalty(&mut self, event_id: u64, royalty_bps: u16) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            self.ensure_own_collection(&event)?;
+            if royalty_bps > MAX_BPS {
+                return Err(Error::InvalidRoyalty);
+            }
+            event.royalty_bps = royalty_bps;
+            self.events.insert(event_id, &event);
+            Self::set_collection_royalty(event.ticket_nft_address, event.host, royalty_bps)
+                .map_err(Error::CollectionUpdateFailed)?;
+
+            self.env().emit_event(RoyaltySet {
+                event_id,
+                beneficiary: event.host,
+                royalty_bps,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_royalty(&self, event_id: u64) -> Option<u16> {
+            self.events.get(event_id).map(|e| e.royalty_bps)
+        }
+
+        /// Makes the event's tickets soulbound with `false`: the collection refuses every
+        /// transfer and approval, so tickets stay with their buyers and can't be scalped.
+        /// Check-in, refunds and burning work as before.
+        #[ink(message)]
+        pub fn set_transferable(&mut self, event_id: u64, transferable: bool) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            self.ensure_own_collection(&event)?;
+            event.transferable = transferable;
+            self.events.insert(event_id, &event);
+            Self::set_collection_transferable(event.ticket_nft_address, transferable)
+                .map_err(Error::CollectionUpdateFailed)?;
+
+            self.env().emit_event(TransferableSet { event_id, transferable });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_transferable(&self, event_id: u64) -> Option<bool> {
+            self.events.get(event_id).map(|e| e.transferable)
+        }
+
+        /// Locks the event's tickets from transfer, and so from resale, until `delay`
+        /// milliseconds after the event, or lifts the lock with `None`. The collection
+        /// counts from the event date each ticket was minted with.
+        #[ink(message)]
+        pub fn set_transfer_lock(&mut self, event_id: u64, delay: Option<Timestamp>) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            self.ensure_own_collection(&event)?;
+            event.transfer_lock = delay;
+            self.events.insert(event_id, &event);
+            Self::set_collection_transfer_lock(event.ticket_nft_address, delay)
+                .map_err(Error::CollectionUpdateFailed)?;
+
+            self.env().emit_event(TransferLockSet { event_id, delay });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_transfer_lock(&self, event_id: u64) -> Option<Timestamp> {
+            self.events.get(event_id).and_then(|e| e.transfer_lock)
+        }
+
+        /// Burns tickets that were never checked in and have expired, clearing them out
+        /// of their holders' wallets. Nothing is refunded.
+        #[ink(message)]
+        pub fn burn_expired_tickets(&mut self, event_id: u64, token_ids: Vec<u64>) -> Result<()> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            let expires_at = event.details.date.saturating_add(TICKET_EXPIRY_GRACE);
+            if self.env().block_timestamp() <= expires_at {
+                return Err(Error::TicketNotExpired);
+            }
+
+            for token_id in token_ids.iter().copied() {
+                let sale = self.ticket_sales
+                    .get((event_id, token_id))
+                    .filter(|_| !self.is_ticket_invalidated(event_id, token_id))
+                    .ok_or(Error::NoValidTicket)?;
+                if self.check_ins.contains((event_id, token_id)) {
+                    return Err(Error::AlreadyCheckedIn);
+                }
+                let holder = self.attendees
+                    .get((event_id, sale.attendee_index))
+                    .ok_or(Error::NoValidTicket)?;
+                self.void_ticket(event_id, holder, token_id);
+                Self::burn_ticket(event.ticket_nft_address, token_id).map_err(Error::BurnFailed)?;
+            }
+
+            self.env().emit_event(ExpiredTicketsBurned { event_id, token_ids });
+            Ok(())
+        }
+
+        /// Burns a stolen or charged-back ticket. Nothing is refunded on-chain; the slot
+        /// returns to sale (or to the next waitlisted account) and a comp's goes back to
+        /// the allocation. `reason_code` is the host's own and only informs indexers.
+        #[ink(message)]
+        pub fn revoke_ticket(
+            &mut self,
+            event_id: u64,
+            token_id: u64,
+            reason_code: u8
+        ) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            let sale = self.ticket_sales
+                .get((event_id, token_id))
+                .filter(|_| !self.is_ticket_invalidated(event_id, token_id))
+                .ok_or(Error::NoValidTicket)?;
+            if self.check_ins.contains((event_id, token_id)) {
+                return Err(Error::AlreadyCheckedIn);
+            }
+            let holder = self.attendees
+                .get((event_id, sale.attendee_index))
+                .ok_or(Error::NoValidTicket)?;
+
+            // The holder no longer paid for it, so a later cancellation doesn't refund it
+            let mut purchase = self.purchases.get((event_id, holder)).unwrap_or_default();
+            purchase.token_ids.retain(|id| *id != token_id);
+            purchase.paid = purchase.paid.saturating_sub(sale.price);
+            purchase.fee = purchase.fee.saturating_sub(sale.fee);
+            self.purchases.insert((event_id, holder), &purchase);
+            self.invalidated_tickets.insert((event_id, token_id), &());
+            self.ticket_sales.remove((event_id, token_id));
+            let held = self.ticket_counts.get((event_id, holder)).unwrap_or(0);
+            self.ticket_counts.insert((event_id, holder), &held.saturating_sub(1));
+            if sale.comp {
+                event.comps_issued -= 1;
+            } else {
+                event.details.tiers[sale.tier as usize].sold -= 1;
+                event.tickets_sold -= 1;
+            }
+            self.remove_attendee(&mut event, sale.attendee_index);
+            self.remove_registration(holder, event_id);
+            self.events.insert(event_id, &event);
+            if !sale.comp {
+                self.list_event(event_id);
+                self.release_slot(event_id, sale.tier);
+            }
+
+            Self::burn_ticket(event.ticket_nft_address, token_id).map_err(Error::BurnFailed)?;
+            self.env().emit_event(TicketRevoked { event_id, holder, token_id, reason_code });
+            Ok(())
+        }
+
+        /// Sets an attribute, e.g. a `seat`, on one of the event's tickets. Wallets read it
+        /// through the collection's `PSP34Metadata::get_attribute`.
+        #[ink(message)]
+        pub fn set_ticket_attribute(
+            &mut self,
+            event_id: u64,
+            token_id: u64,
+            key: Vec<u8>,
+            value: Vec<u8>
+        ) -> Result<()> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if !self.ticket_sales.contains((event_id, token_id)) {
+                return Err(Error::NoValidTicket);
+            }
+            Self::set_collection_attribute(event.ticket_nft_address, token_id, key, value)
+                .map_err(Error::CollectionUpdateFailed)
+        }
+
+        /// Records `seat` in the ticket's on-chain `TicketData`, or clears it with `None`.
+        #[ink(message)]
+        pub fn assign_seat(
+            &mut self,
+            event_id: u64,
+            token_id: u64,
+            seat: Option<u32>
+        ) -> Result<()> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if !self.ticket_sales.contains((event_id, token_id)) {
+                return Err(Error::NoValidTicket);
+            }
+            Self::assign_collection_seat(event.ticket_nft_address, token_id, seat)
+                .map_err(Error::CollectionUpdateFailed)
+        }
+
+        /// Locks every ticket attribute of the event's collection for good, so buyers know
+        /// what they get can't change under them. Meant for when sales open.
+        #[ink(message)]
+        pub fn freeze_ticket_metadata(&mut self, event_id: u64) -> Result<()> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            self.ensure_own_collection(&event)?;
+            Self::freeze_collection_metadata(event.ticket_nft_address)
+                .map_err(Error::CollectionUpdateFailed)
+        }
+
+        /// Sets how many comp tickets the host may hand out, separately from the tiers'
+        /// capacity. It can't drop below the comps already issued.
+        #[ink(message)]
+        pub fn set_comp_allocation(&mut self, event_id: u64, allocation: u64) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if allocation < event.comps_issued {
+                return Err(Error::CompAllocationExceeded);
+            }
+            event.comp_allocation = allocation;
+            self.events.insert(event_id, &event);
+            Ok(())
+        }
+
+        /// The event's comp allocation and how much of it has been issued.
+        #[ink(message)]
+        pub fn get_comp_allocation(&self, event_id: u64) -> Option<(u64, u64)> {
+            self.events.get(event_id).map(|e| (e.comp_allocation, e.comps_issued))
+        }
+
+        /// Mints a free guest-list ticket to each of `recipients`. Comps come out of the
+        /// comp allocation, not the tiers, and stay out of `tickets_sold` and revenue;
+        /// holders are attendees like anyone else and check in the same way.
+        #[ink(message)]
+        pub fn issue_comp_tickets(
+            &mut self,
+            event_id: u64,
+            recipients: Vec<AccountId>
+        ) -> Result<Vec<u64>> {
+            self.ensure_not_paused()?;
+            let now = self.env().block_timestamp();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            if Self::ended(&event, now) {
+                return Err(Error::EventEnded);
+            }
+            if recipients.is_empty() {
+                return Err(Error::InvalidQuantity);
+            }
+            let issued_after = event.comps_issued
+                .checked_add(recipients.len() as u64)
+                .ok_or(Error::Overflow)?;
+            if issued_after > event.comp_allocation {
+                return Err(Error::CompAllocationExceeded);
+            }
+
+            // Consecutive comps for one recipient, e.g. a sponsor's block, mint in one batch
+            let mut batches: Vec<(AccountId, usize)> = Vec::new();
+            for recipient in recipients {
+                match batches.last_mut() {
+                    Some((last, count)) if *last == recipient => {
+                        *count += 1;
+                    }
+                    _ => batches.push((recipient, 1)),
+                }
+            }
+            let mut minted = Vec::new();
+            for (recipient, count) in batches {
+                let token_ids = Self::mint_tickets(
+                    event.ticket_nft_address,
+                    recipient,
+                    vec![String::new(); count],
+                    event_id,
+                    COMP_TIER,
+                    event.details.date
+                ).map_err(Error::MintFailed)?;
+                minted.extend(token_ids.into_iter().map(|token_id| (recipient, token_id)));
+            }
+
+            // A failed mint reverts the call, so the comps are recorded once minted
+            let mut token_ids = Vec::new();
+            for (recipient, token_id) in minted {
+                let attendee_index = event.attendee_count;
+                self.attendees.insert((event_id, attendee_index), &recipient);
+                self.attendee_tickets.insert((event_id, attendee_index), &token_id);
+                event.attendee_count = attendee_index.checked_add(1).ok_or(Error::Overflow)?;
+                self.ticket_sales.insert((event_id, token_id), &TicketSale {
+                    tier: COMP_TIER,
+                    price: 0,
+                    fee: 0,
+                    attendee_index,
+                    referrer: None,
+                    commission: 0,
+                    comp: true,
+                });
+
+                let mut purchase = self.purchases.get((event_id, recipient)).unwrap_or_default();
+                purchase.token_ids.push(token_id);
+                self.purchases.insert((event_id, recipient), &purchase);
+                let held = self.ticket_counts.get((event_id, recipient)).unwrap_or(0);
+                let held = held.checked_add(1).ok_or(Error::Overflow)?;
+                self.ticket_counts.insert((event_id, recipient), &held);
+                self.add_registration(recipient, event_id);
+
+                self.env().emit_event(CompTicketIssued { event_id, recipient, token_id });
+                token_ids.push(token_id);
+            }
+            event.comps_issued = issued_after;
+            self.events.insert(event_id, &event);
+            Ok(token_ids)
+        }
+
+        /// Shared body of every purchase entry point, guarded against reentrancy.
+        fn purchase(
+            &mut self,
+            event_id: u64,
+            tier_index: u32,
+            token_uris: Vec<String>,
+            options: PurchaseOptions
+        ) -> Result<Vec<u64>> {
+            self.ensure_not_paused()?;
+            if self.locked {
+                return Err(Error::Reentrancy);
+            }
+            self.locked = true;
+            let result = self.try_purchase(event_id, tier_index, token_uris, options);
+            self.locked = false;
+            result
+        }
+
+        /// Checks, then effects, then interactions. A failed mint or refund returns `Err`,
+        /// which reverts the whole call, effects included.
+        fn try_purchase(
+            &mut self,
+            event_id: u64,
+            tier_index: u32,
+            token_uris: Vec<String>,
+            options: PurchaseOptions
+        ) -> Result<Vec<u64>> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+
+            let caller = self.env().caller();
+            // Pays for the tickets; everything about holding them is recorded for `holder`
+            let holder = options.recipient.unwrap_or(caller);
+            let payment = self.env().transferred_value();
+            let now = self.env().block_timestamp();
+            let fee_bps = self.platform_fee_bps;
+            let quantity = token_uris.len() as u64;
+
+            if event.status != EventStatus::Active {
+                return Err(Error::EventInactive);
+            }
+            if self.is_banned(event_id, caller) || self.is_banned(event_id, holder) {
+                return Err(Error::Banned);
+            }
+            if event.payment_token.is_some() != options.pay_with_token {
+                return Err(Error::WrongPaymentAsset);
+            }
+            // The oracle quotes the native currency only
+            if event.details.usd_priced && event.payment_token.is_some() {
+                return Err(Error::WrongPaymentAsset);
+            }
+            if now < event.details.sales_start {
+                return Err(Error::SalesNotStarted);
+            }
+            if now > event.details.sales_end {
+                return Err(Error::SalesEnded);
+            }
+            if let Some(goal) = event.details.funding_goal {
+                if !event.funding_confirmed && now > goal.deadline {
+                    return Err(Error::FundingDeadlinePassed);
+                }
+            }
+            let invite = match event.invite_only && options.voucher.is_none() {
+                true => Some(self.verify_invite(&event, holder, options.invite, now)?),
+                false => None,
+            };
+            if let Some(presale) = self.presales.get(event_id) {
+                let in_presale = now < presale.public_sale_start;
+                if in_presale && !Self::is_allowlisted(&presale, holder, &options.proof) {
+                    return Err(Error::NotAllowlisted);
+                }
+            }
+            // A forfeited lottery just opens the sale to everyone
+            if let Some(lottery) = self.lotteries.get(event_id).filter(|l| !l.forfeited(now)) {
+                let drawn_at = lottery.drawn_at.ok_or(Error::LotteryNotDrawn)?;
+                let won = self.lottery_entries.get((event_id, holder)).map_or(false, |e| e.won);
+                if now < drawn_at.saturating_add(lottery.claim_window) && !won {
+                    return Err(Error::NotLotteryWinner);
+                }
+            }
+            let limit = event.details.max_tickets_per_account;
+            let tier = event.details.tiers.get(tier_index as usize).ok_or(Error::TierNotFound)?;
+            // Slots held for waitlisted accounts count as taken, except the holder's own
+            let held_slots = self.held_slots.get(event_id);
+            let slots = held_slots.as_deref().unwrap_or(&[]);
+            let is_live = |slot: &&HeldSlot| slot.tier == tier_index && slot.expires_at > now;
+            let own_slot = slots.iter().filter(is_live).any(|slot| slot.account == holder);
+            let held_for_others = slots.iter().filter(is_live).count() as u64 - own_slot as u64;
+            let claimed = tier.sold
+                .checked_add(quantity)
+                .and_then(|n| n.checked_add(held_for_others))
+                .ok_or(Error::Overflow)?;
+            if claimed > tier.max {
+                return Err(Error::SoldOut);
+            }
+            let tier_price = tier.price;
+            let purchase_count = self.purchase_counts.get((event_id, holder));
+            let purchased_after = purchase_count
+                .unwrap_or(0)
+                .checked_add(quantity)
+                .ok_or(Error::Overflow)?;
+            if limit > 0 && purchased_after > limit {
+                return Err(Error::PurchaseLimitExceeded);
+            }
+            let promo = match options.promo_code {
+                Some(code_hash) => {
+                    let promo = self.promo_codes
+                        .get((event_id, code_hash))
+                        .filter(|promo| !promo.revoked)
+                        .ok_or(Error::InvalidPromoCode)?;
+                    if promo.redemptions as u64 + quantity > promo.max_redemptions as u64 {
+                        return Err(Error::PromoCodeExhausted);
+                    }
+                    Some((code_hash, promo))
+                }
+                None => None,
+            };
+            let referrer = match options.referral_code {
+                Some(code_hash) => {
+                    let referrer = self.referral_codes
+                        .get(code_hash)
+                        .ok_or(Error::InvalidReferralCode)?;
+                    if referrer == caller {
+                        return Err(Error::SelfReferral);
+                    }
+                    Some(referrer)
+                }
+                None => None,
+            };
+            let discount_bps = promo.as_ref().map_or(0, |(_, promo)| promo.discount_bps);
+            let tickets_sold_after = event.tickets_sold
+                .checked_add(quantity)
+                .ok_or(Error::Overflow)?;
+            let prices = (event.tickets_sold..tickets_sold_after)
+                .map(|sold| {
+                    Self::price_at(&event.details, tier_price, sold, now).and_then(|price| {
+                        Self::apply_discount(price, discount_bps)
+                    })
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or(Error::Overflow)?;
+            let (prices, tolerance_bps) = match (options.voucher, event.details.usd_priced) {
+                // The host priced the voucher in the payment asset already
+                (Some((_, price)), _) => (vec![price; quantity as usize], 0),
+                (None, true) => {
+                    let (rate, config) = self.native_rate(now)?;
+                    let prices = prices
+                        .iter()
+                        .map(|cents| self.usd_to_native(*cents, rate, config.decimals))
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or(Error::Overflow)?;
+                    (prices, config.slippage_bps)
+                }
+                (None, false) => (prices, 0),
+            };
+            let quoted = prices
+                .iter()
+                .try_fold(0 as Balance, |sum, price| sum.checked_add(*price))
+                .ok_or(Error::Overflow)?;
+            // PSP22 payments pull exactly `cost`, so only native payments can leave change
+            let (cost, change) = match event.payment_token {
+                Some(_) => (quoted, 0),
+                None => Self::settle(quoted, payment, tolerance_bps)?,
+            };
+
+            let fee = prices
+                .iter()
+                .try_fold(0 as Balance, |sum, price| {
+                    let fee = price.checked_mul(fee_bps as Balance)? / MAX_BPS as Balance;
+                    sum.checked_add(fee)
+                })
+                .ok_or(Error::Overflow)?;
+            // A payment accepted within the slippage tolerance pays a proportional fee
+            let fee = match cost < quoted {
+                true => fee.checked_mul(cost).ok_or(Error::Overflow)? / quoted,
+                false => fee,
+            };
+            let net = cost - fee;
+            // Within the totals checked above; a short payment accepted within the slippage
+            // tolerance is spread over the tickets
+            let ticket_prices: Vec<Balance> = prices
+                .iter()
+                .map(|price| match cost < quoted {
+                    true => price * cost / quoted,
+                    false => *price,
+                })
+                .collect();
+            let commission_bps = referrer.map_or(0, |_| event.referral_bps);
+            let commissions: Vec<Balance> = ticket_prices
+                .iter()
+                .map(|price| Self::referral_commission(*price, fee_bps, commission_bps))
+                .collect();
+            let commission: Balance = commissions.iter().sum();
+            let total_revenue = event.total_revenue.checked_add(net).ok_or(Error::Overflow)?;
+            let previous_purchase = self.purchases.get((event_id, holder));
+            let paid = previous_purchase
+                .as_ref()
+                .map_or(0, |p| p.paid)
+                .checked_add(cost)
+                .ok_or(Error::Overflow)?;
+            let ticket_count = self.ticket_counts.get((event_id, holder));
+            let held_after = u32::try_from(quantity)
+                .ok()
+                .and_then(|n| ticket_count.unwrap_or(0).checked_add(n))
+                .ok_or(Error::Overflow)?;
+            let registrations = self.registration_counts.get(holder).unwrap_or(0);
+            let registrations_after = registrations.checked_add(quantity).ok_or(Error::Overflow)?;
+
+            // Pulled up front like native value, which has arrived before the call runs
+            if let Some(token) = event.payment_token {
+                self.transfer_tokens_from(token, caller, cost)
+                    .map_err(|_| Error::TokenTransferFailed)?;
+            }
+
+            let nft_address = event.ticket_nft_address;
+            let event_date = event.details.date;
+            let payment_token = event.payment_token;
+
+            // Bounded by `claimed` above
+            event.details.tiers[tier_index as usize].sold += quantity;
+            if let Some(mut slots) = held_slots {
+                let mut claimed = !own_slot;
+                slots.retain(|slot| {
+                    let mine = slot.account == holder && slot.tier == tier_index;
+                    if mine && !claimed && slot.expires_at > now {
+                        claimed = true;
+                        return false;
+                    }
+                    slot.expires_at > now
+                });
+                self.held_slots.insert(event_id, &slots);
+            }
+            if let Some((code_hash, mut promo)) = promo {
+                promo.redemptions += quantity as u32;
+                self.promo_codes.insert((event_id, code_hash), &promo);
+            }
+            for index in event.attendee_count..event.attendee_count + quantity {
+                self.attendees.insert((event_id, index), &holder);
+            }
+            event.attendee_count += quantity;
+            // Current revenue and fees never exceed the lifetime total checked above
+            event.tickets_sold = tickets_sold_after;
+            event.revenue += net;
+            event.total_revenue = total_revenue;
+            event.platform_fees += fee;
+            if referrer.is_some() {
+                event.referral_owed += commission;
+                event.referred_tickets += quantity;
+            }
+            let sold_out = event.tickets_sold >= event.details.max_tickets();
+            let tickets_sold = event.tickets_sold;
+            let funded = event.details.funding_goal.map_or(false, |goal| {
+                !event.funding_confirmed && tickets_sold >= goal.min_tickets
+            });
+            event.funding_confirmed |= funded;
+            self.events.insert(event_id, &event);
+            if sold_out {
+                self.unlist_event(event_id);
+            }
+
+            for index in registrations..registrations_after {
+                self.user_registered_events.insert((holder, index), &event_id);
+            }
+            self.registration_counts.insert(holder, &registrations_after);
+            self.total_registrations = self.total_registrations
+                .checked_add(quantity)
+                .ok_or(Error::Overflow)?;
+
+            self.purchase_counts.insert((event_id, holder), &purchased_after);
+            self.ticket_counts.insert((event_id, holder), &held_after);
+
+            let mut purchase = previous_purchase.unwrap_or_default();
+            purchase.paid = paid;
+            purchase.fee = purchase.fee.checked_add(fee).ok_or(Error::Overflow)?;
+            self.purchases.insert((event_id, holder), &purchase);
+
+            let token_ids = Self::mint_tickets(
+                nft_address,
+                holder,
+                token_uris,
+                event_id,
+                tier_index,
+                event_date
+            ).map_err(Error::MintFailed)?;
+
+            // Send any overpayment straight back to the buyer
+            if change > 0 {
+                self.env().transfer(caller, change).map_err(|_| Error::TransferFailed)?;
+            }
+            purchase.token_ids.extend(token_ids.iter().copied());
+            self.purchases.insert((event_id, holder), &purchase);
+            if let Some(payload) = invite {
+                self.used_invites.insert(payload, &());
+            }
+            if let Some((payload, _)) = options.voucher {
+                self.used_vouchers.insert(payload, &());
+            }
+            self.stats.tickets_sold = self.stats.tickets_sold.saturating_add(quantity);
+            self.record_sales(event_id, now, quantity);
+            if payment_token.is_none() {
+                self.stats.native_volume = self.stats.native_volume.saturating_add(cost);
+            }
+            if let Some(referrer) = referrer {
+                let earned = self.referral_earnings.get((event_id, referrer)).unwrap_or(0);
+                let earned = earned.checked_add(commission).ok_or(Error::Overflow)?;
+                self.referral_earnings.insert((event_id, referrer), &earned);
+                let referred = self.referral_counts.get((event_id, referrer)).unwrap_or(0);
+                let referred = referred.checked_add(quantity).ok_or(Error::Overflow)?;
+                self.referral_counts.insert((event_id, referrer), &referred);
+            }
+
+            let first_index = event.attendee_count - quantity;
+            let sales = token_ids.iter().zip(ticket_prices.iter().zip(commissions));
+            for (offset, (token_id, (price, commission))) in sales.enumerate() {
+                let attendee_index = first_index + offset as u64;
+                self.attendee_tickets.insert((event_id, attendee_index), token_id);
+                self.ticket_sales.insert((event_id, *token_id), &TicketSale {
+                    tier: tier_index,
+                    price: *price,
+                    fee: price * fee_bps as Balance / MAX_BPS as Balance,
+                    attendee_index,
+                    referrer,
+                    commission,
+                    comp: false,
+                });
+            }
+
+            for (token_id, price) in token_ids.iter().zip(prices) {
+                self.env().emit_event(TicketPurchased {
+                    event_id,
+                    buyer: caller,
+                    token_id: *token_id,
+                    tier: tier_index,
+                    price,
+                });
+                if holder != caller {
+                    self.env().emit_event(TicketPurchasedFor {
+                        event_id,
+                        buyer: caller,
+                        recipient: holder,
+                        token_id: *token_id,
+                    });
+                }
+            }
+            if invite.is_some() {
+                self.env().emit_event(InviteRedeemed { event_id, invitee: holder });
+            }
+            if funded {
+                self.env().emit_event(FundingConfirmed {
+                    event_id,
+                    tickets_sold,
+                });
+            }
+            if sold_out {
+                self.env().emit_event(SoldOut {
+                    event_id,
+                    tickets_sold,
+                });
+            }
+            Ok(token_ids)
+        }
+
+        /// Mints a ticket per URI on `collection` in one call, each recording `event_id`,
+        /// `tier` and `event_date` as its on-chain `TicketData` and expiring
+        /// `TICKET_EXPIRY_GRACE` after the event. Calls go through `TicketMinter`, so any
+        /// collection implementing it works; unit tests can't make cross-contract calls and
+        /// get the `MockTicketNFT` deployed at that address instead.
+        fn mint_tickets(
+            collection: AccountId,
+            recipient: AccountId,
+            token_uris: Vec<String>,
+            event_id: u64,
+            tier: u32,
+            event_date: Timestamp
+        ) -> core::result::Result<Vec<u64>, TicketNftError> {
+            let data = TicketData {
+                event_id,
+                tier,
+                seat: None,
+                // Stamped by the collection
+                issued_at: 0,
+                event_date,
+                expires_at: event_date.checked_add(TICKET_EXPIRY_GRACE),
+            };
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.mint_batch(recipient, token_uris, data)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.mint_batch(recipient, token_uris, data))
+            }
+        }
+
+        fn set_collection_transfer_lock(
+            collection: AccountId,
+            delay: Option<Timestamp>
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.set_transfer_lock(delay)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.set_transfer_lock(delay))
+            }
+        }
+
+        /// Whether `collection` declares `interface_id`. Collections without interface
+        /// detection support nothing.
+        fn supports_interface(collection: AccountId, interface_id: u32) -> bool {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::InterfaceDetection) = collection.into();
+                let supported = ink::codegen::TraitCallBuilder::call_mut(&mut nft)
+                    .supports_interface(interface_id)
+                    .try_invoke();
+                matches!(supported, Ok(Ok(true)))
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.supports_interface(interface_id))
+            }
+        }
+
+        fn adopt_collection(collection: AccountId) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.adopt_collection()
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.adopt_collection())
+            }
+        }
+
+        /// Opens `event_id`'s scope on a shared collection, capped by the manager itself
+        /// rather than the collection.
+        fn create_event_collection(
+            collection: AccountId,
+            event_id: u64,
+            name: String
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.create_collection(event_id, name, None)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.create_collection(event_id, name, None))
+            }
+        }
+
+        fn set_collection_transferable(
+            collection: AccountId,
+            transferable: bool
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.set_transferable(transferable)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.set_transferable(transferable))
+            }
+        }
+
+        fn assign_collection_seat(
+            collection: AccountId,
+            token_id: u64,
+            seat: Option<u32>
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.assign_seat(token_id, seat)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.assign_seat(token_id, seat))
+            }
+        }
+
+        /// Pulls `amount` of PSP22 `token` from `from` into this contract. Like minting, unit
+        /// tests use the `MockPSP22` deployed at `token` instead of a cross-contract call.
+        fn transfer_tokens_from(
+            &self,
+            token: AccountId,
+            from: AccountId,
+            amount: Balance
+        ) -> core::result::Result<(), PSP22Error> {
+            let to = self.env().account_id();
+            #[cfg(not(test))]
+            {
+                let mut token: ink::contract_ref!(crate::PSP22) = token.into();
+                token.transfer_from(from, to, amount, Vec::new())
+            }
+            #[cfg(test)]
+            {
+                mock_psp22::with(token, |psp22| psp22.transfer_from(to, from, to, amount))
+            }
+        }
+
+        /// Sends `amount` of PSP22 `token` held by this contract to `to`.
+        fn transfer_tokens(
+            &self,
+            token: AccountId,
+            to: AccountId,
+            amount: Balance
+        ) -> core::result::Result<(), PSP22Error> {
+            #[cfg(not(test))]
+            {
+                let mut token: ink::contract_ref!(crate::PSP22) = token.into();
+                token.transfer(to, amount, Vec::new())
+            }
+            #[cfg(test)]
+            {
+                let from = self.env().account_id();
+                mock_psp22::with(token, |psp22| psp22.transfer(from, to, amount))
+            }
+        }
+
+        /// Pays out in the event's asset: PSP22 `token` when set, native balance otherwise.
+        fn pay_out(&self, token: Option<AccountId>, to: AccountId, amount: Balance) -> Result<()> {
+            match token {
+                Some(token) => {
+                    self.transfer_tokens(token, to, amount).map_err(|_| Error::TokenTransferFailed)
+                }
+                None => self.env().transfer(to, amount).map_err(|_| Error::TransferFailed),
+            }
+        }
+
+        fn mint_badge(
+            collection: AccountId,
+            recipient: AccountId
+        ) -> core::result::Result<u64, TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.mint_badge(recipient)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.mint_badge(recipient))
+            }
+        }
+
+        fn set_collection_resale_cap(
+            collection: AccountId,
+            cap_bps: Option<u16>,
+            face_values: Vec<Balance>
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.set_resale_cap(cap_bps, face_values)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.set_resale_cap(cap_bps, face_values))
+            }
+        }
+
+        fn set_collection_royalty(
+            collection: AccountId,
+            beneficiary: AccountId,
+            royalty_bps: u16
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.set_royalty(beneficiary, royalty_bps)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.set_royalty(beneficiary, royalty_bps))
+            }
+        }
+
+        fn set_collection_attribute(
+            collection: AccountId,
+            token_id: u64,
+            key: Vec<u8>,
+            value: Vec<u8>
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.set_attribute(token_id, key, value)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.set_attribute(token_id, key, value))
+            }
+        }
+
+        fn freeze_collection_metadata(
+            collection: AccountId
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.freeze_metadata()
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.freeze_metadata())
+            }
+        }
+
+        fn ticket_owner(collection: AccountId, token_id: u64) -> Option<AccountId> {
+            #[cfg(not(test))]
+            {
+                let nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.owner_of(token_id)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.owner_of(token_id))
+            }
+        }
+
+        /// Moves `from`'s ticket to `to` as their approved operator. The collection reports
+        /// the transfer back through `on_ticket_transfer`, so the call must allow reentry.
+        fn move_ticket(
+            &mut self,
+            collection: AccountId,
+            from: AccountId,
+            to: AccountId,
+            token_id: u64
+        ) -> core::result::Result<(), PSP34Error> {
+            #[cfg(not(test))]
+            {
+                let _ = from;
+                let mut nft: ink::contract_ref!(ticket_nft::PSP34) = collection.into();
+                ink::codegen::TraitCallBuilder::call_mut(&mut nft)
+                    .transfer(to, ticket_nft::Id::U64(token_id), Vec::new())
+                    .call_flags(ink::env::CallFlags::default().set_allow_reentry(true))
+                    .invoke()
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.transfer(from, to, token_id))?;
+                // Stand in for the collection's callback
+                let caller = self.env().caller();
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(collection);
+                let observed = self.on_ticket_transfer(from, to, token_id);
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(caller);
+                observed.map_err(|_| PSP34Error::Custom(String::from("ObserverRejected")))
+            }
+        }
+
+        fn recover_nft_ticket(
+            collection: AccountId,
+            token_id: u64,
+            new_owner: AccountId
+        ) -> core::result::Result<u64, TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.recover_ticket(token_id, new_owner)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.recover_ticket(token_id, new_owner))
+            }
+        }
+
+        fn burn_ticket(
+            collection: AccountId,
+            token_id: u64
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.burn(token_id)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.burn(token_id))
+            }
+        }
+
+        fn redeem_ticket(
+            collection: AccountId,
+            token_id: u64
+        ) -> core::result::Result<(), TicketNftError> {
+            #[cfg(not(test))]
+            {
+                let mut nft: ink::contract_ref!(ticket_nft::TicketMinter) = collection.into();
+                nft.redeem(token_id)
+            }
+            #[cfg(test)]
+            {
+                mock_ticket_nft::with(collection, |nft| nft.redeem(token_id))
+            }
+        }
+
+        /// Changes the per-account ticket limit. Only allowed before the first ticket sells.
+        #[ink(message)]
+        pub fn set_max_tickets_per_account(&mut self, event_id: u64, limit: u64) -> Result<()> {
+            let host = self.env().caller();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if event.tickets_sold > 0 {
+                return Err(Error::SalesStarted);
+            }
+            event.details.max_tickets_per_account = limit;
+            self.events.insert(event_id, &event);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_purchase_count(&self, event_id: u64, account: AccountId) -> u64 {
+            self.purchase_counts.get((event_id, account)).unwrap_or(0)
+        }
+
+        /// Restricts sales to the allowlist until `public_sale_start`.
+        #[ink(message)]
+        pub fn set_presale(
+            &mut self,
+            event_id: u64,
+            merkle_root: [u8; 32],
+            public_sale_start: Timestamp
+        ) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+
+            self.presales.insert(event_id, &Presale {
+                merkle_root,
+                public_sale_start,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_presale(&self, event_id: u64) -> Option<Presale> {
+            self.presales.get(event_id)
+        }
+
+        fn is_allowlisted(presale: &Presale, account: AccountId, proof: &[[u8; 32]]) -> bool {
+            let mut node = Self::hash(account.as_ref());
+            for sibling in proof {
+                let mut pair = [0u8; 64];
+                let (first, second) = if node <= *sibling {
+                    (node, *sibling)
+                } else {
+                    (*sibling, node)
+                };
+                pair[..32].copy_from_slice(&first);
+                pair[32..].copy_from_slice(&second);
+                node = Self::hash(&pair);
+            }
+            node == presale.merkle_root
+        }
+
+        fn hash(input: &[u8]) -> [u8; 32] {
+            let mut output = [0u8; 32];
+            ink::env::hash_bytes::<ink::env::hash::Blake2x256>(input, &mut output);
+            output
+        }
+
+        /// Native amount the next ticket in `tier` costs right now; for USD-priced events this
+        /// is `current_price` converted at the oracle's rate.
+        #[ink(message)]
+        pub fn quote_price(&self, event_id: u64, tier: u32) -> Result<Balance> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            let price = self.current_price(event_id, tier).ok_or(Error::TierNotFound)?;
+            if !event.details.usd_priced {
+                return Ok(price);
+            }
+            let (rate, config) = self.native_rate(self.env().block_timestamp())?;
+            self.usd_to_native(price, rate, config.decimals).ok_or(Error::Overflow)
+        }
+
+        /// Price of the next ticket in `tier`, following the event's price schedule or
+        /// Dutch auction.
+        #[ink(message)]
+        pub fn current_price(&self, event_id: u64, tier: u32) -> Option<Balance> {
+            let now = self.env().block_timestamp();
+            let event = self.events.get(event_id)?;
+            let tier = event.details.tiers.get(tier as usize)?;
+            Self::price_at(&event.details, tier.price, event.tickets_sold, now)
+        }
+
+        /// Price of the ticket sold after `sold` others, at time `now`. `None` on overflow.
+        fn price_at(
+            details: &EventDetails,
+            base: Balance,
+            sold: u64,
+            now: Timestamp
+        ) -> Option<Balance> {
+            if let Some(auction) = details.dutch_auction {
+                let duration = details.sales_end.saturating_sub(details.sales_start);
+                let elapsed = now.saturating_sub(details.sales_start).min(duration);
+                let decay = (auction.start_bps - auction.floor_bps) as u128;
+                let bps = match duration {
+                    0 => auction.floor_bps as u128,
+                    _ => auction.start_bps as u128 - decay * elapsed as u128 / duration as u128,
+                };
+                return Some(base.checked_mul(bps)? / MAX_BPS as Balance);
+            }
+
+            match details.price_schedule.iter().find(|step| sold < step.until_sold) {
+                Some(step) => {
+                    Some(base.checked_mul(step.multiplier_bps as Balance)? / MAX_BPS as Balance)
+                }
+                None => Some(base),
+            }
+        }
+
+        /// Puts the event into lottery mode. Must be set before anyone registers.
+        /// `seed_commitment` is `blake2x256(seed)` for the secret `seed` that
+        /// `draw_winners` reveals.
+        #[ink(message)]
+        pub fn set_lottery(
+            &mut self,
+            event_id: u64,
+            registration_end: Timestamp,
+            claim_window: Timestamp,
+            winners: u32,
+            deposit: Balance,
+            seed_commitment: [u8; 32]
+        ) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if self.lottery_entrants.get(event_id).map_or(false, |e| !e.is_empty()) {
+                return Err(Error::LotteryClosed);
+            }
+
+            self.lotteries.insert(event_id, &Lottery {
+                registration_end,
+                claim_window,
+                winners,
+                deposit,
+                drawn_at: None,
+                seed_commitment,
+                entropy: [0u8; 32],
+            });
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        pub fn register_for_lottery(&mut self, event_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+            let payment = self.env().transferred_value();
+            let now = self.env().block_timestamp();
+            let mut lottery = self.lotteries.get(event_id).ok_or(Error::LotteryNotFound)?;
+
+            if now >= lottery.registration_end || lottery.drawn_at.is_some() {
+                return Err(Error::LotteryClosed);
+            }
+            if payment != lottery.deposit {
+                return Err(Error::InsufficientPayment);
+            }
+            if self.lottery_entries.contains((event_id, caller)) {
+                return Err(Error::AlreadyRegistered);
+            }
+
+            self.lottery_entries.insert((event_id, caller), &LotteryEntry {
+                deposit: payment,
+                ..Default::default()
+            });
+            let mut entrants = self.lottery_entrants.get(event_id).unwrap_or_default();
+            entrants.push(caller);
+            self.lottery_entrants.insert(event_id, &entrants);
+            let mut input = [0u8; 64];
+            input[..32].copy_from_slice(&lottery.entropy);
+            input[32..].copy_from_slice(caller.as_ref());
+            lottery.entropy = Self::hash(&input);
+            self.lotteries.insert(event_id, &lottery);
+            Ok(())
+        }
+
+        /// Picks the winners once registration has closed, seeding a partial Fisher-Yates
+        /// shuffle from the revealed `seed` and the entrants. Past `LOTTERY_REVEAL_WINDOW`
+        /// the lottery is forfeited instead.
+        #[ink(message)]
+        pub fn draw_winners(&mut self, event_id: u64, seed: [u8; 32]) -> Result<u32> {
+            let host = self.env().caller();
+            let now = self.env().block_timestamp();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            let mut lottery = self.lotteries.get(event_id).ok_or(Error::LotteryNotFound)?;
+            if lottery.drawn_at.is_some() {
+                return Err(Error::LotteryAlreadyDrawn);
+            }
+            if now < lottery.registration_end {
+                return Err(Error::LotteryClosed);
+            }
+            if lottery.forfeited(now) {
+                return Err(Error::LotteryForfeited);
+            }
+            if Self::hash(&seed) != lottery.seed_commitment {
+                return Err(Error::InvalidSeed);
+            }
+
+            let mut entrants = self.lottery_entrants.get(event_id).unwrap_or_default();
+            let winners = (lottery.winners as usize).min(entrants.len());
+            let mut input = [0u8; 64];
+            input[..32].copy_from_slice(&seed);
+            input[32..].copy_from_slice(&lottery.entropy);
+            let seed = Self::hash(&input);
+            for i in 0..winners {
+                let mut input = [0u8; 40];
+                input[..32].copy_from_slice(seed.as_ref());
+                input[32..].copy_from_slice(&(i as u64).to_le_bytes());
+                let mut draw = [0u8; 8];
+                draw.copy_from_slice(&Self::hash(&input)[..8]);
+                let pick = i + (u64::from_le_bytes(draw) % (entrants.len() - i) as u64) as usize;
+                entrants.swap(i, pick);
+
+                if let Some(mut entry) = self.lottery_entries.get((event_id, entrants[i])) {
+                    entry.won = true;
+                    self.lottery_entries.insert((event_id, entrants[i]), &entry);
+                }
+            }
+            lottery.drawn_at = Some(now);
+            self.lotteries.insert(event_id, &lottery);
+
+            Self::env().emit_event(LotteryDrawn {
+                event_id,
+                winners: winners as u32,
+                entrants: entrants.len() as u32,
+            });
+            Ok(winners as u32)
+        }
+
+        /// Returns an entrant's deposit once the draw has happened, or the lottery was
+        /// forfeited.
+        #[ink(message)]
+        pub fn claim_lottery_deposit(&mut self, event_id: u64) -> Result<Balance> {
+            let caller = self.env().caller();
+            let lottery = self.lotteries.get(event_id).ok_or(Error::LotteryNotFound)?;
+            if lottery.drawn_at.is_none() && !lottery.forfeited(self.env().block_timestamp()) {
+                return Err(Error::LotteryNotDrawn);
+            }
+            let mut entry = match self.lottery_entries.get((event_id, caller)) {
+                Some(e) if !e.deposit_returned && e.deposit > 0 => e,
+                _ => {
+                    return Err(Error::NothingToRefund);
+                }
+            };
+
+            let amount = entry.deposit;
+            entry.deposit_returned = true;
+            self.lottery_entries.insert((event_id, caller), &entry);
+            self.env().transfer(caller, amount).map_err(|_| Error::TransferFailed)?;
+            Ok(amount)
+        }
+
+        #[ink(message)]
+        pub fn get_lottery(&self, event_id: u64) -> Option<Lottery> {
+            self.lotteries.get(event_id)
+        }
+
+        #[ink(message)]
+        pub fn get_lottery_entry(&self, event_id: u64, account: AccountId) -> Option<LotteryEntry> {
+            self.lottery_entries.get((event_id, account))
+        }
+
+        /// Queues the caller for a sold-out event. Slots freed by refunds or revocations
+        /// are offered to the queue in order.
+        #[ink(message)]
+        pub fn join_waitlist(&mut self, event_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.tickets_sold < event.details.max_tickets() {
+                return Err(Error::NotSoldOut);
+            }
+
+            let mut waitlist = self.waitlists.get(event_id).unwrap_or_default();
+            if waitlist.contains(&caller) {
+                return Err(Error::AlreadyWaitlisted);
+            }
+            waitlist.push(caller);
+            self.waitlists.insert(event_id, &waitlist);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn leave_waitlist(&mut self, event_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+            let mut waitlist = self.waitlists.get(event_id).ok_or(Error::NotWaitlisted)?;
+            let position = waitlist
+                .iter()
+                .position(|account| *account == caller)
+                .ok_or(Error::NotWaitlisted)?;
+            waitlist.remove(position);
+            self.waitlists.insert(event_id, &waitlist);
+            Ok(())
+        }
+
+        /// The event's waitlist in queue order, `limit` at a time (capped at
+        /// `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_waitlist(&self, event_id: u64, offset: u64, limit: u32) -> Vec<AccountId> {
+            Self::page(self.waitlists.get(event_id).unwrap_or_default(), offset, limit)
+        }
+
+        #[ink(message)]
+        pub fn waitlist_length(&self, event_id: u64) -> u64 {
+            self.waitlists.get(event_id).map_or(0, |waitlist| waitlist.len() as u64)
+        }
+
+        /// Slots held for waitlisted accounts, expired ones included until they're
+        /// cleared, `limit` at a time (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_held_slots(&self, event_id: u64, offset: u64, limit: u32) -> Vec<HeldSlot> {
+            Self::page(self.held_slots.get(event_id).unwrap_or_default(), offset, limit)
+        }
+
+        /// Up to `limit` items of `items` (capped at `MAX_PAGE_SIZE`) from `offset`.
+        fn page<T>(items: Vec<T>, offset: u64, limit: u32) -> Vec<T> {
+            let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+            items.into_iter().skip(offset).take(limit.min(MAX_PAGE_SIZE) as usize).collect()
+        }
+
+        /// Called once a sold ticket in `tier` has been given back. Reserves the slot for
+        /// the next waitlisted account; with an empty waitlist it returns to general sale.
+        fn release_slot(&mut self, event_id: u64, tier: u32) {
+            let mut waitlist = self.waitlists.get(event_id).unwrap_or_default();
+            if waitlist.is_empty() {
+                return;
+            }
+            let account = waitlist.remove(0);
+            self.waitlists.insert(event_id, &waitlist);
+            let expires_at = self.env().block_timestamp().saturating_add(WAITLIST_CLAIM_WINDOW);
+
+            let mut slots = self.held_slots.get(event_id).unwrap_or_default();
+            slots.push(HeldSlot {
+                account,
+                tier,
+                expires_at,
+            });
+            self.held_slots.insert(event_id, &slots);
+            self.env().emit_event(WaitlistSlotOffered {
+                event_id,
+                account,
+                tier,
+                expires_at,
+            });
+        }
+
+        /// Registers a promo code by its blake2x256 hash so the code itself never appears
+        /// on-chain until a buyer redeems it.
+        #[ink(message)]
+        pub fn add_promo_code(
+            &mut self,
+            event_id: u64,
+            code_hash: [u8; 32],
+            discount_bps: u16,
+            max_redemptions: u32
+        ) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if discount_bps > MAX_BPS {
+                return Err(Error::InvalidPromoCode);
+            }
+
+            self.promo_codes.insert((event_id, code_hash), &PromoCode {
+                discount_bps,
+                max_redemptions,
+                redemptions: 0,
+                revoked: false,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn revoke_promo_code(&mut self, event_id: u64, code_hash: [u8; 32]) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+
+            let mut promo = self.promo_codes
+                .get((event_id, code_hash))
+                .ok_or(Error::InvalidPromoCode)?;
+            promo.revoked = true;
+            self.promo_codes.insert((event_id, code_hash), &promo);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_promo_code(&self, event_id: u64, code_hash: [u8; 32]) -> Option<PromoCode> {
+            self.promo_codes.get((event_id, code_hash))
+        }
+
+        /// Binds the referral `code` to the caller. Codes are first come, first served.
+        #[ink(message)]
+        pub fn register_referral_code(&mut self, code: String) -> Result<()> {
+            let referrer = self.env().caller();
+            let code_hash = Self::hash(code.as_bytes());
+            if self.referral_codes.contains(code_hash) {
+                return Err(Error::ReferralCodeTaken);
+            }
+            self.referral_codes.insert(code_hash, &referrer);
+
+            self.env().emit_event(ReferralCodeRegistered { code_hash, referrer });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_referrer(&self, code_hash: [u8; 32]) -> Option<AccountId> {
+            self.referral_codes.get(code_hash)
+        }
+
+        /// Sets the share of each referred sale's net price its referrer earns. It comes out
+        /// of the host's revenue and applies to sales from now on.
+        #[ink(message)]
+        pub fn set_referral_commission(
+            &mut self,
+            event_id: u64,
+            commission_bps: u16
+        ) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if commission_bps > MAX_BPS {
+                return Err(Error::InvalidCommission);
+            }
+            event.referral_bps = commission_bps;
+            self.events.insert(event_id, &event);
+
+            self.env().emit_event(ReferralCommissionSet { event_id, commission_bps });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_referral_commission(&self, event_id: u64) -> Option<u16> {
+            self.events.get(event_id).map(|e| e.referral_bps)
+        }
+
+        /// Referred tickets and the commission still owed on them, for host analytics.
+        #[ink(message)]
+        pub fn get_referral_stats(&self, event_id: u64) -> Option<(u64, Balance)> {
+            self.events.get(event_id).map(|e| (e.referred_tickets, e.referral_owed))
+        }
+
+        #[ink(message)]
+        pub fn get_referral_count(&self, event_id: u64, referrer: AccountId) -> u64 {
+            self.referral_counts.get((event_id, referrer)).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn get_referral_earnings(&self, event_id: u64, referrer: AccountId) -> Balance {
+            self.referral_earnings.get((event_id, referrer)).unwrap_or(0)
+        }
+
+        /// Pays the caller the commission earned on an event. Like the host's revenue, it
+        /// stays escrowed until the event is over and is forfeited if it is cancelled.
+        #[ink(message)]
+        pub fn withdraw_referral_earnings(&mut self, event_id: u64) -> Result<Balance> {
+            let referrer = self.env().caller();
+            let now = self.env().block_timestamp();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            if !Self::ended(&event, now) {
+                return Err(Error::EventNotEnded);
+            }
+            if event.details.funding_goal.is_some() && !event.funding_confirmed {
+                return Err(Error::FundingNotConfirmed);
+            }
+            let earned = self.referral_earnings.get((event_id, referrer)).unwrap_or(0);
+            let amount = earned.min(event.revenue);
+            if amount == 0 {
+                return Err(Error::NothingToWithdraw);
+            }
+
+            // A failed payout reverts the call, so no rollback is needed
+            self.referral_earnings.remove((event_id, referrer));
+            event.referral_owed = event.referral_owed.saturating_sub(earned);
+            event.revenue -= amount;
+            self.events.insert(event_id, &event);
+            self.pay_out(event.payment_token, referrer, amount)?;
+
+            self.env().emit_event(ReferralEarningsWithdrawn {
+                event_id,
+                referrer,
+                amount,
+            });
+            Ok(amount)
+        }
+
+        /// A referrer's cut of a ticket sold for `price`, taken after the platform fee.
+        fn referral_commission(price: Balance, fee_bps: u16, commission_bps: u16) -> Balance {
+            let net = price - price * fee_bps as Balance / MAX_BPS as Balance;
+            net * commission_bps as Balance / MAX_BPS as Balance
+        }
+
+        fn apply_discount(price: Balance, discount_bps: u16) -> Option<Balance> {
+            Some(price.checked_mul((MAX_BPS - discount_bps) as Balance)? / MAX_BPS as Balance)
+        }
+
+        /// Returns how much of `payment` exceeds `price` and must go back to the buyer.
+        fn change_due(price: Balance, payment: Balance) -> Result<Balance> {
+            payment.checked_sub(price).ok_or(Error::InsufficientPayment)
+        }
+
+        /// Like `change_due`, but a payment up to `tolerance_bps` short of `price` is
+        /// accepted as the full price. Returns `(cost, change)`.
+        fn settle(
+            price: Balance,
+            payment: Balance,
+            tolerance_bps: u16
+        ) -> Result<(Balance, Balance)> {
+            if payment >= price {
+                return Ok((price, payment - price));
+            }
+            let shortfall = price.checked_mul(tolerance_bps as Balance).ok_or(Error::Overflow)? /
+            MAX_BPS as Balance;
+            if payment < price - shortfall {
+                return Err(Error::InsufficientPayment);
+            }
+            Ok((payment, 0))
+        }
+
+        /// The oracle's current native price in USD, with the config it was read under.
+        fn native_rate(&self, now: Timestamp) -> Result<(u128, OracleConfig)> {
+            let config = self.price_oracle.clone().ok_or(Error::OracleNotSet)?;
+            let (rate, updated_at) = self.query_oracle(&config).ok_or(Error::StalePrice)?;
+            if rate == 0 || now.saturating_sub(updated_at) > config.max_age {
+                return Err(Error::StalePrice);
+            }
+            Ok((rate, config))
+        }
+
+        /// Converts US `cents` to native units at `rate` (scaled by `decimals`), rounding up
+        /// so the converted price never undercuts the USD one.
+        fn usd_to_native(&self, cents: Balance, rate: u128, decimals: u8) -> Option<Balance> {
+            let numerator = cents
+                .checked_mul(10u128.checked_pow(self.currency.decimals as u32)?)?
+                .checked_mul(10u128.checked_pow(decimals as u32)?)?;
+            let denominator = rate.checked_mul(10u128.pow(USD_DECIMALS))?;
+            Some(numerator.checked_add(denominator - 1)? / denominator)
+        }
+
+        fn query_oracle(&self, config: &OracleConfig) -> Option<(u128, u64)> {
+            #[cfg(not(test))]
+            {
+                let oracle: ink::contract_ref!(crate::PriceOracle) = config.oracle.into();
+                oracle.latest_price(config.pair.clone())
+            }
+            #[cfg(test)]
+            {
+                mock_price_oracle::latest_price(config.oracle, &config.pair)
+            }
+        }
+
+        #[ink(message)]
+        pub fn deactivate_event(&mut self, event_id: u64) -> Result<()> {
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+
+            let host = self.env().caller();
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            event.status = EventStatus::Inactive;
+            self.events.insert(event_id, &event);
+            self.unlist_event(event_id);
+
+            self.env().emit_event(EventDeactivated { event_id, host });
+            Ok(())
+        }
+
+        /// Resumes sales for a deactivated event, as long as it hasn't taken place yet.
+        #[ink(message)]
+        pub fn reactivate_event(&mut self, event_id: u64) -> Result<()> {
+            let host = self.env().caller();
+            let now = self.env().block_timestamp();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            match event.status {
+                EventStatus::Inactive => {}
+                EventStatus::Cancelled => return Err(Error::EventCancelled),
+                EventStatus::Active => return Err(Error::EventActive),
+            }
+            if Self::ended(&event, now) {
+                return Err(Error::EventEnded);
+            }
+            event.status = EventStatus::Active;
+            self.events.insert(event_id, &event);
+            if event.tickets_sold < event.details.max_tickets() {
+                self.list_event(event_id);
+            }
+
+            self.env().emit_event(EventReactivated { event_id, host });
+            Ok(())
+        }
+
+        /// Nominates `new_host` to take over the event. Nothing changes until they accept;
+        /// proposing again replaces the pending nominee.
+        #[ink(message)]
+        pub fn propose_host_transfer(&mut self, event_id: u64, new_host: AccountId) -> Result<()> {
+            let host = self.env().caller();
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            self.pending_hosts.insert(event_id, &new_host);
+
+            self.env().emit_event(HostTransferProposed {
+                event_id,
+                host,
+                new_host,
+            });
+            Ok(())
+        }
+
+        /// Completes a host transfer. From here on the new host manages the event and
+        /// claims its revenue.
+        #[ink(message)]
+        pub fn accept_host_transfer(&mut self, event_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+            match self.pending_hosts.get(event_id) {
+                None => {
+                    return Err(Error::NoPendingHostTransfer);
+                }
+                Some(pending) if pending != caller => {
+                    return Err(Error::NotPendingHost);
+                }
+                Some(_) => {}
+            }
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            let previous_host = event.host;
+            event.host = caller;
+            self.events.insert(event_id, &event);
+            self.pending_hosts.remove(event_id);
+
+            if let Some(mut ids) = self.host_events.get(previous_host) {
+                ids.retain(|id| *id != event_id);
+                self.host_events.insert(previous_host, &ids);
+            }
+            let mut hosted = self.host_events.get(caller).unwrap_or_default();
+            hosted.push(event_id);
+            self.host_events.insert(caller, &hosted);
+            // Royalties follow the event to its new host
+            if event.royalty_bps > 0 {
+                Self::set_collection_royalty(event.ticket_nft_address, caller, event.royalty_bps)
+                    .map_err(Error::CollectionUpdateFailed)?;
+            }
+
+            self.env().emit_event(HostTransferred {
+                event_id,
+                previous_host,
+                new_host: caller,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_pending_host(&self, event_id: u64) -> Option<AccountId> {
+            self.pending_hosts.get(event_id)
+        }
+
+        /// Cancels the event for good. Sales stop, the host can no longer claim revenue,
+        /// and every buyer can pull back what they paid with `claim_refund`.
+        #[ink(message)]
+        pub fn cancel_event(&mut self, event_id: u64) -> Result<()> {
+            let host = self.env().caller();
+            let now = self.env().block_timestamp();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if Self::ended(&event, now) {
+                return Err(Error::EventEnded);
+            }
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            event.status = EventStatus::Cancelled;
+            self.events.insert(event_id, &event);
+            self.unlist_event(event_id);
+
+            self.env().emit_event(EventCancelled { event_id, host });
+            Ok(())
+        }
+
+        /// Closes a crowdfunded event's funding round once its deadline has passed. Anyone
+        /// may call it; short of the goal the event is cancelled so buyers can claim full
+        /// refunds. Returns whether the event is confirmed.
+        #[ink(message)]
+        pub fn settle_funding(&mut self, event_id: u64) -> Result<bool> {
+            let now = self.env().block_timestamp();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            let goal = event.details.funding_goal.ok_or(Error::NotCrowdfunded)?;
+            if event.funding_confirmed {
+                return Err(Error::FundingAlreadyConfirmed);
+            }
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            let tickets_sold = event.tickets_sold;
+            // Refund requests can take an event back under its goal, so recheck here
+            if tickets_sold >= goal.min_tickets {
+                event.funding_confirmed = true;
+                self.events.insert(event_id, &event);
+                self.env().emit_event(FundingConfirmed { event_id, tickets_sold });
+                return Ok(true);
+            }
+            if now <= goal.deadline {
+                return Err(Error::FundingPending);
+            }
+
+            event.status = EventStatus::Cancelled;
+            self.events.insert(event_id, &event);
+            self.unlist_event(event_id);
+
+            self.env().emit_event(FundingFailed { event_id, tickets_sold });
+            Ok(false)
+        }
+
+        #[ink(message)]
+        pub fn is_funding_confirmed(&self, event_id: u64) -> Option<bool> {
+            self.events.get(event_id).map(|e| e.funding_confirmed)
+        }
+
+        /// Refunds the caller's full payment for a cancelled event and invalidates the
+        /// tickets it bought. Refunds go to whoever holds a ticket now: transfers carry the
+        /// purchase record to the receiver, and a caller who no longer holds every ticket on
+        /// their record is turned away with `NotTicketHolder`.
+        #[ink(message)]
+        pub fn claim_refund(&mut self, event_id: u64) -> Result<Balance> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.status != EventStatus::Cancelled {
+                return Err(Error::EventNotCancelled);
+            }
+            self.refund_cancelled(event, self.env().caller())
+        }
+
+        /// Refunds up to `max` attendee slots of a cancelled event on the buyers' behalf,
+        /// for those who never call `claim_refund`. A stored cursor carries on where the
+        /// last call stopped and buyers already refunded either way are skipped, so no one
+        /// is paid twice. Returns how many slots it walked; 0 once the event is done.
+        #[ink(message)]
+        pub fn process_refunds(&mut self, event_id: u64, max: u32) -> Result<u32> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if event.status != EventStatus::Cancelled {
+                return Err(Error::EventNotCancelled);
+            }
+
+            let cursor = self.refund_cursors.get(event_id).unwrap_or(0);
+            let end = cursor.saturating_add(max as u64).min(event.attendee_count);
+            for index in cursor..end {
+                let Some(buyer) = self.attendees.get((event_id, index)) else { continue };
+                // Refunding writes the event back, so each buyer starts from the latest copy
+                let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+                match self.refund_cancelled(event, buyer) {
+                    Ok(_) | Err(Error::NothingToRefund) | Err(Error::NotTicketHolder) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            self.refund_cursors.insert(event_id, &end);
+            Ok((end - cursor) as u32)
+        }
+
+        /// Pays `buyer` back everything they paid for the cancelled `event` and voids
+        /// their tickets.
+        fn refund_cancelled(&mut self, mut event: Event, buyer: AccountId) -> Result<Balance> {
+            let event_id = event.event_id;
+            let mut purchase = match self.purchases.get((event_id, buyer)) {
+                Some(p) if !p.refunded && p.paid > 0 => p,
+                _ => {
+                    return Err(Error::NothingToRefund);
+                }
+            };
+
+            // Refunds follow the ticket: when a transfer wasn't reported back, the buyer
+            // on record may no longer hold what they would be paid for
+            let collection = event.ticket_nft_address;
+            let held = purchase.token_ids.iter().all(|token_id| {
+                self.is_ticket_invalidated(event_id, *token_id) ||
+                    Self::ticket_owner(collection, *token_id) == Some(buyer)
+            });
+            if !held {
+                return Err(Error::NotTicketHolder);
+            }
+
+            // The platform fee is still escrowed with the event, so the refund is exact
+            let amount = purchase.paid;
+            event.revenue = event.revenue.saturating_sub(amount.saturating_sub(purchase.fee));
+            event.platform_fees = event.platform_fees.saturating_sub(purchase.fee);
+            // Nothing is left on the record to refund again or to count for a receiver
+            let token_ids = core::mem::take(&mut purchase.token_ids);
+            purchase.paid = 0;
+            purchase.fee = 0;
+            purchase.refunded = true;
+            self.purchases.insert((event_id, buyer), &purchase);
+            self.events.insert(event_id, &event);
+
+            self.pay_out(event.payment_token, buyer, amount)?;
+
+            for token_id in token_ids.iter() {
+                self.invalidated_tickets.insert((event_id, *token_id), &());
+            }
+            if let Some(count) = self.ticket_counts.get((event_id, buyer)) {
+                let remaining = count.saturating_sub(token_ids.len() as u32);
+                self.ticket_counts.insert((event_id, buyer), &remaining);
+            }
+            if event.payment_token.is_none() {
+                self.stats.native_refunded = self.stats.native_refunded.saturating_add(amount);
+            }
+
+            self.env().emit_event(RefundClaimed {
+                event_id,
+                buyer,
+                amount,
+            });
+            Ok(amount)
+        }
+
+        /// Gives back one ticket under the event's refund policy. The ticket is burned, its
+        /// slot returns to sale (or to the next waitlisted account) and the buyer gets the
+        /// share of its price the policy allows right now. Cancelled events are refunded in
+        /// full through `claim_refund` instead.
+        #[ink(message)]
+        pub fn request_refund(&mut self, event_id: u64, token_id: u64) -> Result<Balance> {
+            let buyer = self.env().caller();
+            let now = self.env().block_timestamp();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            if Self::ended(&event, now) {
+                return Err(Error::EventEnded);
+            }
+            let mut purchase = self.purchases.get((event_id, buyer)).ok_or(Error::NoValidTicket)?;
+            let position = purchase.token_ids
+                .iter()
+                .position(|id| *id == token_id)
+                .filter(|_| !self.is_ticket_invalidated(event_id, token_id))
+                .ok_or(Error::NoValidTicket)?;
+            // Only the current holder is refunded, as with `claim_refund`
+            if Self::ticket_owner(event.ticket_nft_address, token_id) != Some(buyer) {
+                return Err(Error::NotTicketHolder);
+            }
+            if self.check_ins.contains((event_id, token_id)) {
+                return Err(Error::AlreadyCheckedIn);
+            }
+            let sale = self.ticket_sales
+                .get((event_id, token_id))
+                .filter(|sale| !sale.comp)
+                .ok_or(Error::RefundNotAvailable)?;
+            let refund_bps = Self::refund_bps(&event.details, now);
+            if refund_bps == 0 {
+                return Err(Error::RefundNotAvailable);
+            }
+            let share = |amount: Balance| {
+                amount.checked_mul(refund_bps as Balance).map(|n| n / MAX_BPS as Balance)
+            };
+            let amount = share(sale.price).ok_or(Error::Overflow)?;
+            let fee_refund = share(sale.fee).ok_or(Error::Overflow)?;
+            let commission_refund = share(sale.commission).ok_or(Error::Overflow)?;
+
+            // Since ink! 4 an `Err` reverts the whole call, so the burn and payout below
+            // can follow the effects without a hand-written rollback
+            purchase.token_ids.remove(position);
+            purchase.paid = purchase.paid.saturating_sub(sale.price);
+            purchase.fee = purchase.fee.saturating_sub(sale.fee);
+            self.purchases.insert((event_id, buyer), &purchase);
+            self.invalidated_tickets.insert((event_id, token_id), &());
+            self.ticket_sales.remove((event_id, token_id));
+            if let Some(count) = self.ticket_counts.get((event_id, buyer)) {
+                self.ticket_counts.insert((event_id, buyer), &count.saturating_sub(1));
+            }
+
+            // The retained share stays with the host and platform
+            event.revenue = event.revenue.saturating_sub(amount - fee_refund);
+            event.platform_fees = event.platform_fees.saturating_sub(fee_refund);
+            // The referrer keeps commission only on the share the host retains
+            if let Some(referrer) = sale.referrer {
+                let earned = self.referral_earnings.get((event_id, referrer)).unwrap_or(0);
+                let earned = earned.saturating_sub(commission_refund);
+                self.referral_earnings.insert((event_id, referrer), &earned);
+                let referred = self.referral_counts.get((event_id, referrer)).unwrap_or(0);
+                self.referral_counts.insert((event_id, referrer), &referred.saturating_sub(1));
+                event.referral_owed = event.referral_owed.saturating_sub(commission_refund);
+                event.referred_tickets = event.referred_tickets.saturating_sub(1);
+            }
+            event.details.tiers[sale.tier as usize].sold -= 1;
+            event.tickets_sold -= 1;
+            self.remove_attendee(&mut event, sale.attendee_index);
+            self.remove_registration(buyer, event_id);
+            self.events.insert(event_id, &event);
+            self.list_event(event_id);
+            self.release_slot(event_id, sale.tier);
+
+            Self::burn_ticket(event.ticket_nft_address, token_id).map_err(Error::BurnFailed)?;
+            if amount > 0 {
+                self.pay_out(event.payment_token, buyer, amount)?;
+            }
+            if event.payment_token.is_none() {
+                self.stats.native_refunded = self.stats.native_refunded.saturating_add(amount);
+            }
+
+            self.env().emit_event(TicketRefunded {
+                event_id,
+                buyer,
+                token_id,
+                amount,
+            });
+            Ok(amount)
+        }
+
+        /// Best refund share the policy grants with the time left until the event.
+        fn refund_bps(details: &EventDetails, now: Timestamp) -> u16 {
+            let notice = details.date.saturating_sub(now);
+            details.refund_policy
+                .iter()
+                .filter(|tier| notice >= tier.min_notice)
+                .map(|tier| tier.refund_bps)
+                .max()
+                .unwrap_or(0)
+        }
+
+        /// Drops the `attendees` entry at `index` by moving the last entry into its place.
+        fn remove_attendee(&mut self, event: &mut Event, index: u64) {
+            let event_id = event.event_id;
+            let last = event.attendee_count - 1;
+            if index != last {
+                if let Some(account) = self.attendees.get((event_id, last)) {
+                    self.attendees.insert((event_id, index), &account);
+                }
+                if let Some(moved) = self.attendee_tickets.get((event_id, last)) {
+                    self.attendee_tickets.insert((event_id, index), &moved);
+                    if let Some(mut sale) = self.ticket_sales.get((event_id, moved)) {
+                        sale.attendee_index = index;
+                        self.ticket_sales.insert((event_id, moved), &sale);
+                    }
+                }
+            }
+            self.attendees.remove((event_id, last));
+            self.attendee_tickets.remove((event_id, last));
+            event.attendee_count = last;
+        }
+
+        fn add_registration(&mut self, account: AccountId, event_id: u64) {
+            let count = self.registration_counts.get(account).unwrap_or(0);
+            self.user_registered_events.insert((account, count), &event_id);
+            self.registration_counts.insert(account, &count.saturating_add(1));
+            self.total_registrations = self.total_registrations.saturating_add(1);
+        }
+
+        /// Drops one of `account`'s registrations for `event_id`, filling the gap with
+        /// their last entry.
+        fn remove_registration(&mut self, account: AccountId, event_id: u64) {
+            let count = self.registration_counts.get(account).unwrap_or(0);
+            let found = (0..count)
+                .rev()
+                .find(|index| self.user_registered_events.get((account, *index)) == Some(event_id));
+            let Some(index) = found else { return };
+            let last = count - 1;
+            if index != last {
+                if let Some(moved) = self.user_registered_events.get((account, last)) {
+                    self.user_registered_events.insert((account, index), &moved);
+                }
+            }
+            self.user_registered_events.remove((account, last));
+            self.registration_counts.insert(account, &last);
+            self.total_registrations = self.total_registrations.saturating_sub(1);
+        }
+
+        #[ink(message)]
+        pub fn is_ticket_invalidated(&self, event_id: u64, token_id: u64) -> bool {
+            self.invalidated_tickets.contains((event_id, token_id))
+        }
+
+        #[ink(message)]
+        pub fn has_ticket(&self, event_id: u64, account: AccountId) -> bool {
+            self.ticket_counts.get((event_id, account)).map_or(false, |count| count > 0)
+        }
+
+        #[ink(message)]
+        pub fn get_purchase(&self, event_id: u64, buyer: AccountId) -> Option<Purchase> {
+            self.purchases.get((event_id, buyer))
+        }
+
+        /// Token ids of the valid tickets `user` holds for the event, comps included. The
+        /// records follow tickets through transfers, refunds and burns.
+        #[ink(message)]
+        pub fn get_user_tickets(&self, user: AccountId, event_id: u64) -> Vec<u64> {
+            let token_ids = self.purchases
+                .get((event_id, user))
+                .map(|purchase| purchase.token_ids)
+                .unwrap_or_default();
+            token_ids
+                .into_iter()
+                .filter(|token_id| !self.is_ticket_invalidated(event_id, *token_id))
+                .collect()
+        }
+
+        /// Reissues a ticket whose holder can't move it, e.g. one stuck in a broken
+        /// contract, to `new_owner`. The old token is burnt and the sale's records follow
+        /// the replacement. Host only; used tickets can't be recovered.
+        #[ink(message)]
+        pub fn recover_ticket(
+            &mut self,
+            event_id: u64,
+            token_id: u64,
+            new_owner: AccountId
+        ) -> Result<u64> {
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if event.host != self.env().caller() {
+                return Err(Error::NotHost);
+            }
+            if self.is_ticket_invalidated(event_id, token_id) {
+                return Err(Error::NoValidTicket);
+            }
+            let sale = self.ticket_sales.get((event_id, token_id)).ok_or(Error::NoValidTicket)?;
+            if self.is_banned(event_id, new_owner) {
+                return Err(Error::Banned);
+            }
+            let holder = self.attendees
+                .get((event_id, sale.attendee_index))
+                .ok_or(Error::NoValidTicket)?;
+            let new_token_id = Self::recover_nft_ticket(
+                event.ticket_nft_address,
+                token_id,
+                new_owner
+            ).map_err(Error::RecoveryFailed)?;
+
+            self.ticket_sales.remove((event_id, token_id));
+            self.ticket_sales.insert((event_id, new_token_id), &sale);
+            self.attendee_tickets.insert((event_id, sale.attendee_index), &new_token_id);
+            self.move_records(event_id, &sale, holder, new_owner, token_id, new_token_id);
+
+            self.env().emit_event(TicketRecovered {
+                event_id,
+                old_token_id: token_id,
+                new_token_id,
+                new_owner,
+            });
+            Ok(new_token_id)
+        }
+
+        /// Gives the caller's ticket to `to` with a short `memo`, carried in `TicketGifted`.
+        /// The manager moves it on the collection, so the caller must first approve it for
+        /// the token; the records move with it as for any transfer.
+        #[ink(message)]
+        pub fn gift_ticket(
+            &mut self,
+            event_id: u64,
+            token_id: u64,
+            to: AccountId,
+            memo: String
+        ) -> Result<()> {
+            let from = self.env().caller();
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(Error::MemoTooLong);
+            }
+            let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+            if !self.ticket_sales.contains((event_id, token_id)) {
+                return Err(Error::NoValidTicket);
+            }
+            if Self::ticket_owner(event.ticket_nft_address, token_id) != Some(from) {
+                return Err(Error::NotTicketHolder);
+            }
+            self.move_ticket(event.ticket_nft_address, from, to, token_id)
+                .map_err(Error::TicketTransferFailed)?;
+
+            self.env().emit_event(TicketGifted { token_id, from, to, memo });
+            Ok(())
+        }
+
+        /// Releases the event's escrowed revenue to its host. Funds stay locked in the
+        /// contract until the event's date has passed; the platform's cut moves to the
+        /// treasury at the same time.
+        #[ink(message)]
+        pub fn claim_revenue(&mut self, event_id: u64) -> Result<Balance> {
+            let now = self.env().block_timestamp();
+            let mut event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+
+            let host = self.env().caller();
+            if event.host != host {
+                return Err(Error::NotHost);
+            }
+            if event.status == EventStatus::Cancelled {
+                return Err(Error::EventCancelled);
+            }
+            if !Self::ended(&event, now) {
+                return Err(Error::EventNotEnded);
+            }
+            // Crowdfunded revenue stays escrowed for refunds until the goal is met
+            if event.details.funding_goal.is_some() && !event.funding_confirmed {
+                return Err(Error::FundingNotConfirmed);
+            }
+            // Referral commission stays behind for `withdraw_referral_earnings`
+            let owed = event.referral_owed.min(event.revenue);
+            let amount = event.revenue - owed;
+            if amount == 0 {
+                return Err(Error::NothingToWithdraw);
+            }
+            // The platform's cut stays in the asset it was paid in
+            let held_fees = match event.payment_token {
+                Some(token) => self.get_token_fees(token),
+                None => self.platform_fees,
+            };
+            let platform_fees = held_fees.checked_add(event.platform_fees).ok_or(Error::Overflow)?;
+
+            event.revenue = owed;
+            event.platform_fees = 0;
+            self.events.insert(event_id, &event);
+            self.set_held_fees(event.payment_token, platform_fees);
+            if !event.details.payees.is_empty() {
+                // A failed share reverts the whole call, shares already paid included
+                self.split_revenue(event_id, &event, amount)?;
+            } else {
+                self.pay_out(event.payment_token, host, amount)?;
+            }
+
+            self.env().emit_event(RevenueClaimed {
+                event_id,
+                host,
+                amount,
+            });
+            Ok(amount)
+        }
+
+        /// Pays `amount` out across the event's payees in proportion to their shares. The
+        /// last payee also gets the rounding dust, so the full amount always leaves.
+        fn split_revenue(&self, event_id: u64, event: &Event, amount: Balance) -> Result<()> {
+            let payees = &event.details.payees;
+            let mut remaining = amount;
+            for (index, payee) in payees.iter().enumerate() {
+                let share = if index + 1 == payees.len() {
+                    remaining
+                } else {
+                    amount.checked_mul(payee.share_bps as Balance).ok_or(Error::Overflow)? /
+                        MAX_BPS as Balance
+                };
+                remaining -= share;
+                if share == 0 {
+                    continue;
+                }
+                self.pay_out(event.payment_token, payee.account, share)?;
+                self.env().emit_event(RevenuePaid {
+                    event_id,
+                    payee: payee.account,
+                    amount: share,
+                });
+            }
+            Ok(())
+        }
+
+        fn set_held_fees(&mut self, token: Option<AccountId>, amount: Balance) {
+            match token {
+                Some(token) => {
+                    self.token_fees.insert(token, &amount);
+                }
+                None => {
+                    self.platform_fees = amount;
+                }
+            }
+        }
+
+        #[ink(message)]
+        pub fn is_upcoming(&self, event_id: u64) -> Option<bool> {
+            let now = self.env().block_timestamp();
+            self.events.get(event_id).map(|e| now < e.details.date)
+        }
+
+        /// Frees the storage of an event that is long over and fully settled, leaving an
+        /// `ArchivedEvent` tombstone; the freed deposit goes back to the caller. Attendee
+        /// entries are cleared `MAX_PAGE_SIZE` at a time, so big events take several calls.
+        /// Returns how many are left. Host or owner only, `ARCHIVE_DELAY` after the event.
+        /// The tickets on the collection are left alone, and so are entries that can't be
+        /// enumerated, like promo codes and operators. Holders keep the event in their
+        /// registrations.
+        #[ink(message)]
+        pub fn archive_event(&mut self, event_id: u64) -> Result<u64> {
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+            let mut archived = match self.archived_events.get(event_id) {
+                Some(archived) if archived.uncleared == 0 => {
+                    return Err(Error::AlreadyArchived);
+                }
+                Some(archived) => {
+                    if archived.host != caller && self.owner != Some(caller) {
+                        return Err(Error::NotHost);
+                    }
+                    archived
+                }
+                None => {
+                    let event = self.events.get(event_id).ok_or(Error::EventNotFound)?;
+                    if event.host != caller && self.owner != Some(caller) {
+                        return Err(Error::NotHost);
+                    }
+                    if now < event.details.date.saturating_add(ARCHIVE_DELAY) {
+                        return Err(Error::ArchiveTooEarly);
+                    }
+                    if event.revenue > 0 || event.platform_fees > 0 {
+                        return Err(Error::FundsOutstanding);
+                    }
+                    let entrants = self.lottery_entrants.get(event_id).unwrap_or_default();
+                    let deposits_held = entrants.iter().any(|entrant| {
+                        self.lottery_entries
+                            .get((event_id, *entrant))
+                            .is_some_and(|e| e.deposit > 0 && !e.deposit_returned)
+                    });
+                    if deposits_held {
+                        return Err(Error::FundsOutstanding);
+                    }
+                    self.clear_event(&event, &entrants);
+                    self.env().emit_event(EventArchived { event_id, archived_by: caller });
+                    ArchivedEvent {
+                        host: event.host,
+                        date: event.details.date,
+                        status: event.status,
+                        tickets_sold: event.tickets_sold,
+                        comps_issued: event.comps_issued,
+                        attendee_count: event.attendee_count,
+                        total_revenue: event.total_revenue,
+                        archived_at: now,
+                        uncleared: event.attendee_count,
+                    }
+                }
+            };
+
+            let end = archived.uncleared;
+            let start = end.saturating_sub(MAX_PAGE_SIZE as u64);
+            for index in (start..end).rev() {
+                self.clear_attendee(event_id, index);
+            }
+            archived.uncleared = start;
+            self.archived_events.insert(event_id, &archived);
+            Ok(start)
+        }
+
+        /// Drops the event and everything stored once per event.
+        fn clear_event(&mut self, event: &Event, entrants: &[AccountId]) {
+            let event_id = event.event_id;
+            self.events.remove(event_id);
+            self.unindex_discovery(event_id, &event.details);
+            self.unlist_event(event_id);
+            // `collection_events` keeps the id, so the collection's tickets can still move
+            // and burn once their records are gone
+            for entrant in entrants.iter() {
+                self.lottery_entries.remove((event_id, *entrant));
+            }
+            self.lottery_entrants.remove(event_id);
+            self.lotteries.remove(event_id);
+            self.presales.remove(event_id);
+            self.waitlists.remove(event_id);
+            self.held_slots.remove(event_id);
+            self.pending_hosts.remove(event_id);
+            self.checked_in_counts.remove(event_id);
+            self.sales_buckets.remove(event_id);
+            self.refund_cursors.remove(event_id);
+        }
+
+        /// Drops the attendee entry at `index` and the records of the ticket and account
+        /// behind it.
+        fn clear_attendee(&mut self, event_id: u64, index: u64) {
+            if let Some(token_id) = self.attendee_tickets.take((event_id, index)) {
+                self.ticket_sales.remove((event_id, token_id));
+                self.check_ins.remove((event_id, token_id));
+                self.invalidated_tickets.remove((event_id, token_id));
+            }
+            if let Some(account) = self.attendees.take((event_id, index)) {
+                self.purchases.remove((event_id, account));
+                self.purchase_counts.remove((event_id, account));
+                self.ticket_counts.remove((event_id, account));
+                self.check_in_nonces.remove((event_id, account));
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_archived_event(&self, event_id: u64) -> Option<ArchivedEvent> {
+            self.archived_events.get(event_id)
+        }
+
+        #[ink(message)]
+        pub fn has_ended(&self, event_id: u64) -> Option<bool> {
+            let now = self.env().block_timestamp();
+            self.events.get(event_id).map(|e| Self::ended(&e, now))
+        }
+
+        fn ended(event: &Event, now: Timestamp) -> bool {
+            now > event.details.date
+        }
+
+        #[ink(message)]
+        pub fn get_event_revenue(&self, event_id: u64) -> Option<(Balance, Balance)> {
+            self.events.get(event_id).map(|e| (e.revenue, e.total_revenue))
+        }
+
+        #[ink(message)]
+        pub fn get_event(&self, event_id: u64) -> Option<EventInfo> {
+            let event = self.events.get(event_id)?;
+            let details = &event.details;
+            Some(EventInfo {
+                event_id,
+                title: details.title.clone(),
+                date: details.date,
+                location: details.location.clone(),
+                category: details.category,
+                tiers: details.tiers.clone(),
+                sales_start: details.sales_start,
+                sales_end: details.sales_end,
+                ticket_nft_address: event.ticket_nft_address,
+                host: event.host,
+                tickets_sold: event.tickets_sold,
+                tickets_remaining: details.max_tickets().saturating_sub(event.tickets_sold),
+                status: event.status,
+                currency: event.payment_currency.unwrap_or_else(|| self.currency.clone()),
+                payment_token: event.payment_token,
+            })
+        }
+
+        #[ink(message)]
+        pub fn get_event_details(&self, event_id: u64) -> Option<EventDetails> {
+            self.events.get(event_id).map(|e| e.details)
+        }
+
+        #[ink(message)]
+        pub fn event_count(&self) -> u64 {
+            self.next_event_id - 1
+        }
+
+        /// Lists events in creation order, `limit` at a time (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_events(&self, offset: u64, limit: u32) -> Vec<(u64, EventDetails)> {
+            let limit = limit.min(MAX_PAGE_SIZE) as usize;
+            (offset.saturating_add(1)..self.next_event_id)
+                .take(limit)
+                .filter_map(|id| self.events.get(id).map(|e| (id, e.details)))
+                .collect()
+        }
+
+        /// Lists the events hosted by `host`, `limit` at a time (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_events_by_host(
+            &self,
+            host: AccountId,
+            offset: u64,
+            limit: u32
+        ) -> Vec<(u64, EventDetails)> {
+            self.page_of(self.host_events.get(host), offset, limit)
+        }
+
+        #[ink(message)]
+        pub fn get_events_by_category(
+            &self,
+            category: u8,
+            offset: u64,
+            limit: u32
+        ) -> Vec<(u64, EventDetails)> {
+            self.page_of(self.category_events.get(category), offset, limit)
+        }
+
+        /// `tag` is the blake2x256 hash of the lowercase tag string.
+        #[ink(message)]
+        pub fn get_events_by_tag(
+            &self,
+            tag: [u8; 32],
+            offset: u64,
+            limit: u32
+        ) -> Vec<(u64, EventDetails)> {
+            self.page_of(self.tag_events.get(tag), offset, limit)
+        }
+
+        fn page_of(
+            &self,
+            ids: Option<Vec<u64>>,
+            offset: u64,
+            limit: u32
+        ) -> Vec<(u64, EventDetails)> {
+            ids.unwrap_or_default()
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit.min(MAX_PAGE_SIZE) as usize)
+                .filter_map(|id| self.events.get(id).map(|e| (id, e.details)))
+                .collect()
+        }
+
+        /// Lists events that are active, not sold out and currently selling, `limit` at a
+        /// time (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_active_events(&self, offset: u64, limit: u32) -> Vec<(u64, EventDetails)> {
+            let now = self.env().block_timestamp();
+            self.active_events
+                .get()
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|id| self.events.get(id).map(|e| (id, e.details)))
+                .filter(|(_, details)| details.sales_start <= now && now <= details.sales_end)
+                .skip(offset as usize)
+                .take(limit.min(MAX_PAGE_SIZE) as usize)
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn tickets_remaining(&self, event_id: u64) -> Option<u64> {
+            self.events
+                .get(event_id)
+                .map(|e| e.details.max_tickets().saturating_sub(e.tickets_sold))
+        }
+
+        #[ink(message)]
+        pub fn is_sold_out(&self, event_id: u64) -> Option<bool> {
+            self.tickets_remaining(event_id).map(|remaining| remaining == 0)
+        }
+
+        /// Lists an event's attendees, `limit` at a time (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_attendees(&self, event_id: u64, offset: u64, limit: u32) -> Vec<AccountId> {
+            let count = self.attendee_count(event_id).unwrap_or(0);
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE) as u64).min(count);
+            (offset..end).filter_map(|index| self.attendees.get((event_id, index))).collect()
+        }
+
+        #[ink(message)]
+        pub fn attendee_count(&self, event_id: u64) -> Option<u64> {
+            self.events.get(event_id).map(|e| e.attendee_count)
+        }
+
+        #[ink(message)]
+        pub fn get_ticket_nft_address(&self, event_id: u64) -> Option<AccountId> {
+            self.events.get(event_id).map(|e| e.ticket_nft_address)
+        }
+
+        /// Event ids the user bought tickets for, once per ticket, `limit` at a time
+        /// (capped at `MAX_PAGE_SIZE`).
+        #[ink(message)]
+        pub fn get_registered_events(&self, user: AccountId, offset: u64, limit: u32) -> Vec<u64> {
+            let count = self.registration_count(user);
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE) as u64).min(count);
+            (offset..end)
+                .filter_map(|index| self.user_registered_events.get((user, index)))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn registration_count(&self, user: AccountId) -> u64 {
+            self.registration_counts.get(user).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn platform_stats(&self) -> PlatformStats {
+            self.stats.clone()
+        }
+
+        /// Tickets sold per day as `(day start, count)`, oldest first. Days with no sales
+        /// are left out, and refunds don't take sales back off.
+        #[ink(message)]
+        pub fn sales_history(&self, event_id: u64) -> Vec<(Timestamp, u64)> {
+            self.sales_buckets.get(event_id).unwrap_or_default()
+        }
+
+        fn record_sales(&mut self, event_id: u64, now: Timestamp, quantity: u64) {
+            let day = now - now % SALES_BUCKET;
+            let mut buckets = self.sales_buckets.get(event_id).unwrap_or_default();
+            match buckets.last_mut() {
+                Some((start, count)) if *start == day => *count = count.saturating_add(quantity),
+                _ => buckets.push((day, quantity)),
+            }
+            self.sales_buckets.insert(event_id, &buckets);
+        }
+
+        #[ink(message)]
+        pub fn get_currency(&self) -> CurrencyInfo {
+            self.currency.clone()
+        }
+
+        /// Recomputes bookkeeping totals and reports drift instead of panicking.
+        #[ink(message)]
+        pub fn audit(&self) -> AuditReport {
+            let mut events_checked = 0;
+            let mut total_tickets_sold = 0;
+            let mut total_comp_tickets = 0;
+            let mut total_attendees = 0;
+            let mut oversold_events = 0;
+            let mut total_revenue_held: Balance = 0;
+            let mut platform_fees_held = self.platform_fees;
+            let mut lottery_deposits_held: Balance = 0;
+
+            // Archived events keep their headline counts, and their holders their registrations
+            for archived in (1..self.next_event_id).filter_map(|id| self.archived_events.get(id)) {
+                total_tickets_sold = total_tickets_sold.saturating_add(archived.tickets_sold);
+                total_comp_tickets = total_comp_tickets.saturating_add(archived.comps_issued);
+                total_attendees = total_attendees.saturating_add(archived.attendee_count);
+            }
+            for (event_id, event) in
+                (1..self.next_event_id).filter_map(|id| Some((id, self.events.get(id)?)))
+            {
+                events_checked += 1;
+                total_tickets_sold = total_tickets_sold.saturating_add(event.tickets_sold);
+                total_comp_tickets = total_comp_tickets.saturating_add(event.comps_issued);
+                total_attendees = total_attendees.saturating_add(event.attendee_count);
+                // PSP22 revenue sits in the token contracts, not in our native balance.
+                // Referral commission is escrowed inside `revenue`, so count whichever of
+                // the two is larger rather than both.
+                if event.payment_token.is_none() {
+                    let held = event.revenue.max(event.referral_owed);
+                    total_revenue_held = total_revenue_held.saturating_add(held);
+                    platform_fees_held = platform_fees_held.saturating_add(event.platform_fees);
+                }
+                // Lottery deposits are always paid in native currency
+                for entrant in self.lottery_entrants.get(event_id).unwrap_or_default() {
+                    let deposit = self.lottery_entries
+                        .get((event_id, entrant))
+                        .filter(|e| !e.deposit_returned)
+                        .map_or(0, |e| e.deposit);
+                    lottery_deposits_held = lottery_deposits_held.saturating_add(deposit);
+                }
+                let tier_oversold = event.details.tiers.iter().any(|tier| tier.sold > tier.max);
+                if tier_oversold || event.tickets_sold > event.details.max_tickets() {
+                    oversold_events += 1;
+                }
+            }
+
+            let total_registrations = self.total_registrations;
+            let total_held = total_revenue_held
+                .saturating_add(platform_fees_held)
+                .saturating_add(lottery_deposits_held);
+
+            AuditReport {
+                events_checked,
+                total_tickets_sold,
+                total_comp_tickets,
+                total_attendees,
+                total_registrations,
+                oversold_events,
+                total_revenue_held,
+                platform_fees_held,
+                lottery_deposits_held,
+                tickets_match_attendees: total_tickets_sold.saturating_add(total_comp_tickets) ==
+                total_attendees,
+                registrations_match_attendees: total_registrations == total_attendees,
+                balance_covers_revenue: self.env().balance() >= total_held,
+            }
+        }
+    }
+
+    impl TicketObserver for EventManager {
+        /// Hands a ticket's records to its new holder when it moves on its collection:
+        /// they become the attendee, can check in with it and get its refunds.
+        #[ink(message)]
+        fn on_ticket_transfer(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            token_id: u64
+        ) -> core::result::Result<(), TicketNftError> {
+            // Badges have no sale record and nothing to move
+            let Some(event_id) = self.ticket_event(token_id)? else { return Ok(()) };
+            if self.is_banned(event_id, from) || self.is_banned(event_id, to) {
+                return Err(TicketNftError::Banned);
+            }
+            if from == to || self.is_ticket_invalidated(event_id, token_id) {
+                return Ok(());
+            }
+            let Some(sale) = self.ticket_sales.get((event_id, token_id)) else { return Ok(()) };
+            self.move_records(event_id, &sale, from, to, token_id, token_id);
+            Ok(())
+        }
+
+        /// Voids a ticket its holder burned. Nothing is refunded and the seat isn't
+        /// resold; the holder just no longer has it.
+        #[ink(message)]
+        fn on_ticket_burn(
+            &mut self,
+            owner: AccountId,
+            token_id: u64
+        ) -> core::result::Result<(), TicketNftError> {
+            let Some(event_id) = self.ticket_event(token_id)? else { return Ok(()) };
+            if !self.is_ticket_invalidated(event_id, token_id) {
+                self.void_ticket(event_id, owner, token_id);
+            }
+            Ok(())
+        }
+
+        /// Platform bans apply to every token, event bans to the event's tickets.
+        #[ink(message)]
+        fn is_banned_holder(&self, token_id: u64, account: AccountId) -> bool {
+            match self.ticket_event(token_id).ok().flatten() {
+                Some(event_id) => self.is_banned(event_id, account),
+                None => self.platform_bans.contains(account),
+            }
+        }
+    }
+
+    impl EventManager {
+        /// Hands the attendee slot, purchase share and registration behind `sale` from
+        /// `from` to `to`, with the ticket now known as `new_token_id`.
+        fn move_records(
+            &mut self,
+            event_id: u64,
+            sale: &TicketSale,
+            from: AccountId,
+            to: AccountId,
+            old_token_id: u64,
+            new_token_id: u64
+        ) {
+            self.attendees.insert((event_id, sale.attendee_index), &to);
+            let mut sender = self.purchases.get((event_id, from)).unwrap_or_default();
+            sender.token_ids.retain(|id| *id != old_token_id);
+            sender.paid = sender.paid.saturating_sub(sale.price);
+            sender.fee = sender.fee.saturating_sub(sale.fee);
+            // A holder who let their last ticket go drops out of `attendees`, where
+            // `archive_event` finds the records it clears, so nothing is left behind
+            if sender.token_ids.is_empty() && !sender.refunded {
+                self.purchases.remove((event_id, from));
+            } else {
+                self.purchases.insert((event_id, from), &sender);
+            }
+            let mut receiver = self.purchases.get((event_id, to)).unwrap_or_default();
+            // A refunded record is empty, so the incoming ticket opens a fresh claim
+            receiver.refunded = false;
+            receiver.token_ids.push(new_token_id);
+            receiver.paid = receiver.paid.saturating_add(sale.price);
+            receiver.fee = receiver.fee.saturating_add(sale.fee);
+            self.purchases.insert((event_id, to), &receiver);
+
+            match self.ticket_counts.get((event_id, from)).unwrap_or(0) {
+                0 | 1 => self.ticket_counts.remove((event_id, from)),
+                held => {
+                    self.ticket_counts.insert((event_id, from), &(held - 1));
+                }
+            }
+            let held = self.ticket_counts.get((event_id, to)).unwrap_or(0);
+            self.ticket_counts.insert((event_id, to), &held.saturating_add(1));
+            self.remove_registration(from, event_id);
+            self.add_registration(to, event_id);
+        }
+
+        /// Takes a burned ticket off `owner`'s records. Its sale stays counted, so the
+        /// seat isn't resold.
+        fn void_ticket(&mut self, event_id: u64, owner: AccountId, token_id: u64) {
+            self.invalidated_tickets.insert((event_id, token_id), &());
+            if let Some(mut purchase) = self.purchases.get((event_id, owner)) {
+                purchase.token_ids.retain(|id| *id != token_id);
+                self.purchases.insert((event_id, owner), &purchase);
+            }
+            let held = self.ticket_counts.get((event_id, owner)).unwrap_or(0);
+            self.ticket_counts.insert((event_id, owner), &held.saturating_sub(1));
+            self.remove_registration(owner, event_id);
+        }
+
+        /// Event that the calling collection's `token_id` was sold for, if any. Only
+        /// collections registered for an event may report on their tickets.
+        fn ticket_event(
+            &self,
+            token_id: u64
+        ) -> core::result::Result<Option<u64>, TicketNftError> {
+            let collection = self.env().caller();
+            let event_ids = self.collection_events.get(collection).unwrap_or_default();
+            if event_ids.is_empty() {
+                return Err(TicketNftError::Unauthorized);
+            }
+            Ok(
+                event_ids
+                    .into_iter()
+                    .find(|event_id| self.ticket_sales.contains((*event_id, token_id)))
+            )
+        }
+    }
+
+    /// Off-chain stand-in for `TicketNFT`, keyed by the address it is "deployed" at. It
+    /// mirrors the `TicketMinter` messages and can be told to reject mints.
+    #[cfg(test)]
+    mod mock_ticket_nft {
+        use super::{
+            AccountId,
+            Balance,
+            EventCollection,
+            PSP34Error,
+            String,
+            TicketData,
+            TicketNftError,
+            Vec,
+        };
+        use std::{ cell::RefCell, collections::{ BTreeMap, BTreeSet } };
+
+        thread_local! {
+            static COLLECTIONS: RefCell<BTreeMap<AccountId, MockTicketNFT>> =
+                RefCell::new(BTreeMap::new());
+        }
+
+        pub struct MockTicketNFT {
+            token_id_counter: u64,
+            tokens: BTreeMap<u64, AccountId>,
+            redeemed: BTreeSet<u64>,
+            /// Successful mints left before every further one fails with the given reason.
+            fail_after: Option<(u32, TicketNftError)>,
+            resale_cap: Option<(u16, Vec<Balance>)>,
+            royalty: Option<(AccountId, u16)>,
+            attributes: BTreeMap<(u64, Vec<u8>), Vec<u8>>,
+            metadata_frozen: bool,
+            ticket_data: BTreeMap<u64, TicketData>,
+            collections: BTreeMap<u64, EventCollection>,
+            transferable: bool,
+            transfer_lock: Option<u64>,
+            adopted: bool,
+        }
+
+        impl MockTicketNFT {
+            pub fn mint(
+                &mut self,
+                recipient: AccountId,
+                _token_uri: String,
+                _tier: u32
+            ) -> Result<u64, TicketNftError> {
+                if let Some((remaining, reason)) = self.fail_after.as_mut() {
+                    if *remaining == 0 {
+                        return Err(*reason);
+                    }
+                    *remaining -= 1;
+                }
+                let token_id = self.token_id_counter;
+                self.token_id_counter = token_id.checked_add(1).ok_or(TicketNftError::Overflow)?;
+                self.tokens.insert(token_id, recipient);
+                Ok(token_id)
+            }
+
+            /// Mints every URI or, like a reverted call, none of them.
+            pub fn mint_batch(
+                &mut self,
+                recipient: AccountId,
+                token_uris: Vec<String>,
+                data: TicketData
+            ) -> Result<Vec<u64>, TicketNftError> {
+                let first = self.token_id_counter;
+                let minted = token_uris
+                    .into_iter()
+                    .map(|token_uri| self.mint(recipient, token_uri, data.tier))
+                    .collect::<Result<Vec<_>, _>>();
+                match &minted {
+                    Ok(token_ids) => {
+                        for token_id in token_ids {
+                            self.ticket_data.insert(*token_id, data.clone());
+                        }
+                    }
+                    Err(_) => {
+                        (first..self.token_id_counter).for_each(|token_id| {
+                            self.tokens.remove(&token_id);
+                        });
+                        self.token_id_counter = first;
+                    }
+                }
+                minted
+            }
+
+            pub fn create_collection(
+                &mut self,
+                event_id: u64,
+                name: String,
+                max_supply: Option<u64>
+            ) -> Result<(), TicketNftError> {
+                if self.collections.contains_key(&event_id) {
+                    return Err(TicketNftError::CollectionExists);
+                }
+                self.collections.insert(event_id, EventCollection { name, max_supply, minted: 0 });
+                Ok(())
+            }
+
+            pub fn supports_interface(&self, interface_id: u32) -> bool {
+                interface_id == ticket_nft::TICKET_MINTER_INTERFACE_ID
+            }
+
+            pub fn adopt_collection(&mut self) -> Result<(), TicketNftError> {
+                self.adopted = true;
+                Ok(())
+            }
+
+            pub fn is_adopted(&self) -> bool {
+                self.adopted
+            }
+
+            pub fn collection(&self, event_id: u64) -> Option<EventCollection> {
+                self.collections.get(&event_id).cloned()
+            }
+
+            pub fn set_transferable(&mut self, transferable: bool) -> Result<(), TicketNftError> {
+                self.transferable = transferable;
+                Ok(())
+            }
+
+            pub fn is_transferable(&self) -> bool {
+                self.transferable
+            }
+
+            pub fn set_transfer_lock(&mut self, delay: Option<u64>) -> Result<(), TicketNftError> {
+                self.transfer_lock = delay;
+                Ok(())
+            }
+
+            pub fn transfer_lock(&self) -> Option<u64> {
+                self.transfer_lock
+            }
+
+            pub fn ticket_data(&self, token_id: u64) -> Option<TicketData> {
+                self.ticket_data.get(&token_id).cloned()
+            }
+
+            pub fn assign_seat(
+                &mut self,
+                token_id: u64,
+                seat: Option<u32>
+            ) -> Result<(), TicketNftError> {
+                let data = self.ticket_data.get_mut(&token_id);
+                data.ok_or(TicketNftError::TokenNotFound)?.seat = seat;
+                Ok(())
+            }
+
+            pub fn owner_of(&self, token_id: u64) -> Option<AccountId> {
+                self.tokens.get(&token_id).copied()
+            }
+
+            pub fn transfer(
+                &mut self,
+                from: AccountId,
+                to: AccountId,
+                token_id: u64
+            ) -> Result<(), PSP34Error> {
+                if self.tokens.get(&token_id) != Some(&from) {
+                    return Err(PSP34Error::TokenNotExists);
+                }
+                if !self.transferable {
+                    return Err(PSP34Error::Custom(String::from("NonTransferable")));
+                }
+                self.tokens.insert(token_id, to);
+                Ok(())
+            }
+
+            pub fn burn(&mut self, token_id: u64) -> Result<(), TicketNftError> {
+                self.tokens.remove(&token_id).map(|_| ()).ok_or(TicketNftError::TokenNotFound)
+            }
+
+            pub fn recover_ticket(
+                &mut self,
+                old_token_id: u64,
+                new_owner: AccountId
+            ) -> Result<u64, TicketNftError> {
+                if !self.tokens.contains_key(&old_token_id) {
+                    return Err(TicketNftError::TokenNotFound);
+                }
+                if self.redeemed.contains(&old_token_id) {
+                    return Err(TicketNftError::AlreadyRedeemed);
+                }
+                self.tokens.remove(&old_token_id);
+                let new_token_id = self.mint(new_owner, String::new(), 0)?;
+                if let Some(data) = self.ticket_data.remove(&old_token_id) {
+                    self.ticket_data.insert(new_token_id, data);
+                }
+                Ok(new_token_id)
+            }
+
+            pub fn redeem(&mut self, token_id: u64) -> Result<(), TicketNftError> {
+                if !self.tokens.contains_key(&token_id) {
+                    return Err(TicketNftError::TokenNotFound);
+                }
+                if self.redeemed.contains(&token_id) {
+                    return Err(TicketNftError::AlreadyRedeemed);
+                }
+                let expires_at = self.ticket_data.get(&token_id).and_then(|data| data.expires_at);
+                let now = ink::env::block_timestamp::<ink::env::DefaultEnvironment>();
+                if expires_at.map_or(false, |expires_at| now > expires_at) {
+                    return Err(TicketNftError::Expired);
+                }
+                self.redeemed.insert(token_id);
+                Ok(())
+            }
+
+            pub fn is_redeemed(&self, token_id: u64) -> bool {
+                self.redeemed.contains(&token_id)
+            }
+
+            pub fn mint_badge(&mut self, recipient: AccountId) -> Result<u64, TicketNftError> {
+                self.mint(recipient, String::new(), 0)
+            }
+
+            pub fn set_resale_cap(
+                &mut self,
+                cap_bps: Option<u16>,
+                face_values: Vec<Balance>
+            ) -> Result<(), TicketNftError> {
+                self.resale_cap = cap_bps.map(|cap_bps| (cap_bps, face_values));
+                Ok(())
+            }
+
+            pub fn resale_cap(&self) -> Option<(u16, Vec<Balance>)> {
+                self.resale_cap.clone()
+            }
+
+            pub fn set_royalty(
+                &mut self,
+                beneficiary: AccountId,
+                royalty_bps: u16
+            ) -> Result<(), TicketNftError> {
+                self.royalty = (royalty_bps > 0).then_some((beneficiary, royalty_bps));
+                Ok(())
+            }
+
+            pub fn royalty(&self) -> Option<(AccountId, u16)> {
+                self.royalty
+            }
+
+            pub fn set_attribute(
+                &mut self,
+                token_id: u64,
+                key: Vec<u8>,
+                value: Vec<u8>
+            ) -> Result<(), TicketNftError> {
+                if self.metadata_frozen {
+                    return Err(TicketNftError::MetadataFrozen);
+                }
+                self.attributes.insert((token_id, key), value);
+                Ok(())
+            }
+
+            pub fn attribute(&self, token_id: u64, key: &[u8]) -> Option<Vec<u8>> {
+                self.attributes.get(&(token_id, key.to_vec())).cloned()
+            }
+
+            pub fn freeze_metadata(&mut self) -> Result<(), TicketNftError> {
+                self.metadata_frozen = true;
+                Ok(())
+            }
+
+            /// Lets `successes` more mints through, then rejects the rest with `reason`.
+            pub fn fail_after(&mut self, successes: u32, reason: TicketNftError) {
+                self.fail_after = Some((successes, reason));
+            }
+        }
+
+        /// Deploys an empty collection at `address`, replacing any earlier one.
+        pub fn deploy(address: AccountId) {
+            let nft = MockTicketNFT {
+                token_id_counter: 1,
+                tokens: BTreeMap::new(),
+                redeemed: BTreeSet::new(),
+                fail_after: None,
+                resale_cap: None,
+                royalty: None,
+                attributes: BTreeMap::new(),
+                metadata_frozen: false,
+                ticket_data: BTreeMap::new(),
+                collections: BTreeMap::new(),
+                transferable: true,
+                transfer_lock: None,
+                adopted: false,
+            };
+            COLLECTIONS.with(|collections| collections.borrow_mut().insert(address, nft));
+        }
+
+        pub fn with<R>(address: AccountId, f: impl FnOnce(&mut MockTicketNFT) -> R) -> R {
+            COLLECTIONS.with(|collections| {
+                let mut collections = collections.borrow_mut();
+                f(collections.get_mut(&address).expect("no mock collection at this address"))
+            })
+        }
+    }
+
+    /// Off-chain stand-in for a PSP22 token, keyed by its address like `mock_ticket_nft`.
+    /// Methods take the calling account explicitly since there's no real call context.
+    #[cfg(test)]
+    mod mock_psp22 {
+        use super::{ AccountId, Balance, PSP22Error };
+        use std::{ cell::RefCell, collections::BTreeMap };
+
+        thread_local! {
+            static TOKENS: RefCell<BTreeMap<AccountId, MockPSP22>> = RefCell::new(BTreeMap::new());
+        }
+
+        #[derive(Default)]
+        pub struct MockPSP22 {
+            balances: BTreeMap<AccountId, Balance>,
+            allowances: BTreeMap<(AccountId, AccountId), Balance>,
+        }
+
+        impl MockPSP22 {
+            pub fn balance_of(&self, owner: AccountId) -> Balance {
+                self.balances.get(&owner).copied().unwrap_or(0)
+            }
+
+            pub fn mint(&mut self, to: AccountId, value: Balance) {
+                *self.balances.entry(to).or_default() += value;
+            }
+
+            pub fn approve(&mut self, owner: AccountId, spender: AccountId, value: Balance) {
+                self.allowances.insert((owner, spender), value);
+            }
+
+            pub fn transfer(
+                &mut self,
+                from: AccountId,
+                to: AccountId,
+                value: Balance
+            ) -> Result<(), PSP22Error> {
+                let balance = self.balance_of(from);
+                if balance < value {
+                    return Err(PSP22Error::InsufficientBalance);
+                }
+                self.balances.insert(from, balance - value);
+                self.mint(to, value);
+                Ok(())
+            }
+
+            pub fn transfer_from(
+                &mut self,
+                spender: AccountId,
+                from: AccountId,
+                to: AccountId,
+                value: Balance
+            ) -> Result<(), PSP22Error> {
+                let allowance = self.allowances.get(&(from, spender)).copied().unwrap_or(0);
+                if allowance < value {
+                    return Err(PSP22Error::InsufficientAllowance);
+                }
+                self.transfer(from, to, value)?;
+                self.allowances.insert((from, spender), allowance - value);
+                Ok(())
+            }
+        }
+
+        /// Deploys a token with no balances at `address`, replacing any earlier one.
+        pub fn deploy(address: AccountId) {
+            TOKENS.with(|tokens| tokens.borrow_mut().insert(address, MockPSP22::default()));
+        }
+
+        pub fn with<R>(address: AccountId, f: impl FnOnce(&mut MockPSP22) -> R) -> R {
+            TOKENS.with(|tokens| {
+                let mut tokens = tokens.borrow_mut();
+                f(tokens.get_mut(&address).expect("no mock token at this address"))
+            })
+        }
+    }
+
+    /// Off-chain stand-in for a `PriceOracle`, quoting whatever a test sets.
+    #[cfg(test)]
+    mod mock_price_oracle {
+        use super::{ AccountId, String };
+        use std::{ cell::RefCell, collections::BTreeMap };
+
+        thread_local! {
+            static QUOTES: RefCell<BTreeMap<(AccountId, String), (u128, u64)>> =
+                RefCell::new(BTreeMap::new());
+        }
+
+        pub fn set_price(oracle: AccountId, pair: &str, price: u128, updated_at: u64) {
+            QUOTES.with(|quotes| {
+                quotes.borrow_mut().insert((oracle, pair.into()), (price, updated_at))
+            });
+        }
+
+        pub fn latest_price(oracle: AccountId, pair: &str) -> Option<(u128, u64)> {
+            QUOTES.with(|quotes| quotes.borrow().get(&(oracle, pair.into())).copied())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const EVENT_DATE: Timestamp = 1_733_011_200_000; // 2024-12-01
+
+        fn native_currency() -> CurrencyInfo {
+            CurrencyInfo {
+                symbol: "DOT".to_string(),
+                decimals: 10,
+            }
+        }
+
+        fn general_admission(price: Balance, max: u64) -> TicketTier {
+            TicketTier {
+                name: "General Admission".to_string(),
+                price,
+                max,
+                sold: 0,
+            }
+        }
+
+        /// A general admission concert on `EVENT_DATE`; tests override what they exercise.
+        fn concert() -> EventDetails {
+            EventDetails {
+                title: "Concert".to_string(),
+                date: EVENT_DATE,
+                location: "Stadium".to_string(),
+                tiers: vec![general_admission(1_000_000, 100)],
+                max_tickets_per_account: 0,
+                sales_start: 0,
+                sales_end: EVENT_DATE,
+                price_schedule: vec![],
+                dutch_auction: None,
+                category: 0,
+                tags: vec![],
+                usd_priced: false,
+                refund_policy: vec![],
+                funding_goal: None,
+                payees: vec![],
+            }
+        }
+
+        /// Mapping hands out copies, so seeded state has to be written back.
+        fn edit_event(contract: &mut EventManager, event_id: u64, edit: impl FnOnce(&mut Event)) {
+            let mut event = contract.events.get(event_id).unwrap();
+            edit(&mut event);
+            contract.events.insert(event_id, &event);
+        }
+
+        #[ink::test]
+        fn test_create_event() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 1); // EventCreated
+
+            let info = contract.get_event(event_id).unwrap();
+            assert_eq!(info.title, "Concert");
+            assert_eq!(info.host, contract.env().caller());
+            assert_eq!(info.tickets_sold, 0);
+            assert_eq!(info.tickets_remaining, 100);
+            assert_eq!(info.status, EventStatus::Active);
+            assert_eq!(info.currency, native_currency());
+            assert_eq!(contract.get_event(event_id + 1), None);
+
+            let event_details = contract.get_event_details(event_id);
+            assert!(event_details.is_some());
+            let event_details = event_details.unwrap();
+            assert_eq!(event_details.title, "Concert");
+            assert_eq!(event_details.date, EVENT_DATE);
+            assert_eq!(event_details.location, "Stadium");
+            assert_eq!(event_details.tiers, vec![general_admission(1_000_000, 100)]);
+            assert_eq!(event_details.max_tickets(), 100);
+        }
+
+        #[ink::test]
+        fn test_get_events_paginates() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            assert_eq!(contract.event_count(), 0);
+            for _ in 0..5 {
+                contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            }
+            assert_eq!(contract.event_count(), 5);
+
+            let ids = |page: Vec<(u64, EventDetails)>| {
+                page.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            };
+            assert_eq!(ids(contract.get_events(0, 2)), vec![1, 2]);
+            assert_eq!(ids(contract.get_events(2, 2)), vec![3, 4]);
+            assert_eq!(ids(contract.get_events(4, 2)), vec![5]);
+            assert!(contract.get_events(5, 2).is_empty());
+        }
+
+        #[ink::test]
+        fn test_get_events_by_host() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            contract.register_event(details, ticket_nft_address).unwrap();
+
+            let ids = |page: Vec<(u64, EventDetails)>| {
+                page.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            };
+            assert_eq!(ids(contract.get_events_by_host(accounts.alice, 0, 10)), vec![1]);
+            assert_eq!(ids(contract.get_events_by_host(accounts.bob, 0, 10)), vec![2, 3]);
+            assert_eq!(ids(contract.get_events_by_host(accounts.bob, 1, 10)), vec![3]);
+            assert!(contract.get_events_by_host(accounts.charlie, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn test_get_events_by_category_and_tag() {
+            let mut contract = EventManager::new(native_currency());
+            let rock = EventManager::hash(b"rock");
+            let details = EventDetails {
+                category: 1,
+                tags: vec![rock, rock],
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let concert = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            let meetup = EventDetails { category: 3, tags: vec![], ..details.clone() };
+            let meetup = contract.register_event(meetup, ticket_nft_address).unwrap();
+
+            let ids = |page: Vec<(u64, EventDetails)>| {
+                page.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            };
+            assert_eq!(ids(contract.get_events_by_category(1, 0, 10)), vec![concert]);
+            assert_eq!(ids(contract.get_events_by_category(3, 0, 10)), vec![meetup]);
+            assert!(contract.get_events_by_category(2, 0, 10).is_empty());
+            assert_eq!(ids(contract.get_events_by_tag(rock, 0, 10)), vec![concert]);
+
+            let too_many_tags = EventDetails { tags: vec![rock; MAX_TAGS + 1], ..details };
+            assert_eq!(
+                contract.register_event(too_many_tags, ticket_nft_address),
+                Err(Error::TooManyTags)
+            );
+        }
+
+        #[ink::test]
+        fn test_get_active_events() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let open = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            let deactivated = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            let future_sale = EventDetails { sales_start: EVENT_DATE - 1, ..details.clone() };
+            contract.register_event(future_sale, ticket_nft_address).unwrap();
+            let cancelled = contract.register_event(details, ticket_nft_address).unwrap();
+
+            contract.deactivate_event(deactivated).unwrap();
+            contract.cancel_event(cancelled).unwrap();
+
+            let ids = |page: Vec<(u64, EventDetails)>| {
+                page.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            };
+            assert_eq!(ids(contract.get_active_events(0, 10)), vec![open]);
+            assert!(contract.get_active_events(1, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn test_tickets_remaining() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            assert_eq!(contract.tickets_remaining(event_id), Some(100));
+            assert_eq!(contract.is_sold_out(event_id), Some(false));
+
+            edit_event(&mut contract, event_id, |e| e.tickets_sold = 100);
+            assert_eq!(contract.tickets_remaining(event_id), Some(0));
+            assert_eq!(contract.is_sold_out(event_id), Some(true));
+
+            assert_eq!(contract.tickets_remaining(event_id + 1), None);
+            assert_eq!(contract.is_sold_out(event_id + 1), None);
+        }
+
+        #[ink::test]
+        fn test_purchase_ticket() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            // Attempting to purchase a ticket without sending any balance should fail
+            let result = contract.purchase_ticket(event_id, 0, "TicketURI".to_string());
+            assert_eq!(result, Err(Error::InsufficientPayment)); // No payment was made
+        }
+
+        #[ink::test]
+        fn test_change_due() {
+            // Exact payment
+            assert_eq!(EventManager::change_due(1_000_000, 1_000_000), Ok(0));
+            // Overpayment is returned
+            assert_eq!(EventManager::change_due(1_000_000, 1_500_000), Ok(500_000));
+            // Underpayment is rejected
+            assert_eq!(
+                EventManager::change_due(1_000_000, 999_999),
+                Err(Error::InsufficientPayment)
+            );
+        }
+
+        #[ink::test]
+        fn test_purchase_tickets_validates_quantity() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                tiers: vec![general_admission(1_000_000, 3)],
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let uris = |n: usize| vec!["TicketURI".to_string(); n];
+
+            assert_eq!(
+                contract.purchase_tickets(event_id, 0, 0, uris(0)),
+                Err(Error::InvalidQuantity)
+            );
+            assert_eq!(
+                contract.purchase_tickets(event_id, 0, 2, uris(3)),
+                Err(Error::InvalidQuantity)
+            );
+
+            // More tickets than the event has left
+            assert_eq!(contract.purchase_tickets(event_id, 0, 4, uris(4)), Err(Error::SoldOut));
+
+            // Payment must cover every ticket
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(
+                contract.purchase_tickets(event_id, 0, 2, uris(2)),
+                Err(Error::InsufficientPayment)
+            );
+        }
+
+        #[ink::test]
+        fn test_ticket_tiers() {
+            let mut contract = EventManager::new(native_currency());
+            let vip = TicketTier {
+                name: "VIP".to_string(),
+                price: 5_000_000,
+                max: 1,
+                sold: 7, // Ignored on creation
+            };
+            let details = EventDetails {
+                tiers: vec![general_admission(1_000_000, 100), vip],
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+
+            let stored = contract.get_event_details(event_id).unwrap();
+            assert_eq!(stored.max_tickets(), 101);
+            assert_eq!(stored.tiers[1].sold, 0);
+
+            assert_eq!(
+                contract.purchase_ticket(event_id, 2, "TicketURI".to_string()),
+                Err(Error::TierNotFound)
+            );
+            let uris = vec!["TicketURI".to_string(); 2];
+            assert_eq!(contract.purchase_tickets(event_id, 1, 2, uris), Err(Error::SoldOut));
+
+            // VIP price must be paid in full
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 1, "TicketURI".to_string()),
+                Err(Error::InsufficientPayment)
+            );
+
+            let no_tiers = EventDetails { tiers: vec![], ..details };
+            assert_eq!(contract.register_event(no_tiers, ticket_nft_address), Err(Error::NoTiers));
+        }
+
+        #[ink::test]
+        fn test_sales_window() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                sales_start: 1_000,
+                sales_end: 2_000,
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+
+            let now = contract.env().block_timestamp();
+            assert!(now < 1_000);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::SalesNotStarted)
+            );
+
+            let closed = EventDetails { sales_start: 0, sales_end: 0, ..details.clone() };
+            let closed_id = contract.register_event(closed, ticket_nft_address).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(
+                contract.purchase_ticket(closed_id, 0, "TicketURI".to_string()),
+                Err(Error::SalesEnded)
+            );
+
+            let inverted = EventDetails { sales_start: 2_000, sales_end: 1_000, ..details };
+            assert_eq!(
+                contract.register_event(inverted, ticket_nft_address),
+                Err(Error::InvalidSalesWindow)
+            );
+        }
+
+        #[ink::test]
+        fn test_allowlist_presale() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Two-leaf tree of bob and charlie
+            let bob_leaf = EventManager::hash(accounts.bob.as_ref());
+            let charlie_leaf = EventManager::hash(accounts.charlie.as_ref());
+            let mut pair = [0u8; 64];
+            let (first, second) = if bob_leaf <= charlie_leaf {
+                (bob_leaf, charlie_leaf)
+            } else {
+                (charlie_leaf, bob_leaf)
+            };
+            pair[..32].copy_from_slice(&first);
+            pair[32..].copy_from_slice(&second);
+            let root = EventManager::hash(&pair);
+
+            assert_eq!(contract.set_presale(event_id, root, EVENT_DATE - 1), Ok(()));
+
+            // Alice is not on the allowlist
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::NotAllowlisted)
+            );
+
+            // Bob's proof passes the allowlist and fails later on payment
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.purchase_ticket_with_proof(
+                    event_id,
+                    0,
+                    "TicketURI".to_string(),
+                    vec![charlie_leaf]
+                ),
+                Err(Error::InsufficientPayment)
+            );
+            assert_eq!(contract.set_presale(event_id, root, 0), Err(Error::NotHost));
+        }
+
+        #[ink::test]
+        fn test_early_bird_price_schedule() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                tiers: vec![general_admission(1_000_000, 300)],
+                price_schedule: vec![
+                    PriceBreakpoint { until_sold: 50, multiplier_bps: 8_000 },
+                    PriceBreakpoint { until_sold: 150, multiplier_bps: 10_000 },
+                    PriceBreakpoint { until_sold: u64::MAX, multiplier_bps: 12_000 }
+                ],
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            assert_eq!(contract.current_price(event_id, 0), Some(800_000));
+            assert_eq!(contract.current_price(event_id, 1), None);
+
+            edit_event(&mut contract, event_id, |e| e.tickets_sold = 50);
+            assert_eq!(contract.current_price(event_id, 0), Some(1_000_000));
+            edit_event(&mut contract, event_id, |e| e.tickets_sold = 150);
+            assert_eq!(contract.current_price(event_id, 0), Some(1_200_000));
+
+            // Breakpoints must be strictly increasing
+            let unordered = EventDetails {
+                price_schedule: vec![
+                    PriceBreakpoint { until_sold: 150, multiplier_bps: 10_000 },
+                    PriceBreakpoint { until_sold: 50, multiplier_bps: 8_000 }
+                ],
+                ..details
+            };
+            assert_eq!(
+                contract.register_event(unordered, ticket_nft_address),
+                Err(Error::InvalidPriceSchedule)
+            );
+        }
+
+        #[ink::test]
+        fn test_dutch_auction_price() {
+            let details = EventDetails {
+                sales_start: 1_000,
+                sales_end: 2_000,
+                dutch_auction: Some(DutchAuction { start_bps: 20_000, floor_bps: 10_000 }),
+                ..concert()
+            };
+
+            // Starts at 2x, halfway through the window is 1.5x, then stays at the floor
+            assert_eq!(EventManager::price_at(&details, 1_000_000, 0, 0), Some(2_000_000));
+            assert_eq!(EventManager::price_at(&details, 1_000_000, 0, 1_500), Some(1_500_000));
+            assert_eq!(EventManager::price_at(&details, 1_000_000, 0, 2_000), Some(1_000_000));
+            assert_eq!(EventManager::price_at(&details, 1_000_000, 0, 9_000), Some(1_000_000));
+
+            let mut contract = EventManager::new(native_currency());
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            assert_eq!(contract.current_price(event_id, 0), Some(2_000_000));
+
+            let inverted = EventDetails {
+                dutch_auction: Some(DutchAuction { start_bps: 5_000, floor_bps: 10_000 }),
+                ..details
+            };
+            assert_eq!(
+                contract.register_event(inverted, ticket_nft_address),
+                Err(Error::InvalidAuction)
+            );
+        }
+
+        #[ink::test]
+        fn test_promo_codes() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let code_hash = EventManager::hash(b"HALFPRICE");
+            assert_eq!(contract.add_promo_code(event_id, code_hash, 5_000, 1), Ok(()));
+            assert_eq!(EventManager::apply_discount(1_000_000, 5_000), Some(500_000));
+
+            let buy = |contract: &mut EventManager, code: &str| {
+                contract.purchase_ticket_with_code(
+                    event_id,
+                    0,
+                    "TicketURI".to_string(),
+                    code.to_string()
+                )
+            };
+
+            assert_eq!(buy(&mut contract, "FREE"), Err(Error::InvalidPromoCode));
+
+            // The discounted price still has to be paid
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(400_000);
+            assert_eq!(buy(&mut contract, "HALFPRICE"), Err(Error::InsufficientPayment));
+
+            let mut promo = contract.promo_codes.get((event_id, code_hash)).unwrap();
+            promo.redemptions = 1;
+            contract.promo_codes.insert((event_id, code_hash), &promo);
+            assert_eq!(buy(&mut contract, "HALFPRICE"), Err(Error::PromoCodeExhausted));
+
+            assert_eq!(contract.revoke_promo_code(event_id, code_hash), Ok(()));
+            assert!(contract.get_promo_code(event_id, code_hash).unwrap().revoked);
+            assert_eq!(buy(&mut contract, "HALFPRICE"), Err(Error::InvalidPromoCode));
+        }
+
+        #[ink::test]
+        fn test_lottery_sale() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let registration_end = contract.env().block_timestamp() + 1;
+            let seed = [7u8; 32];
+            let commitment = EventManager::hash(&seed);
+            let result =
+                contract.set_lottery(event_id, registration_end, 1_000, 5, 100, commitment);
+            assert_eq!(result, Ok(()));
+
+            // Deposit has to match exactly
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.register_for_lottery(event_id), Err(Error::InsufficientPayment));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(contract.register_for_lottery(event_id), Ok(()));
+            assert_eq!(contract.register_for_lottery(event_id), Err(Error::AlreadyRegistered));
+
+            // No sales until the draw
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::LotteryNotDrawn)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.draw_winners(event_id, seed), Err(Error::LotteryClosed));
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(contract.register_for_lottery(event_id), Err(Error::LotteryClosed));
+
+            // Only the committed seed draws
+            assert_eq!(contract.draw_winners(event_id, [8u8; 32]), Err(Error::InvalidSeed));
+
+            // Fewer entrants than winner slots, so bob wins
+            assert_eq!(contract.draw_winners(event_id, seed), Ok(1));
+            assert_eq!(contract.draw_winners(event_id, seed), Err(Error::LotteryAlreadyDrawn));
+            assert!(contract.get_lottery_entry(event_id, accounts.bob).unwrap().won);
+
+            // Alice didn't win and is still inside the claim window
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::NotLotteryWinner)
+            );
+
+            // The audit expects bob's deposit to still be in the contract
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                99
+            );
+            let report = contract.audit();
+            assert_eq!(report.lottery_deposits_held, 100);
+            assert!(!report.balance_covers_revenue);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                100
+            );
+            assert!(contract.audit().balance_covers_revenue);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_lottery_deposit(event_id), Ok(100));
+            assert_eq!(contract.claim_lottery_deposit(event_id), Err(Error::NothingToRefund));
+            assert_eq!(contract.audit().lottery_deposits_held, 0);
+        }
+
+        #[ink::test]
+        fn test_lottery_draw_is_committed() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let seed = [7u8; 32];
+            let commitment = EventManager::hash(&seed);
+            assert!(contract.set_lottery(event_id, 10, 1_000, 1, 100, commitment).is_ok());
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            for entrant in [accounts.bob, accounts.charlie, accounts.django, accounts.eve] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(entrant);
+                assert_eq!(contract.register_for_lottery(event_id), Ok(()));
+            }
+
+            // Once entries are in, the host can't swap in a seed that favours someone
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let other_commitment = EventManager::hash(&[0; 32]);
+            let result = contract.set_lottery(event_id, 10, 1_000, 1, 100, other_commitment);
+            assert_eq!(result, Err(Error::LotteryClosed));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(10);
+            for other in 0..=255u8 {
+                if other != seed[0] {
+                    let result = contract.draw_winners(event_id, [other; 32]);
+                    assert_eq!(result, Err(Error::InvalidSeed));
+                }
+            }
+
+            // Left unrevealed, the lottery is void: deposits come back and sales open
+            let deadline = 10 + LOTTERY_REVEAL_WINDOW;
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(deadline);
+            assert_eq!(contract.draw_winners(event_id, seed), Err(Error::LotteryForfeited));
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                400
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.claim_lottery_deposit(event_id), Ok(100));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(contract.purchase_ticket(event_id, 0, String::new()), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_waitlist_reserves_freed_slot() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                tiers: vec![general_admission(1_000_000, 1)],
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.join_waitlist(event_id), Err(Error::NotSoldOut));
+
+            // Sell the only ticket, then bob queues up
+            edit_event(&mut contract, event_id, |e| {
+                e.tickets_sold = 1;
+                e.details.tiers[0].sold = 1;
+            });
+            assert_eq!(contract.join_waitlist(event_id), Ok(()));
+            assert_eq!(contract.join_waitlist(event_id), Err(Error::AlreadyWaitlisted));
+
+            // The ticket is given back and the slot is held for bob
+            edit_event(&mut contract, event_id, |e| e.details.tiers[0].sold = 0);
+            contract.release_slot(event_id, 0);
+            assert!(contract.get_waitlist(event_id, 0, 10).is_empty());
+            assert_eq!(contract.get_held_slots(event_id, 0, 10)[0].account, accounts.bob);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::SoldOut)
+            );
+
+            // Bob gets past the capacity check
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::InsufficientPayment)
+            );
+        }
+
+        #[ink::test]
+        fn test_max_tickets_per_account() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                max_tickets_per_account: 2,
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let caller = contract.env().caller();
+
+            let uris = vec!["TicketURI".to_string(); 3];
+            assert_eq!(
+                contract.purchase_tickets(event_id, 0, 3, uris),
+                Err(Error::PurchaseLimitExceeded)
+            );
+
+            // Already bought the allowance
+            contract.purchase_counts.insert((event_id, caller), &2);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::PurchaseLimitExceeded)
+            );
+            assert_eq!(contract.get_purchase_count(event_id, caller), 2);
+
+            // The host can lift the limit before sales start, but not after
+            assert_eq!(contract.set_max_tickets_per_account(event_id, 4), Ok(()));
+            edit_event(&mut contract, event_id, |e| e.tickets_sold = 2);
+            assert_eq!(contract.set_max_tickets_per_account(event_id, 6), Err(Error::SalesStarted));
+        }
+
+        #[ink::test]
+        fn test_update_event_details() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+
+            // Before any sale, pricing can change too
+            let cheaper = EventDetails {
+                tiers: vec![general_admission(500_000, 100)],
+                category: 2,
+                ..details.clone()
+            };
+            assert_eq!(contract.update_event_details(event_id, cheaper), Ok(()));
+            assert_eq!(contract.get_event_details(event_id).unwrap().tiers[0].price, 500_000);
+            assert!(contract.get_events_by_category(0, 0, 10).is_empty());
+            assert_eq!(contract.get_events_by_category(2, 0, 10).len(), 1);
+
+            // After a sale only non-financial fields may change
+            edit_event(&mut contract, event_id, |e| {
+                e.tickets_sold = 1;
+                e.details.tiers[0].sold = 1;
+            });
+            assert_eq!(
+                contract.update_event_details(event_id, details.clone()),
+                Err(Error::SalesStarted)
+            );
+            let renamed = EventDetails {
+                title: "Open Air Concert".to_string(),
+                location: "Park".to_string(),
+                tiers: vec![general_admission(500_000, 100)],
+                category: 2,
+                ..details.clone()
+            };
+            assert_eq!(contract.update_event_details(event_id, renamed), Ok(()));
+            let stored = contract.get_event_details(event_id).unwrap();
+            assert_eq!(stored.title, "Open Air Concert");
+            assert_eq!(stored.tiers[0].sold, 1);
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.update_event_details(event_id, details.clone()),
+                Err(Error::NotHost)
+            );
+        }
+
+        #[ink::test]
+        fn test_event_operators() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(contract.add_event_operator(event_id, accounts.bob), Ok(()));
+            assert!(contract.is_event_operator(event_id, accounts.bob));
+
+            // Operators can edit the listing but not its pricing, funds or lifecycle
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let moved = EventDetails { location: "Arena".to_string(), ..details.clone() };
+            assert_eq!(contract.update_event_details(event_id, moved), Ok(()));
+            let repriced = EventDetails {
+                tiers: vec![general_admission(2_000_000, 100)],
+                ..details.clone()
+            };
+            assert_eq!(contract.update_event_details(event_id, repriced), Err(Error::NotHost));
+            assert_eq!(contract.cancel_event(event_id), Err(Error::NotHost));
+            assert_eq!(contract.claim_revenue(event_id), Err(Error::NotHost));
+            assert_eq!(
+                contract.add_event_operator(event_id, accounts.charlie),
+                Err(Error::NotHost)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.remove_event_operator(event_id, accounts.bob), Ok(()));
+            assert_eq!(
+                contract.remove_event_operator(event_id, accounts.bob),
+                Err(Error::NotOperator)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.update_event_details(event_id, details), Err(Error::NotHost));
+        }
+
+        #[ink::test]
+        fn test_host_transfer() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(contract.accept_host_transfer(event_id), Err(Error::NoPendingHostTransfer));
+            assert_eq!(contract.propose_host_transfer(event_id, accounts.bob), Ok(()));
+            assert_eq!(contract.get_pending_host(event_id), Some(accounts.bob));
+
+            // Only the nominee can accept, and the old host stays in charge until then
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.accept_host_transfer(event_id), Err(Error::NotPendingHost));
+            assert_eq!(contract.get_event(event_id).unwrap().host, accounts.alice);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.accept_host_transfer(event_id), Ok(()));
+            assert_eq!(contract.get_event(event_id).unwrap().host, accounts.bob);
+            assert_eq!(contract.get_pending_host(event_id), None);
+            assert!(contract.get_events_by_host(accounts.alice, 0, 10).is_empty());
+            assert_eq!(contract.get_events_by_host(accounts.bob, 0, 10).len(), 1);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.deactivate_event(event_id), Err(Error::NotHost));
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            // EventCreated, HostTransferProposed, HostTransferred
+            assert_eq!(emitted_events.len(), 3);
+        }
+
+        #[ink::test]
+        fn test_deactivate_event() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            // Ensure the event is active
+            let event = contract.get_event(event_id).unwrap();
+            assert_eq!(event.status, EventStatus::Active);
+
+            // Deactivate the event
+            let result = contract.deactivate_event(event_id);
+            assert!(result.is_ok());
+
+            // Verify the event is deactivated
+            let event = contract.get_event(event_id).unwrap();
+            assert_eq!(event.status, EventStatus::Inactive);
+            assert!(contract.get_active_events(0, 10).is_empty());
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 2); // EventCreated, EventDeactivated
+
+            // Reactivate it again
+            assert_eq!(contract.reactivate_event(event_id), Ok(()));
+            assert_eq!(contract.reactivate_event(event_id), Err(Error::EventActive));
+            assert_eq!(contract.get_event(event_id).unwrap().status, EventStatus::Active);
+            assert_eq!(contract.get_active_events(0, 10).len(), 1);
+
+            // Once the event date has passed it stays inactive
+            contract.deactivate_event(event_id).unwrap();
+            edit_event(&mut contract, event_id, |e| e.details.date = 0);
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(contract.reactivate_event(event_id), Err(Error::EventEnded));
+
+            // Cancellation is final
+            edit_event(&mut contract, event_id, |e| e.status = EventStatus::Cancelled);
+            assert_eq!(contract.deactivate_event(event_id), Err(Error::EventCancelled));
+            assert_eq!(contract.reactivate_event(event_id), Err(Error::EventCancelled));
+        }
+
+        #[ink::test]
+        fn test_deactivate_event_errors() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            assert_eq!(contract.deactivate_event(event_id + 1), Err(Error::EventNotFound));
+
+            // Only the host may deactivate
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.deactivate_event(event_id), Err(Error::NotHost));
+        }
+
+        #[ink::test]
+        fn test_claim_revenue_is_escrowed_until_event_date() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            // Nothing has been sold yet and the event is still in the future
+            assert_eq!(contract.get_event_revenue(event_id), Some((0, 0)));
+            assert_eq!(contract.claim_revenue(event_id), Err(Error::EventNotEnded));
+
+            // Only the host may claim
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_revenue(event_id), Err(Error::NotHost));
+        }
+
+        #[ink::test]
+        fn test_claim_revenue_after_event_date() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                date: contract.env().block_timestamp() + 1,
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            assert_eq!(contract.is_upcoming(event_id), Some(true));
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(contract.has_ended(event_id), Some(true));
+
+            // The escrow is released, but there is no revenue to pay out
+            assert_eq!(contract.claim_revenue(event_id), Err(Error::NothingToWithdraw));
+
+            // A finished event can no longer be cancelled
+            assert_eq!(contract.cancel_event(event_id), Err(Error::EventEnded));
+        }
+
+        #[ink::test]
+        fn test_create_event_in_past() {
+            let mut contract = EventManager::new(native_currency());
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            let details = EventDetails {
+                date: contract.env().block_timestamp(),
+                sales_end: 0,
+                ..concert()
+            };
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            assert_eq!(
+                contract.register_event(details, ticket_nft_address),
+                Err(Error::EventInPast)
+            );
+            assert_eq!(contract.is_upcoming(1), None);
+            assert_eq!(contract.has_ended(1), None);
+        }
+
+        #[ink::test]
+        fn test_cancel_event_and_claim_refund() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Seed a purchase by bob straight into storage
+            mock_ticket_nft::with(ticket_nft_address, |nft| {
+                nft.mint(accounts.bob, "TicketURI".to_string(), 0)
+            }).unwrap();
+            contract.purchases.insert((event_id, accounts.bob), &Purchase {
+                paid: 1_000_000,
+                fee: 0,
+                token_ids: vec![1],
+                refunded: false,
+            });
+            edit_event(&mut contract, event_id, |e| e.revenue = 1_000_000);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                1_000_000
+            ).unwrap();
+
+            assert_eq!(contract.claim_refund(event_id), Err(Error::EventNotCancelled));
+            assert!(contract.cancel_event(event_id).is_ok());
+            assert_eq!(contract.cancel_event(event_id), Err(Error::EventCancelled));
+            assert_eq!(contract.claim_revenue(event_id), Err(Error::EventCancelled));
+
+            // Alice never bought a ticket
+            assert_eq!(contract.claim_refund(event_id), Err(Error::NothingToRefund));
+
+            contract.ticket_counts.insert((event_id, accounts.bob), &1);
+            assert!(contract.has_ticket(event_id, accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_refund(event_id), Ok(1_000_000));
+            assert!(!contract.has_ticket(event_id, accounts.bob));
+            assert!(contract.is_ticket_invalidated(event_id, 1));
+            assert_eq!(contract.get_event_revenue(event_id), Some((0, 0)));
+
+            // A second claim pays nothing, and the record keeps nothing to pay out
+            assert_eq!(contract.claim_refund(event_id), Err(Error::NothingToRefund));
+            assert_eq!(contract.get_purchase(event_id, accounts.bob), Some(Purchase {
+                paid: 0,
+                fee: 0,
+                token_ids: vec![],
+                refunded: true,
+            }));
+        }
+
+        #[ink::test]
+        fn test_transfer_to_refunded_holder() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            for buyer in [accounts.bob, accounts.charlie] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(buyer);
+                ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
+            }
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                2_000_000
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            contract.cancel_event(event_id).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_refund(event_id), Ok(1_000_000));
+
+            // Charlie's unrefunded ticket reaches bob, who can then claim it once
+            mock_ticket_nft::with(ticket_nft_address, |nft| {
+                nft.transfer(accounts.charlie, accounts.bob, 2)
+            }).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(contract.on_ticket_transfer(accounts.charlie, accounts.bob, 2), Ok(()));
+            let purchase = contract.get_purchase(event_id, accounts.bob).unwrap();
+            assert_eq!((purchase.paid, purchase.token_ids), (1_000_000, vec![2]));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.claim_refund(event_id), Err(Error::NothingToRefund));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_refund(event_id), Ok(1_000_000));
+            assert_eq!(contract.claim_refund(event_id), Err(Error::NothingToRefund));
+            assert_eq!(contract.get_event_revenue(event_id), Some((0, 2_000_000)));
+        }
+
+        #[ink::test]
+        fn test_process_refunds() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            for buyer in [accounts.bob, accounts.charlie, accounts.django] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(buyer);
+                ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
+            }
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                3_000_000
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.process_refunds(event_id, 2), Err(Error::EventNotCancelled));
+            contract.cancel_event(event_id).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_refund(event_id), Ok(1_000_000));
+            assert_eq!(contract.process_refunds(event_id, 2), Err(Error::NotHost));
+
+            let balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .unwrap()
+            };
+            let before = [accounts.bob, accounts.charlie, accounts.django].map(balance);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            // Bob already claimed, so the first chunk pays only charlie
+            assert_eq!(contract.process_refunds(event_id, 2), Ok(2));
+            assert_eq!(balance(accounts.bob), before[0]);
+            assert_eq!(balance(accounts.charlie), before[1] + 1_000_000);
+            assert_eq!(balance(accounts.django), before[2]);
+            assert_eq!(contract.process_refunds(event_id, 2), Ok(1));
+            assert_eq!(balance(accounts.charlie), before[1] + 1_000_000);
+            assert_eq!(balance(accounts.django), before[2] + 1_000_000);
+            assert_eq!(contract.process_refunds(event_id, 2), Ok(0));
+            assert_eq!(balance(contract.env().account_id()), 0);
+            assert_eq!(contract.get_event_revenue(event_id), Some((0, 3_000_000)));
+
+            // Nothing is left for anyone to claim
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.claim_refund(event_id), Err(Error::NothingToRefund));
+        }
+
+        #[ink::test]
+        fn test_platform_fee() {
+            let mut contract = EventManager::new(native_currency());
+            assert_eq!(contract.get_platform_fee(), 0);
+
+            assert_eq!(contract.set_platform_fee(MAX_BPS + 1), Err(Error::InvalidFee));
+            assert_eq!(contract.set_platform_fee(250), Ok(()));
+            assert_eq!(contract.get_platform_fee(), 250);
+
+            // Nothing collected yet
+            assert_eq!(contract.withdraw_platform_fees(), Err(Error::NothingToWithdraw));
+
+            contract.platform_fees = 25_000;
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                25_000
+            ).unwrap();
+
+            // Only role holders may change the fee or withdraw
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_platform_fee(100), Err(Error::MissingRole));
+            assert_eq!(contract.withdraw_platform_fees(), Err(Error::MissingRole));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.withdraw_platform_fees(), Ok(25_000));
+            assert_eq!(contract.get_platform_fees(), 0);
+        }
+
+        #[ink::test]
+        fn test_roles() {
+            let mut contract = EventManager::new(native_currency());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // The deployer starts out with every role
+            for role in [DEFAULT_ADMIN, PAUSER, FEE_MANAGER].iter() {
+                assert!(contract.has_role(*role, accounts.alice));
+            }
+
+            assert_eq!(contract.grant_role(FEE_MANAGER, accounts.bob), Ok(()));
+            assert_eq!(
+                contract.grant_role(FEE_MANAGER, accounts.bob),
+                Err(Error::RoleAlreadyGranted)
+            );
+
+            // A fee manager can set the fee but cannot administer roles or the treasury
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_platform_fee(100), Ok(()));
+            assert_eq!(contract.grant_role(PAUSER, accounts.bob), Err(Error::MissingRole));
+            assert_eq!(
+                contract.set_ticket_nft_code_hash(Hash::from([0x1; 32])),
+                Err(Error::MissingRole)
+            );
+            assert_eq!(contract.renounce_role(FEE_MANAGER), Ok(()));
+            assert_eq!(contract.set_platform_fee(200), Err(Error::MissingRole));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.revoke_role(FEE_MANAGER, accounts.bob), Err(Error::MissingRole));
+            assert_eq!(contract.revoke_role(FEE_MANAGER, accounts.alice), Ok(()));
+            assert_eq!(contract.set_platform_fee(200), Err(Error::MissingRole));
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 4); // RoleGranted, PlatformFeeUpdated, 2x RoleRevoked
+        }
+
+        #[ink::test]
+        fn test_ownership_transfer() {
+            let mut contract = EventManager::new(native_currency());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(contract.get_owner(), Some(accounts.alice));
+
+            assert_eq!(contract.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(contract.get_pending_owner(), Some(accounts.bob));
+
+            // Nothing moves until the nominee accepts
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.accept_ownership(), Err(Error::NotPendingOwner));
+            assert_eq!(contract.transfer_ownership(accounts.charlie), Err(Error::NotOwner));
+            assert_eq!(contract.get_owner(), Some(accounts.alice));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.accept_ownership(), Ok(()));
+            assert_eq!(contract.get_owner(), Some(accounts.bob));
+            assert_eq!(contract.get_pending_owner(), None);
+            assert!(contract.has_role(DEFAULT_ADMIN, accounts.bob));
+            assert!(!contract.has_role(DEFAULT_ADMIN, accounts.alice));
+
+            assert_eq!(contract.renounce_ownership(), Ok(()));
+            assert_eq!(contract.get_owner(), None);
+            assert!(!contract.has_role(DEFAULT_ADMIN, accounts.bob));
+            assert_eq!(contract.transfer_ownership(accounts.bob), Err(Error::NotOwner));
+
+            // OwnershipTransferStarted, 2x OwnershipTransferred
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 3);
+        }
+
+        #[ink::test]
+        fn test_counters_do_not_wrap() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                tiers: vec![general_admission(Balance::MAX, 100)],
+                price_schedule: vec![PriceBreakpoint { until_sold: 10, multiplier_bps: 20_000 }],
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+
+            // Doubling the maximum price overflows instead of wrapping to a cheap ticket
+            assert_eq!(contract.current_price(event_id, 0), None);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::Overflow)
+            );
+
+            contract.next_event_id = u64::MAX;
+            assert_eq!(contract.register_event(details, ticket_nft_address), Err(Error::Overflow));
+        }
+
+        #[ink::test]
+        fn test_purchase_rejects_reentry() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            // As seen from a callee re-entering while a purchase is still in flight
+            contract.locked = true;
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::Reentrancy)
+            );
+            assert_eq!(contract.tickets_remaining(event_id), Some(100));
+        }
+
+        #[ink::test]
+        fn test_purchase_mints_on_collection() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let buyer = contract.env().caller();
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(contract.purchase_ticket(event_id, 0, "TicketURI".to_string()), Ok(()));
+
+            let purchase = contract.get_purchase(event_id, buyer).unwrap();
+            assert_eq!(purchase.paid, 1_000_000);
+            assert_eq!(purchase.token_ids, vec![1]);
+            assert!(contract.has_ticket(event_id, buyer));
+            assert_eq!(contract.tickets_remaining(event_id), Some(99));
+            let owner = mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(buyer));
+        }
+
+        #[ink::test]
+        fn test_rejected_mint_rolls_back_purchase() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+
+            // The collection refuses the second ticket, so the batch mints neither. On chain
+            // the `Err` also reverts the sale's bookkeeping, which off-chain tests don't.
+            mock_ticket_nft::with(ticket_nft_address, |nft| {
+                nft.fail_after(1, TicketNftError::Paused)
+            });
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000_000);
+            let uris = vec!["TicketURI".to_string(), "TicketURI".to_string()];
+            assert_eq!(
+                contract.purchase_tickets(event_id, 0, 2, uris),
+                Err(Error::MintFailed(TicketNftError::Paused))
+            );
+            assert_eq!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(1)), None);
+            assert!(!contract.locked);
+        }
+
+        #[ink::test]
+        fn test_purchase_with_token() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let manager = contract.env().account_id();
+            let token = AccountId::from([0x7; 32]);
+            mock_psp22::deploy(token);
+            mock_psp22::with(token, |psp22| psp22.mint(accounts.bob, 1_500_000));
+
+            // Only the host picks the asset, and the event then reports it as its currency
+            let usdc = CurrencyInfo { symbol: "USDC".to_string(), decimals: 6 };
+            let payment = Some((token, usdc.clone()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_payment_token(event_id, payment.clone()), Err(Error::NotHost));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_payment_token(event_id, payment), Ok(()));
+            let info = contract.get_event(event_id).unwrap();
+            assert_eq!((info.payment_token, info.currency), (Some(token), usdc));
+            assert_eq!(contract.get_currency(), native_currency());
+
+            // Native value is refused, and so is a purchase without an allowance
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::WrongPaymentAsset)
+            );
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(
+                contract.purchase_ticket_with_token(event_id, 0, "TicketURI".to_string()),
+                Err(Error::TokenTransferFailed)
+            );
+            assert_eq!(contract.tickets_remaining(event_id), Some(100));
+
+            // Exactly the price is pulled, even with a larger allowance
+            mock_psp22::with(token, |psp22| psp22.approve(accounts.bob, manager, 1_500_000));
+            assert_eq!(
+                contract.purchase_ticket_with_token(event_id, 0, "TicketURI".to_string()),
+                Ok(())
+            );
+            assert_eq!(mock_psp22::with(token, |psp22| psp22.balance_of(accounts.bob)), 500_000);
+            assert_eq!(mock_psp22::with(token, |psp22| psp22.balance_of(manager)), 1_000_000);
+
+            // The asset is frozen after the first sale
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_payment_token(event_id, None), Err(Error::SalesStarted));
+
+            // Refunds go back out in the same asset
+            assert_eq!(contract.cancel_event(event_id), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_refund(event_id), Ok(1_000_000));
+            assert_eq!(mock_psp22::with(token, |psp22| psp22.balance_of(accounts.bob)), 1_500_000);
+            assert_eq!(mock_psp22::with(token, |psp22| psp22.balance_of(manager)), 0);
+        }
+
+        #[ink::test]
+        fn test_usd_priced_purchase() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                tiers: vec![general_admission(2_000, 100)], // $20.00
+                usd_priced: true,
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            assert_eq!(contract.quote_price(event_id, 0), Err(Error::OracleNotSet));
+
+            // 1 DOT = $5.00000000, so a $20 ticket costs 4 DOT
+            let oracle = AccountId::from([0x9; 32]);
+            let config = OracleConfig {
+                oracle,
+                pair: "DOT/USD".to_string(),
+                decimals: 8,
+                max_age: 0,
+                slippage_bps: 100,
+            };
+            let too_loose = OracleConfig { slippage_bps: MAX_BPS + 1, ..config.clone() };
+            assert_eq!(contract.set_price_oracle(too_loose), Err(Error::InvalidSlippage));
+            assert_eq!(contract.set_price_oracle(config), Ok(()));
+            assert_eq!(contract.quote_price(event_id, 0), Err(Error::StalePrice));
+            mock_price_oracle::set_price(oracle, "DOT/USD", 500_000_000, 0);
+            let four_dot = 40_000_000_000;
+            assert_eq!(contract.quote_price(event_id, 0), Ok(four_dot));
+
+            // Up to 1% short is accepted, more is not
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(
+                four_dot - four_dot / 50
+            );
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::InsufficientPayment)
+            );
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(
+                four_dot - four_dot / 100
+            );
+            assert_eq!(contract.purchase_ticket(event_id, 0, "TicketURI".to_string()), Ok(()));
+            let buyer = contract.env().caller();
+            assert_eq!(contract.get_purchase(event_id, buyer).unwrap().paid, four_dot * 99 / 100);
+
+            // Quotes older than `max_age` are refused
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(four_dot);
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::StalePrice)
+            );
+        }
+
+        #[ink::test]
+        fn test_check_in() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Bob buys two tickets
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000_000);
+            let uris = vec!["TicketURI".to_string(), "TicketURI".to_string()];
+            assert_eq!(contract.purchase_tickets(event_id, 0, 2, uris), Ok(vec![1, 2]));
+
+            // Charlie isn't staff until the host makes them a verifier
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.check_in(event_id, accounts.bob), Err(Error::NotVerifier));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.add_event_verifier(event_id, accounts.charlie), Ok(()));
+            assert_eq!(contract.add_event_operator(event_id, accounts.eve), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(!contract.is_checked_in(event_id, accounts.bob));
+            assert_eq!(contract.check_in(event_id, accounts.bob), Ok(1));
+            assert!(contract.is_checked_in(event_id, accounts.bob));
+            assert!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.is_redeemed(1)));
+            assert_eq!(contract.get_check_in_time(event_id, 1), Some(0));
+            assert_eq!(contract.check_in(event_id, accounts.django), Err(Error::NoValidTicket));
+
+            // Checked-in attendees can claim one badge
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.claim_attendance_badge(event_id), Err(Error::NotCheckedIn));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_attendance_badge(event_id), Ok(3));
+            assert_eq!(contract.claim_attendance_badge(event_id), Err(Error::BadgeAlreadyClaimed));
+            assert_eq!(contract.get_attendance_badge(event_id, accounts.bob), Some(3));
+            assert_eq!(contract.get_attended_events(accounts.bob, 0, 10), vec![event_id]);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+
+            // Operators run the door too; each ticket gets in once
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.check_in(event_id, accounts.bob), Ok(2));
+            assert_eq!(contract.check_in(event_id, accounts.bob), Err(Error::AlreadyCheckedIn));
+            assert_eq!(contract.checked_in_count(event_id), 2);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.remove_event_verifier(event_id, accounts.charlie), Ok(()));
+            assert_eq!(
+                contract.remove_event_verifier(event_id, accounts.charlie),
+                Err(Error::NotVerifier)
+            );
+        }
+
+        #[ink::test]
+        fn test_check_in_with_signature() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Each challenge is specific to the attendee and moves on with the nonce
+            let challenge = contract.check_in_challenge(event_id, accounts.bob);
+            assert_ne!(challenge, contract.check_in_challenge(event_id, accounts.charlie));
+            contract.check_in_nonces.insert((event_id, accounts.bob), &1);
+            assert_ne!(challenge, contract.check_in_challenge(event_id, accounts.bob));
+
+            // Only door staff may submit, and garbage doesn't recover to the attendee
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.check_in_with_signature(event_id, accounts.bob, [0x1; 65]),
+                Err(Error::NotVerifier)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                contract.check_in_with_signature(event_id, accounts.bob, [0x1; 65]),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(contract.check_in_nonce(event_id, accounts.bob), 1);
+        }
+
+        #[ink::test]
+        fn test_request_refund() {
+            let mut contract = EventManager::new(native_currency());
+            let day: Timestamp = 24 * 60 * 60 * 1000;
+            let details = EventDetails {
+                refund_policy: vec![
+                    RefundTier { min_notice: 7 * day, refund_bps: 10_000 },
+                    RefundTier { min_notice: day, refund_bps: 5_000 },
+                ],
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                10_000_000
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(3_000_000);
+            let uris = vec!["TicketURI".to_string(); 3];
+            assert_eq!(contract.purchase_tickets(event_id, 0, 3, uris), Ok(vec![1, 2, 3]));
+
+            // A week out the whole price comes back and the slot goes back on sale
+            assert_eq!(contract.request_refund(event_id, 1), Ok(1_000_000));
+            assert_eq!(contract.request_refund(event_id, 1), Err(Error::NoValidTicket));
+            assert_eq!(contract.tickets_remaining(event_id), Some(98));
+            assert_eq!(contract.attendee_count(event_id), Some(2));
+            let registered = contract.get_registered_events(accounts.bob, 0, 10);
+            assert_eq!(registered, vec![event_id, event_id]);
+            assert!(contract.is_ticket_invalidated(event_id, 1));
+            assert_eq!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(1)), None);
+
+            // Two days out only half
+            edit_event(&mut contract, event_id, |e| e.details.date = 2 * day);
+            assert_eq!(contract.request_refund(event_id, 3), Ok(500_000));
+            assert_eq!(contract.get_purchase(event_id, accounts.bob).unwrap().paid, 1_000_000);
+            assert_eq!(contract.get_attendees(event_id, 0, 10), vec![accounts.bob]);
+
+            // Used tickets aren't refundable, nor is anything inside the last day
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.check_in(event_id, accounts.bob), Ok(2));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.request_refund(event_id, 2), Err(Error::AlreadyCheckedIn));
+            edit_event(&mut contract, event_id, |e| e.details.date = day / 2);
+            contract.check_ins.remove((event_id, 2));
+            assert_eq!(contract.request_refund(event_id, 2), Err(Error::RefundNotAvailable));
+            assert!(contract.audit().is_healthy());
+
+            // Refunded tickets still count as sold; the money is tracked separately
+            let stats = contract.platform_stats();
+            assert_eq!(stats, PlatformStats {
+                events_created: 1,
+                tickets_sold: 3,
+                native_volume: 3_000_000,
+                native_refunded: 1_500_000,
+            });
+        }
+
+        #[ink::test]
+        fn test_refund_requires_holder() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                refund_policy: vec![RefundTier { min_notice: 0, refund_bps: 10_000 }],
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                10_000_000
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000_000);
+            let uris = vec!["TicketURI".to_string(); 2];
+            assert_eq!(contract.purchase_tickets(event_id, 0, 2, uris), Ok(vec![1, 2]));
+
+            // Bob hands ticket 1 to charlie on a collection that never tells the manager
+            mock_ticket_nft::with(ticket_nft_address, |nft| {
+                nft.transfer(accounts.bob, accounts.charlie, 1)
+            }).unwrap();
+            assert_eq!(contract.request_refund(event_id, 1), Err(Error::NotTicketHolder));
+            assert_eq!(contract.request_refund(event_id, 2), Ok(1_000_000));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            contract.cancel_event(event_id).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.claim_refund(event_id), Err(Error::NotTicketHolder));
+            assert!(!contract.get_purchase(event_id, accounts.bob).unwrap().refunded);
+        }
+
+        #[ink::test]
+        fn test_sales_history() {
+            let mut contract = EventManager::new(native_currency());
+            let day: Timestamp = 24 * 60 * 60 * 1000;
+            let details = EventDetails {
+                tiers: vec![general_admission(1_000, 100)],
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            assert_eq!(contract.sales_history(event_id), vec![]);
+
+            // Sales within a day share its bucket; quiet days get none
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(day + 5);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000);
+            let uris = vec!["TicketURI".to_string(); 2];
+            assert!(contract.purchase_tickets(event_id, 0, 2, uris).is_ok());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2 * day - 1);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000);
+            assert_eq!(contract.purchase_ticket(event_id, 0, String::new()), Ok(()));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(4 * day);
+            assert_eq!(contract.purchase_ticket(event_id, 0, String::new()), Ok(()));
+            assert_eq!(contract.sales_history(event_id), vec![(day, 3), (4 * day, 1)]);
+        }
+
+        #[ink::test]
+        fn test_archive_event() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                tiers: vec![general_admission(1_000, 100)],
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000);
+            let uris = vec!["TicketURI".to_string(); 2];
+            assert!(contract.purchase_tickets(event_id, 0, 2, uris).is_ok());
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                2_000
+            );
+            // Charlie passes on the ticket bob gave them, leaving no record of their own
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(contract.on_ticket_transfer(accounts.bob, accounts.charlie, 1), Ok(()));
+            assert_eq!(contract.on_ticket_transfer(accounts.charlie, accounts.django, 1), Ok(()));
+            assert_eq!(contract.get_purchase(event_id, accounts.charlie), None);
+
+            // Not before the window closes, not by anyone else, and not with funds held
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(EVENT_DATE + 1);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.archive_event(event_id), Err(Error::ArchiveTooEarly));
+            let later = EVENT_DATE + ARCHIVE_DELAY;
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(later);
+            assert_eq!(contract.archive_event(event_id), Err(Error::FundsOutstanding));
+            assert!(contract.claim_revenue(event_id).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.archive_event(event_id), Err(Error::NotHost));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.archive_event(event_id), Ok(0));
+            assert_eq!(contract.archive_event(event_id), Err(Error::AlreadyArchived));
+            assert!(contract.get_event(event_id).is_none());
+            assert_eq!(contract.get_purchase(event_id, accounts.bob), None);
+            assert_eq!(contract.get_purchase(event_id, accounts.django), None);
+            assert_eq!(contract.get_attendees(event_id, 0, 10), vec![]);
+            let archived = contract.get_archived_event(event_id).unwrap();
+            assert_eq!((archived.tickets_sold, archived.attendee_count), (2, 2));
+            assert_eq!(archived.total_revenue, 2_000);
+
+            // Holders keep the event in their history, and the books still balance
+            assert_eq!(contract.get_registered_events(accounts.bob, 0, 10), vec![1]);
+            assert_eq!(contract.get_registered_events(accounts.django, 0, 10), vec![1]);
+            assert!(contract.audit().is_healthy());
+
+            // The collection can still report its tickets moving and burning
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(contract.on_ticket_transfer(accounts.django, accounts.eve, 1), Ok(()));
+            assert_eq!(contract.on_ticket_burn(accounts.bob, 2), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_crowdfunded_event() {
+            let mut contract = EventManager::new(native_currency());
+            let deadline = EVENT_DATE / 2;
+            let details = EventDetails {
+                funding_goal: Some(FundingGoal { min_tickets: 3, deadline }),
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let unreachable = FundingGoal { min_tickets: 101, deadline };
+            let invalid = EventDetails { funding_goal: Some(unreachable), ..details.clone() };
+            assert_eq!(
+                contract.register_event(invalid, ticket_nft_address),
+                Err(Error::InvalidFundingGoal)
+            );
+            let failing = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            let funded = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                10_000_000
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000_000);
+            let uris = vec!["TicketURI".to_string(); 2];
+            assert_eq!(contract.purchase_tickets(failing, 0, 2, uris.clone()), Ok(vec![1, 2]));
+            assert_eq!(contract.settle_funding(failing), Err(Error::FundingPending));
+
+            // The third ticket confirms the second event straight away
+            assert_eq!(contract.purchase_tickets(funded, 0, 2, uris.clone()), Ok(vec![3, 4]));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(funded, 0, "TicketURI".to_string()).unwrap();
+            assert_eq!(contract.is_funding_confirmed(funded), Some(true));
+            assert_eq!(contract.settle_funding(funded), Err(Error::FundingAlreadyConfirmed));
+
+            // Past the deadline the short event can no longer sell and gets cancelled
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(deadline + 1);
+            assert_eq!(
+                contract.purchase_ticket(failing, 0, "TicketURI".to_string()),
+                Err(Error::FundingDeadlinePassed)
+            );
+            assert_eq!(contract.settle_funding(failing), Ok(false));
+            let status = contract.get_event(failing).map(|e| e.status);
+            assert_eq!(status, Some(EventStatus::Cancelled));
+            assert_eq!(contract.claim_refund(failing), Ok(2_000_000));
+
+            // The confirmed event keeps selling and pays the host once it is over
+            contract.purchase_ticket(funded, 0, "TicketURI".to_string()).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(EVENT_DATE + 1);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(contract.claim_revenue(funded).is_ok());
+            assert!(contract.audit().is_healthy());
+        }
+
+        #[ink::test]
+        fn test_revenue_split() {
+            let mut contract = EventManager::new(native_currency());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let payees = vec![
+                Payee { account: accounts.bob, share_bps: 5_000 },
+                Payee { account: accounts.charlie, share_bps: 3_333 },
+                Payee { account: accounts.django, share_bps: 1_667 },
+            ];
+            let details = EventDetails {
+                date: contract.env().block_timestamp() + 1,
+                tiers: vec![general_admission(1_000_001, 100)],
+                payees: payees.clone(),
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let mut short = payees.clone();
+            short[2].share_bps = 1_666;
+            let invalid = EventDetails { payees: short, ..details.clone() };
+            assert_eq!(
+                contract.register_event(invalid, ticket_nft_address),
+                Err(Error::InvalidPayees)
+            );
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                10_000_000
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_001);
+            contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+
+            let balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .unwrap_or(0)
+            };
+            let before: Vec<Balance> = payees.iter().map(|p| balance(p.account)).collect();
+            let (revenue, _) = contract.get_event_revenue(event_id).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.claim_revenue(event_id), Ok(revenue));
+
+            // Shares round down and the last payee picks up the dust
+            let half = revenue * 5_000 / 10_000;
+            let third = revenue * 3_333 / 10_000;
+            let received: Vec<Balance> = payees
+                .iter()
+                .zip(before)
+                .map(|(p, before)| balance(p.account) - before)
+                .collect();
+            assert_eq!(received, vec![half, third, revenue - half - third]);
+            assert_eq!(contract.get_event_revenue(event_id).map(|(r, _)| r), Some(0));
+        }
+
+        #[ink::test]
+        fn test_referral_commission() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                date: contract.env().block_timestamp() + 1,
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                10_000_000
+            );
+            let too_high = contract.set_referral_commission(event_id, 10_001);
+            assert_eq!(too_high, Err(Error::InvalidCommission));
+            assert_eq!(contract.set_referral_commission(event_id, 1_000), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.register_referral_code("BOB10".to_string()), Ok(()));
+            let code_hash = EventManager::hash(b"BOB10");
+            assert_eq!(contract.get_referrer(code_hash), Some(accounts.bob));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                contract.register_referral_code("BOB10".to_string()),
+                Err(Error::ReferralCodeTaken)
+            );
+
+            // Nobody earns on their own purchases or through unknown codes
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            let uri = || "TicketURI".to_string();
+            assert_eq!(
+                contract.purchase_ticket_with_referral(event_id, 0, uri(), "NOPE".to_string()),
+                Err(Error::InvalidReferralCode)
+            );
+            for _ in 0..2 {
+                let code = "BOB10".to_string();
+                let result = contract.purchase_ticket_with_referral(event_id, 0, uri(), code);
+                assert_eq!(result, Ok(()));
+            }
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.purchase_ticket_with_referral(event_id, 0, uri(), "BOB10".to_string()),
+                Err(Error::SelfReferral)
+            );
+            assert_eq!(contract.get_referral_count(event_id, accounts.bob), 2);
+            assert_eq!(contract.get_referral_earnings(event_id, accounts.bob), 200_000);
+            assert_eq!(contract.get_referral_stats(event_id), Some((2, 200_000)));
+            assert_eq!(contract.withdraw_referral_earnings(event_id), Err(Error::EventNotEnded));
+
+            // Once it's over the host's revenue excludes the commission owed
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.claim_revenue(event_id), Ok(1_800_000));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_referral_earnings(event_id), Ok(200_000));
+            let again = contract.withdraw_referral_earnings(event_id);
+            assert_eq!(again, Err(Error::NothingToWithdraw));
+            assert_eq!(contract.get_referral_stats(event_id), Some((2, 0)));
+            assert!(contract.audit().is_healthy());
+        }
+
+        #[ink::test]
+        fn test_purchase_ticket_for() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                max_tickets_per_account: 1,
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            let uri = || "TicketURI".to_string();
+            assert_eq!(contract.purchase_ticket_for(event_id, 0, uri(), accounts.charlie), Ok(()));
+            let owner = mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.charlie));
+            assert_eq!(contract.get_attendees(event_id, 0, 10), vec![accounts.charlie]);
+            assert_eq!(contract.get_registered_events(accounts.charlie, 0, 10), vec![event_id]);
+            assert!(contract.has_ticket(event_id, accounts.charlie));
+            assert!(!contract.has_ticket(event_id, accounts.bob));
+
+            // The limit is the recipient's, so the buyer can still get their own
+            assert_eq!(
+                contract.purchase_ticket_for(event_id, 0, uri(), accounts.charlie),
+                Err(Error::PurchaseLimitExceeded)
+            );
+            assert_eq!(contract.purchase_ticket(event_id, 0, uri()), Ok(()));
+            assert_eq!(contract.attendee_count(event_id), Some(2));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.check_in(event_id, accounts.charlie), Ok(1));
+        }
 
-    #[ink(storage)]
-    pub struct EventManager {
-        owner: AccountId,
-        next_event_id: u64,
-        events: HashMap<u64, Event>,
-        user_registered_events: HashMap<AccountId, StorageVec<u64>>,
-    }
+        #[ink::test]
+        fn test_issue_comp_tickets() {
+            let mut contract = EventManager::new(native_currency());
+            let details = EventDetails {
+                tiers: vec![general_admission(1_000_000, 1)],
+                refund_policy: vec![RefundTier { min_notice: 0, refund_bps: 10_000 }],
+                ..concert()
+            };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let guests = vec![accounts.charlie, accounts.django];
 
-    #[derive(scale::Encode, scale::Decode, Clone)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub struct EventDetails {
-        title: String,
-        date: String,
-        location: String,
-        ticket_price: u128,
-        max_tickets: u64,
-    }
+            assert_eq!(
+                contract.issue_comp_tickets(event_id, guests.clone()),
+                Err(Error::CompAllocationExceeded)
+            );
+            assert_eq!(contract.set_comp_allocation(event_id, 2), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.issue_comp_tickets(event_id, guests.clone()), Err(Error::NotHost));
 
-    #[derive(scale::Encode, scale::Decode, Clone)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub struct Event {
-        event_id: u64,
-        details: EventDetails,
-        ticket_nft_address: AccountId,
-        attendees: StorageVec<AccountId>,
-        tickets_sold: u64,
-        active: bool,
-        host: AccountId,
-    }
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.issue_comp_tickets(event_id, guests.clone()), Ok(vec![1, 2]));
+            assert_eq!(contract.get_comp_allocation(event_id), Some((2, 2)));
+            let shrunk = contract.set_comp_allocation(event_id, 1);
+            assert_eq!(shrunk, Err(Error::CompAllocationExceeded));
 
-    impl EventManager {
-        #[ink(constructor)]
-        pub fn new() -> Self {
-            Self {
-                owner: Self::env().caller(),
-                next_event_id: 1,
-                events: HashMap::new(),
-                user_registered_events: HashMap::new(),
-            }
+            // Comps leave the paid capacity, sales and revenue alone
+            assert_eq!(contract.tickets_remaining(event_id), Some(1));
+            assert_eq!(contract.get_event_revenue(event_id), Some((0, 0)));
+            assert_eq!(contract.attendee_count(event_id), Some(2));
+            assert_eq!(contract.get_attendees(event_id, 0, 10), guests);
+            let owner = mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(2));
+            assert_eq!(owner, Some(accounts.django));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.request_refund(event_id, 1), Err(Error::RefundNotAvailable));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.check_in(event_id, accounts.charlie), Ok(1));
+
+            // A block of comps for one guest mints as a batch
+            assert_eq!(contract.set_comp_allocation(event_id, 4), Ok(()));
+            let block = vec![accounts.eve, accounts.eve];
+            assert_eq!(contract.issue_comp_tickets(event_id, block), Ok(vec![3, 4]));
+            let purchase = contract.get_purchase(event_id, accounts.eve).unwrap();
+            assert_eq!(purchase.token_ids, vec![3, 4]);
+            let report = contract.audit();
+            assert!(report.is_healthy());
+            assert_eq!(report.total_comp_tickets, 4);
         }
 
-        #[ink(message)]
-        pub fn create_event(
-            &mut self,
-            details: EventDetails,
-            ticket_nft_address: AccountId
-        ) -> u64 {
-            let event_id = self.next_event_id;
-            self.next_event_id += 1;
+        #[ink::test]
+        fn test_invite_only_event() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(contract.set_invite_only(event_id, true), Ok(()));
+            assert_eq!(contract.is_invite_only(event_id), Some(true));
 
-            let event = Event {
-                event_id,
-                details,
-                ticket_nft_address,
-                attendees: StorageVec::new(),
-                tickets_sold: 0,
-                active: true,
-                host: self.env().caller(),
-            };
+            // Vouchers are specific to the invitee and expiry
+            let payload = contract.invite_payload(event_id, accounts.bob, EVENT_DATE);
+            assert_ne!(payload, contract.invite_payload(event_id, accounts.charlie, EVENT_DATE));
+            assert_ne!(payload, contract.invite_payload(event_id, accounts.bob, EVENT_DATE - 1));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            let uri = || "TicketURI".to_string();
+            assert_eq!(contract.purchase_ticket(event_id, 0, uri()), Err(Error::InviteRequired));
+            assert_eq!(
+                contract.purchase_ticket_with_invite(event_id, 0, uri(), EVENT_DATE, [0x1; 65]),
+                Err(Error::InvalidSignature)
+            );
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            let expired = contract.env().block_timestamp() - 1;
+            assert_eq!(
+                contract.purchase_ticket_with_invite(event_id, 0, uri(), expired, [0x1; 65]),
+                Err(Error::InviteExpired)
+            );
+            contract.used_invites.insert(payload, &());
+            assert!(contract.is_invite_used(payload));
+            assert_eq!(
+                contract.purchase_ticket_with_invite(event_id, 0, uri(), EVENT_DATE, [0x1; 65]),
+                Err(Error::InviteAlreadyUsed)
+            );
 
-            self.events.insert(event_id, event);
-            event_id
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_invite_only(event_id, false), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_invite_only(event_id, true), Err(Error::NotHost));
         }
 
-        #[ink(message, payable)]
-        pub fn purchase_ticket(&mut self, event_id: u64, token_uri: String) -> bool {
-            let event = match self.events.get_mut(&event_id) {
-                Some(e) => e,
-                None => {
-                    return false;
-                }
+        #[ink::test]
+        fn test_redeem_voucher() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let voucher = TicketVoucher {
+                event_id,
+                tier: 0,
+                price: 500_000,
+                nonce: 1,
+                expiry: EVENT_DATE,
             };
 
-            let caller = self.env().caller();
-            let payment = self.env().transferred_balance();
+            // Every field is covered by the signature
+            let payload = contract.voucher_payload(voucher.clone());
+            let cheaper = TicketVoucher { price: 1, ..voucher.clone() };
+            assert_ne!(payload, contract.voucher_payload(cheaper));
+            let next = TicketVoucher { nonce: 2, ..voucher.clone() };
+            assert_ne!(payload, contract.voucher_payload(next));
 
-            if
-                event.active &&
-                event.tickets_sold < event.details.max_tickets &&
-                payment >= event.details.ticket_price
-            {
-                let mut nft_contract: TicketNFT = FromAccountId::from_account_id(
-                    event.ticket_nft_address
-                );
-                let token_id = nft_contract.mint_ticket(caller, token_uri);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(500_000);
+            let uri = || "TicketURI".to_string();
+            assert_eq!(
+                contract.redeem_voucher(voucher.clone(), uri(), [0x1; 65]),
+                Err(Error::InvalidSignature)
+            );
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            let expiry = contract.env().block_timestamp() - 1;
+            let expired = TicketVoucher { expiry, ..voucher.clone() };
+            let result = contract.redeem_voucher(expired, uri(), [0x1; 65]);
+            assert_eq!(result, Err(Error::VoucherExpired));
+            contract.used_vouchers.insert(payload, &());
+            assert!(contract.is_voucher_used(payload));
+            assert_eq!(
+                contract.redeem_voucher(voucher.clone(), uri(), [0x1; 65]),
+                Err(Error::VoucherAlreadyUsed)
+            );
+            let unknown = TicketVoucher { event_id: 99, ..voucher };
+            let result = contract.redeem_voucher(unknown, uri(), [0x1; 65]);
+            assert_eq!(result, Err(Error::EventNotFound));
+        }
 
-                if minted_ticket_id == 0 {
-                    return false;
-                }
+        #[ink::test]
+        fn test_ticket_transfer_moves_records() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
 
-                event.attendees.push(caller);
-                event.tickets_sold += 1;
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
 
-                let user_events = self.user_registered_events
-                    .entry(caller)
-                    .or_insert(StorageVec::new());
-                user_events.push(event_id);
+            // Only a known collection reports transfers
+            assert_eq!(
+                contract.on_ticket_transfer(accounts.bob, accounts.charlie, 1),
+                Err(TicketNftError::Unauthorized)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(contract.on_ticket_transfer(accounts.bob, accounts.charlie, 1), Ok(()));
 
-                true
-            } else {
-                false
-            }
+            assert_eq!(contract.get_attendees(event_id, 0, 10), vec![accounts.charlie]);
+            assert!(contract.has_ticket(event_id, accounts.charlie));
+            assert!(!contract.has_ticket(event_id, accounts.bob));
+            assert_eq!(contract.get_registered_events(accounts.charlie, 0, 10), vec![event_id]);
+            assert!(contract.get_registered_events(accounts.bob, 0, 10).is_empty());
+            assert_eq!(contract.get_purchase(event_id, accounts.charlie).unwrap().paid, 1_000_000);
+            assert_eq!(contract.get_purchase(event_id, accounts.bob), None);
+            assert_eq!(contract.get_user_tickets(accounts.charlie, event_id), vec![1]);
+            assert!(contract.get_user_tickets(accounts.bob, event_id).is_empty());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.check_in(event_id, accounts.bob), Err(Error::NoValidTicket));
+            assert_eq!(contract.check_in(event_id, accounts.charlie), Ok(1));
+            assert!(contract.audit().is_healthy());
         }
 
-        #[ink(message)]
-        pub fn deactivate_event(&mut self, event_id: u64) -> bool {
-            let event = match self.events.get_mut(&event_id) {
-                Some(e) => e,
-                None => {
-                    return false;
-                }
-            };
+        #[ink::test]
+        fn test_gift_ticket() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
 
-            if event.host == self.env().caller() {
-                event.active = false;
-                true
-            } else {
-                false
-            }
+            let memo = "Happy Birthday".to_string();
+            let result = contract.gift_ticket(event_id, 1, accounts.charlie, "x".repeat(129));
+            assert_eq!(result, Err(Error::MemoTooLong));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            let result = contract.gift_ticket(event_id, 1, accounts.eve, memo.clone());
+            assert_eq!(result, Err(Error::NotTicketHolder));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.gift_ticket(event_id, 1, accounts.charlie, memo.clone()), Ok(()));
+            let owner = mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.charlie));
+            assert_eq!(contract.get_user_tickets(accounts.charlie, event_id), vec![1]);
+            assert!(contract.get_user_tickets(accounts.bob, event_id).is_empty());
+            assert_eq!(contract.get_registered_events(accounts.charlie, 0, 10), vec![event_id]);
+
+            let emitted = ink::env::test::recorded_events().last().unwrap();
+            let gifted = <TicketGifted as ink::scale::Decode>::decode(&mut &emitted.data[..]);
+            let gifted = gifted.unwrap();
+            assert_eq!(gifted.token_id, 1);
+            assert_eq!((gifted.from, gifted.to), (accounts.bob, accounts.charlie));
+            assert_eq!(gifted.memo, memo);
+            assert!(contract.audit().is_healthy());
         }
 
-        #[ink(message)]
-        pub fn get_event_details(&self, event_id: u64) -> Option<EventDetails> {
-            self.events.get(&event_id).map(|e| e.details.clone())
+        #[ink::test]
+        fn test_banned_accounts() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
+            assert_eq!(contract.ban_account(accounts.charlie), Err(Error::MissingRole));
+            assert_eq!(contract.ban_from_event(event_id, accounts.charlie), Err(Error::NotHost));
+
+            // The host's ban stops sales and trades for their event only
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.ban_from_event(event_id, accounts.charlie), Ok(()));
+            let result = contract.ban_from_event(event_id, accounts.charlie);
+            assert_eq!(result, Err(Error::AlreadyBanned));
+            assert!(contract.is_banned(event_id, accounts.charlie));
+            assert!(!contract.is_banned(event_id + 1, accounts.charlie));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let result = contract.purchase_ticket(event_id, 0, String::new());
+            assert_eq!(result, Err(Error::Banned));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(
+                contract.on_ticket_transfer(accounts.bob, accounts.charlie, 1),
+                Err(TicketNftError::Banned)
+            );
+
+            // A platform ban reaches every event, and tickets the account already holds
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unban_from_event(event_id, accounts.charlie), Ok(()));
+            let result = contract.unban_from_event(event_id, accounts.charlie);
+            assert_eq!(result, Err(Error::NotBanned));
+            assert_eq!(contract.ban_account(accounts.bob), Ok(()));
+            assert!(contract.is_banned(event_id + 1, accounts.bob));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(
+                contract.on_ticket_transfer(accounts.bob, accounts.charlie, 1),
+                Err(TicketNftError::Banned)
+            );
+            // Marketplaces can ask through the collection before taking a bid
+            assert!(contract.is_banned_holder(1, accounts.bob));
+            assert!(!contract.is_banned_holder(1, accounts.charlie));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unban_account(accounts.bob), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(contract.on_ticket_transfer(accounts.bob, accounts.charlie, 1), Ok(()));
         }
 
-        #[ink(message)]
-        pub fn get_event_attendees(&self, event_id: u64) -> Option<StorageVec<AccountId>> {
-            self.events.get(&event_id).map(|e| e.attendees.clone())
+        #[ink::test]
+        fn test_recover_ticket() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
+            assert_eq!(
+                contract.recover_ticket(event_id, 1, accounts.charlie),
+                Err(Error::NotHost)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.recover_ticket(event_id, 1, accounts.charlie), Ok(2));
+            assert!(contract.get_user_tickets(accounts.bob, event_id).is_empty());
+            assert_eq!(contract.get_user_tickets(accounts.charlie, event_id), vec![2]);
+            assert!(!contract.has_ticket(event_id, accounts.bob));
+            assert!(contract.has_ticket(event_id, accounts.charlie));
+            assert_eq!(contract.get_registered_events(accounts.charlie, 0, 10), vec![event_id]);
+            assert_eq!(
+                contract.recover_ticket(event_id, 1, accounts.charlie),
+                Err(Error::NoValidTicket)
+            );
+
+            assert_eq!(contract.check_in(event_id, accounts.charlie), Ok(2));
+            assert_eq!(
+                contract.recover_ticket(event_id, 2, accounts.django),
+                Err(Error::RecoveryFailed(TicketNftError::AlreadyRedeemed))
+            );
         }
 
-        #[ink(message)]
-        pub fn get_ticket_nft_address(&self, event_id: u64) -> Option<AccountId> {
-            self.events.get(&event_id).map(|e| e.ticket_nft_address)
+        #[ink::test]
+        fn test_holder_burn_voids_ticket() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
+            assert_eq!(
+                contract.on_ticket_burn(accounts.bob, 1),
+                Err(TicketNftError::Unauthorized)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ticket_nft_address);
+            assert_eq!(contract.on_ticket_burn(accounts.bob, 1), Ok(()));
+            assert!(contract.is_ticket_invalidated(event_id, 1));
+            assert!(!contract.has_ticket(event_id, accounts.bob));
+            assert!(contract.get_registered_events(accounts.bob, 0, 10).is_empty());
+            assert!(contract.get_user_tickets(accounts.bob, event_id).is_empty());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.check_in(event_id, accounts.bob), Err(Error::NoValidTicket));
         }
 
-        #[ink(message)]
-        pub fn get_registered_events(&self, user: AccountId) -> Option<StorageVec<u64>> {
-            self.user_registered_events.get(&user).cloned()
+        #[ink::test]
+        fn test_set_resale_cap() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_resale_cap(event_id, Some(11_000)), Err(Error::NotHost));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_resale_cap(event_id, Some(11_000)), Ok(()));
+            assert_eq!(contract.get_resale_cap(event_id), Some(11_000));
+            let cap = mock_ticket_nft::with(ticket_nft_address, |nft| nft.resale_cap());
+            assert_eq!(cap, Some((11_000, vec![1_000_000])));
+
+            // Repricing the tiers updates the face values the collection caps against
+            let tiers = vec![general_admission(2_000_000, 100)];
+            let repriced = EventDetails { tiers, ..details };
+            assert_eq!(contract.update_event_details(event_id, repriced), Ok(()));
+            let cap = mock_ticket_nft::with(ticket_nft_address, |nft| nft.resale_cap());
+            assert_eq!(cap, Some((11_000, vec![2_000_000])));
+
+            assert_eq!(contract.set_resale_cap(event_id, None), Ok(()));
+            assert_eq!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.resale_cap()), None);
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use ink_env::AccountId;
-        use ink_storage::collections::Vec as StorageVec;
-        use ink_lang as ink;
+        #[ink::test]
+        fn test_set_royalty() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(contract.set_royalty(event_id, 10_001), Err(Error::InvalidRoyalty));
+            assert_eq!(contract.set_royalty(event_id, 500), Ok(()));
+            assert_eq!(contract.get_royalty(event_id), Some(500));
+            let royalty = mock_ticket_nft::with(ticket_nft_address, |nft| nft.royalty());
+            assert_eq!(royalty, Some((accounts.alice, 500)));
+
+            // A new host takes over the royalty
+            assert_eq!(contract.propose_host_transfer(event_id, accounts.bob), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.accept_host_transfer(event_id), Ok(()));
+            let royalty = mock_ticket_nft::with(ticket_nft_address, |nft| nft.royalty());
+            assert_eq!(royalty, Some((accounts.bob, 500)));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_royalty(event_id, 0), Err(Error::NotHost));
+        }
 
         #[ink::test]
-        fn test_create_event() {
-            let mut contract = EventManager::new();
-            let details = EventDetails {
-                title: "Concert".to_string(),
-                date: "2024-12-01".to_string(),
-                location: "Stadium".to_string(),
-                ticket_price: 1_000_000,
-                max_tickets: 100,
+        fn test_ticket_metadata() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, "TicketURI".to_string()).unwrap();
+
+            let seat = |contract: &mut EventManager, token_id| {
+                contract.set_ticket_attribute(event_id, token_id, b"seat".to_vec(), b"A12".to_vec())
             };
+            assert_eq!(seat(&mut contract, 2), Err(Error::NoValidTicket));
+            assert_eq!(seat(&mut contract, 1), Ok(()));
+            let stored = mock_ticket_nft::with(ticket_nft_address, |nft| nft.attribute(1, b"seat"));
+            assert_eq!(stored, Some(b"A12".to_vec()));
 
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(seat(&mut contract, 1), Err(Error::NotHost));
+            assert_eq!(contract.freeze_ticket_metadata(event_id), Err(Error::NotHost));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.freeze_ticket_metadata(event_id), Ok(()));
+            assert_eq!(
+                seat(&mut contract, 1),
+                Err(Error::CollectionUpdateFailed(TicketNftError::MetadataFrozen))
+            );
+        }
+
+        #[ink::test]
+        fn test_soulbound_tickets() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
             let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
-            let event_id = contract.create_event(details, ticket_nft_address);
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(contract.is_transferable(event_id), Some(true));
 
-            let event_details = contract.get_event_details(event_id);
-            assert!(event_details.is_some());
-            let event_details = event_details.unwrap();
-            assert_eq!(event_details.title, "Concert");
-            assert_eq!(event_details.date, "2024-12-01");
-            assert_eq!(event_details.location, "Stadium");
-            assert_eq!(event_details.ticket_price, 1_000_000);
-            assert_eq!(event_details.max_tickets, 100);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_transferable(event_id, false), Err(Error::NotHost));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_transferable(event_id, false), Ok(()));
+            assert_eq!(contract.is_transferable(event_id), Some(false));
+            assert!(!mock_ticket_nft::with(ticket_nft_address, |nft| nft.is_transferable()));
         }
 
         #[ink::test]
-        fn test_purchase_ticket() {
-            let mut contract = EventManager::new();
-            let details = EventDetails {
-                title: "Concert".to_string(),
-                date: "2024-12-01".to_string(),
-                location: "Stadium".to_string(),
-                ticket_price: 1_000_000,
-                max_tickets: 100,
-            };
+        fn test_set_transfer_lock() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_transfer_lock(event_id, Some(86_400_000)), Err(Error::NotHost));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_transfer_lock(event_id, Some(86_400_000)), Ok(()));
+            assert_eq!(contract.get_transfer_lock(event_id), Some(86_400_000));
+            let lock = mock_ticket_nft::with(ticket_nft_address, |nft| nft.transfer_lock());
+            assert_eq!(lock, Some(86_400_000));
+        }
 
+        #[ink::test]
+        fn test_ticket_data() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
             let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
-            let event_id = contract.create_event(details, ticket_nft_address);
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, String::new()).unwrap();
 
-            // Attempting to purchase a ticket without sending any balance should fail
-            let result = contract.purchase_ticket(event_id, "TicketURI".to_string());
-            assert!(!result); // Should fail because no payment was made
+            // The collection knows which event the ticket is for without its URI
+            let data = mock_ticket_nft::with(ticket_nft_address, |nft| nft.ticket_data(1)).unwrap();
+            assert_eq!((data.event_id, data.tier, data.seat), (event_id, 0, None));
+            assert_eq!(data.expires_at, Some(EVENT_DATE + TICKET_EXPIRY_GRACE));
+            assert_eq!(contract.assign_seat(event_id, 1, Some(12)), Ok(()));
+            let data = mock_ticket_nft::with(ticket_nft_address, |nft| nft.ticket_data(1)).unwrap();
+            assert_eq!(data.seat, Some(12));
+
+            assert_eq!(contract.assign_seat(event_id, 2, Some(12)), Err(Error::NoValidTicket));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.assign_seat(event_id, 1, None), Err(Error::NotHost));
         }
 
         #[ink::test]
-        fn test_deactivate_event() {
-            let mut contract = EventManager::new();
+        fn test_burn_expired_tickets() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000_000);
+            let uris = vec![String::new(); 2];
+            contract.purchase_tickets(event_id, 0, 2, uris).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.check_in(event_id, accounts.bob), Ok(1));
+            let result = contract.burn_expired_tickets(event_id, vec![2]);
+            assert_eq!(result, Err(Error::TicketNotExpired));
+
+            // Past the grace period the unused ticket can neither get in nor stay around
+            let expired = EVENT_DATE + TICKET_EXPIRY_GRACE + 1;
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(expired);
+            let result = contract.check_in(event_id, accounts.bob);
+            assert_eq!(result, Err(Error::RedeemFailed(TicketNftError::Expired)));
+            let result = contract.burn_expired_tickets(event_id, vec![1]);
+            assert_eq!(result, Err(Error::AlreadyCheckedIn));
+            assert_eq!(contract.burn_expired_tickets(event_id, vec![2]), Ok(()));
+            assert!(contract.is_ticket_invalidated(event_id, 2));
+            assert_eq!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(2)), None);
+            assert_eq!(contract.get_purchase(event_id, accounts.bob).unwrap().token_ids, vec![1]);
+            let result = contract.burn_expired_tickets(event_id, vec![2]);
+            assert_eq!(result, Err(Error::NoValidTicket));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = contract.burn_expired_tickets(event_id, vec![1]);
+            assert_eq!(result, Err(Error::NotHost));
+        }
+
+        #[ink::test]
+        fn test_revoke_ticket() {
+            let mut contract = EventManager::new(native_currency());
             let details = EventDetails {
-                title: "Concert".to_string(),
-                date: "2024-12-01".to_string(),
-                location: "Stadium".to_string(),
-                ticket_price: 1_000_000,
-                max_tickets: 100,
+                tiers: vec![general_admission(1_000_000, 1)],
+                ..concert()
             };
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            contract.purchase_ticket(event_id, 0, String::new()).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.join_waitlist(event_id), Ok(()));
+            assert_eq!(contract.revoke_ticket(event_id, 1, 1), Err(Error::NotHost));
+
+            // The chargeback costs bob the ticket, and the seat goes to charlie
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.revoke_ticket(event_id, 1, 1), Ok(()));
+            assert!(contract.is_ticket_invalidated(event_id, 1));
+            assert_eq!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(1)), None);
+            let purchase = contract.get_purchase(event_id, accounts.bob).unwrap();
+            assert!(purchase.token_ids.is_empty());
+            assert_eq!(purchase.paid, 0);
+            assert_eq!(contract.get_held_slots(event_id, 0, 10)[0].account, accounts.charlie);
+            assert!(contract.audit().tickets_match_attendees);
+
+            assert_eq!(contract.revoke_ticket(event_id, 1, 1), Err(Error::NoValidTicket));
+        }
 
+        #[ink::test]
+        fn test_shared_collection() {
+            let mut contract = EventManager::new(native_currency());
+            let details = |title: &str| EventDetails {
+                title: title.to_string(),
+                refund_policy: vec![RefundTier { min_notice: 0, refund_bps: 10_000 }],
+                ..concert()
+            };
             let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
-            let event_id = contract.create_event(details, ticket_nft_address);
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                10_000_000
+            );
+            let result = contract.create_shared_event(details("Concert"), ticket_nft_address);
+            assert_eq!(result, Err(Error::NotSharedCollection));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = contract.add_shared_collection(ticket_nft_address);
+            assert_eq!(result, Err(Error::MissingRole));
 
-            // Ensure the event is active
-            let event = contract.get_event_details(event_id).unwrap();
-            assert!(event.active);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.add_shared_collection(ticket_nft_address), Ok(()));
+            assert!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.is_adopted()));
+            let concert = contract.create_shared_event(details("Concert"), ticket_nft_address);
+            let concert = concert.unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let festival = contract.create_shared_event(details("Festival"), ticket_nft_address);
+            let festival = festival.unwrap();
+            let collection = mock_ticket_nft::with(ticket_nft_address, |nft| {
+                nft.collection(festival)
+            });
+            assert_eq!(collection.unwrap().name, "Festival");
 
-            // Deactivate the event
-            let result = contract.deactivate_event(event_id);
-            assert!(result);
+            // Both events sell from the one collection, but neither host can bind it
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(contract.purchase_ticket(concert, 0, String::new()), Ok(()));
+            assert_eq!(contract.purchase_ticket(festival, 0, String::new()), Ok(()));
+            let data = mock_ticket_nft::with(ticket_nft_address, |nft| nft.ticket_data(2));
+            assert_eq!(data.unwrap().event_id, festival);
+            assert_eq!(contract.set_transferable(festival, false), Err(Error::SharedCollection));
+            assert_eq!(contract.set_royalty(festival, 500), Err(Error::SharedCollection));
 
-            // Verify the event is deactivated
-            let event = contract.get_event_details(event_id).unwrap();
-            assert!(!event.active);
+            // As the collection's owner the manager can burn the ticket it refunds
+            assert_eq!(contract.request_refund(festival, 2), Ok(1_000_000));
+            assert!(contract.is_ticket_invalidated(festival, 2));
+            assert_eq!(mock_ticket_nft::with(ticket_nft_address, |nft| nft.owner_of(2)), None);
+        }
+
+        #[ink::test]
+        fn test_emergency_withdraw() {
+            let mut contract = EventManager::new(native_currency());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(
+                contract.env().account_id(),
+                5_000
+            );
+            assert_eq!(contract.emergency_withdraw(), Err(Error::NoEmergencyWithdrawal));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = contract.announce_emergency_withdraw(None, accounts.bob, 5_000);
+            assert_eq!(result, Err(Error::NotOwner));
+
+            // Announced now, runnable a week later, and only by the owner
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.announce_emergency_withdraw(None, accounts.eve, 3_000), Ok(()));
+            assert_eq!(contract.cancel_emergency_withdraw(), Ok(()));
+            assert_eq!(contract.get_emergency_withdrawal(), None);
+            assert_eq!(contract.announce_emergency_withdraw(None, accounts.eve, 3_000), Ok(()));
+            assert_eq!(contract.emergency_withdraw(), Err(Error::TimelockActive));
+            let executable_at = contract.get_emergency_withdrawal().unwrap().executable_at;
+            assert_eq!(executable_at, contract.env().block_timestamp() + EMERGENCY_TIMELOCK);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(executable_at);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.emergency_withdraw(), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.emergency_withdraw(), Ok(3_000));
+            assert_eq!(contract.emergency_withdraw(), Err(Error::NoEmergencyWithdrawal));
+
+            // Announced, cancelled, announced, executed
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 4);
+        }
+
+        #[ink::test]
+        fn test_upgrade_timelock() {
+            let mut contract = EventManager::new(native_currency());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let code_hash = Hash::from([0x1; 32]);
+            assert_eq!(contract.upgrade(), Err(Error::NoPendingUpgrade));
+            assert_eq!(contract.cancel_upgrade(), Err(Error::NoPendingUpgrade));
+
+            let announced_at = contract.env().block_timestamp();
+            assert_eq!(contract.announce_upgrade(code_hash), Ok(()));
+            let executable_at = announced_at + UPGRADE_TIMELOCK;
+            assert_eq!(contract.get_pending_upgrade(), Some((code_hash, executable_at)));
+
+            // The new code can't be installed before the week is up
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                executable_at - 1
+            );
+            assert_eq!(contract.upgrade(), Err(Error::TimelockActive));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.cancel_upgrade(), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.cancel_upgrade(), Ok(()));
+            assert_eq!(contract.get_pending_upgrade(), None);
+            assert_eq!(contract.upgrade(), Err(Error::NoPendingUpgrade));
+
+            // Announcing again restarts the clock from now
+            assert_eq!(contract.announce_upgrade(code_hash), Ok(()));
+            assert_eq!(
+                contract.get_pending_upgrade(),
+                Some((code_hash, executable_at - 1 + UPGRADE_TIMELOCK))
+            );
+
+            // Announced, cancelled, announced
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 3);
+        }
+
+        #[ink::test]
+        fn test_migrate() {
+            let mut contract = EventManager::new(native_currency());
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.migrate(), Err(Error::AlreadyMigrated));
+
+            // Storage left behind by older code
+            contract.storage_version = 0;
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.migrate(), Err(Error::NotOwner));
+            assert_eq!(contract.announce_upgrade(Hash::from([0x1; 32])), Err(Error::NotOwner));
+            assert_eq!(contract.upgrade(), Err(Error::NotOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.migrate(), Ok(()));
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+        }
+
+        #[ink::test]
+        fn test_pause() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.pause(), Err(Error::MissingRole));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.grant_role(PAUSER, accounts.bob), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.pause(), Ok(()));
+            assert!(contract.is_paused());
+            assert_eq!(contract.pause(), Err(Error::Paused));
+
+            assert_eq!(
+                contract.register_event(details, ticket_nft_address),
+                Err(Error::Paused)
+            );
+            assert_eq!(
+                contract.purchase_ticket(event_id, 0, "TicketURI".to_string()),
+                Err(Error::Paused)
+            );
+
+            // Refunds stay available while paused
+            edit_event(&mut contract, event_id, |e| e.status = EventStatus::Cancelled);
+            assert_eq!(contract.claim_refund(event_id), Err(Error::NothingToRefund));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unpause(), Ok(()));
+            assert_eq!(contract.unpause(), Err(Error::NotPaused));
         }
 
         #[ink::test]
         fn test_get_event_attendees() {
-            let mut contract = EventManager::new();
-            let details = EventDetails {
-                title: "Concert".to_string(),
-                date: "2024-12-01".to_string(),
-                location: "Stadium".to_string(),
-                ticket_price: 1_000_000,
-                max_tickets: 100,
-            };
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
 
             let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
-            let event_id = contract.create_event(details, ticket_nft_address);
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
 
-            let result = contract.purchase_ticket(event_id, "TicketURI".to_string());
-            assert!(result);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            let result = contract.purchase_ticket(event_id, 0, "TicketURI".to_string());
+            assert!(result.is_ok());
 
             // Retrieve the attendees
-            let attendees = contract.get_event_attendees(event_id);
-            assert!(attendees.is_some());
-            let attendees = attendees.unwrap();
+            let attendees = contract.get_attendees(event_id, 0, 10);
             assert_eq!(attendees.len(), 1); // Expect one attendee (the contract caller)
+            assert_eq!(contract.attendee_count(event_id), Some(1));
+        }
+
+        #[ink::test]
+        fn test_get_attendees_paginates() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            for (index, account) in [accounts.bob, accounts.charlie, accounts.django]
+                .into_iter()
+                .enumerate()
+            {
+                contract.attendees.insert((event_id, index as u64), &account);
+            }
+            edit_event(&mut contract, event_id, |e| e.attendee_count = 3);
+
+            assert_eq!(contract.attendee_count(event_id), Some(3));
+            let first_page = contract.get_attendees(event_id, 0, 2);
+            assert_eq!(first_page, vec![accounts.bob, accounts.charlie]);
+            assert_eq!(contract.get_attendees(event_id, 2, 2), vec![accounts.django]);
+            assert!(contract.get_attendees(event_id + 1, 0, 2).is_empty());
+            assert_eq!(contract.attendee_count(event_id + 1), None);
         }
 
         #[ink::test]
         fn test_get_registered_events() {
-            let mut contract = EventManager::new();
-            let details = EventDetails {
-                title: "Concert".to_string(),
-                date: "2024-12-01".to_string(),
-                location: "Stadium".to_string(),
-                ticket_price: 1_000_000,
-                max_tickets: 100,
-            };
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
 
             let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
-            let event_id = contract.create_event(details, ticket_nft_address);
+            mock_ticket_nft::deploy(ticket_nft_address);
+            let event_id = contract.register_event(details, ticket_nft_address).unwrap();
 
-            let result = contract.purchase_ticket(event_id, "TicketURI".to_string());
-            assert!(result);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            let result = contract.purchase_ticket(event_id, 0, "TicketURI".to_string());
+            assert!(result.is_ok());
 
             // Retrieve the registered events for the caller
             let user = contract.env().caller();
-            let registered_events = contract.get_registered_events(user);
-            assert!(registered_events.is_some());
-            let registered_events = registered_events.unwrap();
+            let registered_events = contract.get_registered_events(user, 0, 10);
             assert_eq!(registered_events.len(), 1); // Expect one registered event
             assert_eq!(registered_events[0], event_id);
+
+            // Pages stop at the end and never exceed `MAX_PAGE_SIZE`
+            let result = contract.purchase_ticket(event_id, 0, "TicketURI".to_string());
+            assert!(result.is_ok());
+            assert_eq!(contract.registration_count(user), 2);
+            assert_eq!(contract.get_registered_events(user, 1, 10), vec![event_id]);
+            assert!(contract.get_registered_events(user, 2, 10).is_empty());
+            assert_eq!(contract.get_registered_events(user, 0, u32::MAX).len(), 2);
+        }
+
+        #[ink::test]
+        fn test_audit_reports_healthy_state() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let ticket_nft_address = AccountId::from([0x0; 32]); // Mock NFT address for testing
+            contract.register_event(details.clone(), ticket_nft_address).unwrap();
+            contract.register_event(details, ticket_nft_address).unwrap();
+
+            let report = contract.audit();
+            assert!(report.is_healthy());
+            assert_eq!(report.events_checked, 2);
+            assert_eq!(report.total_tickets_sold, 0);
+            assert_eq!(report.oversold_events, 0);
+        }
+
+        #[ink::test]
+        fn test_set_ticket_nft_code_hash() {
+            let mut contract = EventManager::new(native_currency());
+            assert_eq!(contract.get_ticket_nft_code_hash(), None);
+
+            let code_hash = Hash::from([0x1; 32]);
+            assert_eq!(contract.set_ticket_nft_code_hash(code_hash), Ok(()));
+            assert_eq!(contract.get_ticket_nft_code_hash(), Some(code_hash));
+        }
+
+        #[ink::test]
+        fn test_create_event_requires_code_hash() {
+            let mut contract = EventManager::new(native_currency());
+            let details = concert();
+
+            let result = contract.create_event(
+                details.clone(),
+                "BlockPassNFT".to_string(),
+                "BPNT".to_string()
+            );
+            assert_eq!(result, Err(Error::CodeHashNotSet));
+
+            // Details are validated before any collection gets instantiated
+            contract.set_ticket_nft_code_hash(Hash::from([0x1; 32])).unwrap();
+            let no_tiers = EventDetails { tiers: vec![], ..details };
+            let result = contract.create_event(
+                no_tiers,
+                "BlockPassNFT".to_string(),
+                "BPNT".to_string()
+            );
+            assert_eq!(result, Err(Error::NoTiers));
+        }
+
+        #[ink::test]
+        fn test_get_currency() {
+            let contract = EventManager::new(native_currency());
+            let currency = contract.get_currency();
+            assert_eq!(currency, native_currency());
+            assert_eq!(currency.symbol, "DOT");
+            assert_eq!(currency.decimals, 10);
+        }
+    }
+
+    /// Randomised sequences of create/purchase/cancel/refund calls, checking the bookkeeping
+    /// invariants after every step.
+    #[cfg(all(test, feature = "std"))]
+    mod invariant_tests {
+        use super::*;
+        use ink::env::test::DefaultAccounts;
+        use proptest::prelude::*;
+        use std::collections::{ BTreeMap, BTreeSet };
+
+        type Env = ink::env::DefaultEnvironment;
+
+        const EVENT_DATE: Timestamp = 1_733_011_200_000; // 2024-12-01
+        const PRICE: Balance = 1_000;
+
+        #[derive(Clone, Debug)]
+        enum Op {
+            Create { max: u64 },
+            Purchase { event: usize, buyer: usize, quantity: u32 },
+            Cancel { event: usize },
+            Refund { event: usize, buyer: usize },
+        }
+
+        fn op() -> impl Strategy<Value = Op> {
+            prop_oneof![
+                (1u64..5).prop_map(|max| Op::Create { max }),
+                (0usize..4, 0usize..3, 1u32..4).prop_map(|(event, buyer, quantity)| {
+                    Op::Purchase { event, buyer, quantity }
+                }),
+                (0usize..4).prop_map(|event| Op::Cancel { event }),
+                (0usize..4, 0usize..3).prop_map(|(event, buyer)| Op::Refund { event, buyer }),
+            ]
+        }
+
+        fn check(
+            ops: Vec<Op>,
+            accounts: DefaultAccounts<Env>
+        ) -> std::result::Result<(), TestCaseError> {
+            let mut contract = EventManager::new(CurrencyInfo {
+                symbol: "DOT".to_string(),
+                decimals: 10,
+            });
+            // Payments aren't credited off-chain, so fund refunds up front
+            ink::env::test::set_account_balance::<Env>(
+                ink::env::test::callee::<Env>(),
+                1_000_000_000
+            );
+            let buyers = [accounts.bob, accounts.charlie, accounts.django];
+            let mut event_ids = Vec::new();
+            let mut seen_ids = BTreeSet::new();
+            let mut paid: BTreeMap<u64, Balance> = BTreeMap::new();
+            let mut refunded: BTreeMap<u64, Balance> = BTreeMap::new();
+
+            for op in ops {
+                ink::env::test::set_caller::<Env>(accounts.alice);
+                match op {
+                    Op::Create { max } => {
+                        let ticket_nft_address = AccountId::from([event_ids.len() as u8 + 1; 32]);
+                        mock_ticket_nft::deploy(ticket_nft_address);
+                        let details = EventDetails {
+                            title: "Concert".to_string(),
+                            date: EVENT_DATE,
+                            location: "Stadium".to_string(),
+                            tiers: vec![TicketTier {
+                                name: "General Admission".to_string(),
+                                price: PRICE,
+                                max,
+                                sold: 0,
+                            }],
+                            max_tickets_per_account: 0,
+                            sales_start: 0,
+                            sales_end: EVENT_DATE,
+                            price_schedule: vec![],
+                            dutch_auction: None,
+                            category: 0,
+                            tags: vec![],
+                            usd_priced: false,
+                            refund_policy: vec![],
+                            funding_goal: None,
+                            payees: vec![],
+                        };
+                        let event_id = contract.register_event(details, ticket_nft_address);
+                        prop_assert!(event_id.is_ok());
+                        let event_id = event_id.unwrap();
+                        prop_assert!(seen_ids.insert(event_id), "event id {} reused", event_id);
+                        event_ids.push(event_id);
+                    }
+                    Op::Purchase { event, buyer, quantity } => {
+                        let Some(&event_id) = event_ids.get(event) else { continue };
+                        let cost = PRICE * quantity as Balance;
+                        ink::env::test::set_caller::<Env>(buyers[buyer]);
+                        ink::env::test::set_value_transferred::<Env>(cost);
+                        let uris = vec!["TicketURI".to_string(); quantity as usize];
+                        if contract.purchase_tickets(event_id, 0, quantity, uris).is_ok() {
+                            *paid.entry(event_id).or_default() += cost;
+                        }
+                    }
+                    Op::Cancel { event } => {
+                        if let Some(&event_id) = event_ids.get(event) {
+                            let _ = contract.cancel_event(event_id);
+                        }
+                    }
+                    Op::Refund { event, buyer } => {
+                        let Some(&event_id) = event_ids.get(event) else { continue };
+                        ink::env::test::set_caller::<Env>(buyers[buyer]);
+                        if let Ok(amount) = contract.claim_refund(event_id) {
+                            *refunded.entry(event_id).or_default() += amount;
+                        }
+                    }
+                }
+
+                for event_id in event_ids.iter() {
+                    let event = contract.events.get(*event_id).unwrap();
+                    prop_assert!(event.tickets_sold <= event.details.max_tickets());
+                    prop_assert_eq!(event.attendee_count, event.tickets_sold);
+                    let refunded = refunded.get(event_id).copied().unwrap_or(0);
+                    prop_assert!(refunded <= paid.get(event_id).copied().unwrap_or(0));
+                }
+                prop_assert!(contract.audit().is_healthy());
+            }
+            Ok(())
+        }
+
+        proptest! {
+            #[test]
+            fn invariants_hold_over_random_operations(ops in prop::collection::vec(op(), 1..40)) {
+                let mut outcome = Ok(());
+                ink::env::test::run_test::<Env, _>(|accounts| {
+                    outcome = check(ops, accounts);
+                    Ok(())
+                })
+                .unwrap();
+                outcome?;
+            }
+        }
+    }
+
+    /// Runs against a contracts node (`cargo test --features e2e-tests`), so unlike the unit
+    /// tests above these go through the real `TicketNFT` instantiation and mint calls.
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::{ ContractsBackend, E2EBackend };
+        use ticket_nft::{ TicketMinter, TicketNFT };
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        const EVENT_DATE: Timestamp = 4_102_444_800_000; // 2100-01-01
+        const PRICE: Balance = 1_000_000;
+
+        fn concert() -> EventDetails {
+            EventDetails {
+                title: "Concert".to_string(),
+                date: EVENT_DATE,
+                location: "Stadium".to_string(),
+                tiers: vec![TicketTier {
+                    name: "General Admission".to_string(),
+                    price: PRICE,
+                    max: 100,
+                    sold: 0,
+                }],
+                max_tickets_per_account: 0,
+                sales_start: 0,
+                sales_end: EVENT_DATE,
+                price_schedule: vec![],
+                dutch_auction: None,
+                category: 0,
+                tags: vec![],
+                usd_priced: false,
+                refund_policy: vec![],
+                funding_goal: None,
+                payees: vec![],
+            }
+        }
+
+        /// Deploys the manager, points it at the uploaded `TicketNFT` code and creates one
+        /// event, returning the manager's address with the event and collection ids.
+        async fn deploy_with_event<Client: E2EBackend>(
+            client: &mut Client
+        ) -> E2EResult<(AccountId, u64, AccountId)> {
+            let nft_code = client
+                .upload("ticket_nft", &ink_e2e::alice())
+                .submit().await
+                .expect("TicketNFT upload failed");
+            let mut constructor = EventManagerRef::new(CurrencyInfo {
+                symbol: "DOT".to_string(),
+                decimals: 10,
+            });
+            let manager = client
+                .instantiate("block_pass", &ink_e2e::alice(), &mut constructor)
+                .submit().await
+                .expect("EventManager instantiation failed");
+            let mut call_builder = manager.call_builder::<EventManager>();
+
+            let set_code_hash = call_builder.set_ticket_nft_code_hash(nft_code.code_hash);
+            client.call(&ink_e2e::alice(), &set_code_hash).submit().await?.return_value()?;
+
+            let create = call_builder.create_event(
+                concert(),
+                "BlockPassNFT".to_string(),
+                "BPNT".to_string()
+            );
+            let (event_id, nft_address) = client
+                .call(&ink_e2e::alice(), &create)
+                .submit().await?
+                .return_value()?;
+            Ok((manager.account_id, event_id, nft_address))
+        }
+
+        #[ink_e2e::test]
+        async fn e2e_purchase_mints_ticket_to_buyer<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            let (manager, event_id, nft_address) = deploy_with_event(&mut client).await?;
+            let mut call_builder = ink_e2e::create_call_builder::<EventManager>(manager);
+            let bob = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let purchase = call_builder.purchase_ticket(event_id, 0, "TicketURI".to_string());
+            client
+                .call(&ink_e2e::bob(), &purchase)
+                .value(PRICE)
+                .submit().await?
+                .return_value()?;
+
+            let purchase = client
+                .call(&ink_e2e::bob(), &call_builder.get_purchase(event_id, bob))
+                .dry_run().await?
+                .return_value()
+                .expect("purchase was not recorded");
+            assert_eq!(purchase.paid, PRICE);
+            assert_eq!(purchase.token_ids.len(), 1);
+
+            let attendees = client
+                .call(&ink_e2e::bob(), &call_builder.get_attendees(event_id, 0, 10))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(attendees, vec![bob]);
+            let registered = client
+                .call(&ink_e2e::bob(), &call_builder.get_registered_events(bob, 0, 10))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(registered, vec![event_id]);
+
+            // The collection the factory deployed really holds bob's ticket
+            let nft = ink_e2e::create_call_builder::<TicketNFT>(nft_address);
+            let owner = client
+                .call(&ink_e2e::bob(), &nft.owner_of(purchase.token_ids[0]))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(owner, Some(bob));
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn e2e_underpaid_purchase_mints_nothing<Client: E2EBackend>(
+            mut client: Client
+        ) -> E2EResult<()> {
+            let (manager, event_id, nft_address) = deploy_with_event(&mut client).await?;
+            let mut call_builder = ink_e2e::create_call_builder::<EventManager>(manager);
+            let bob = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let purchase = call_builder.purchase_ticket(event_id, 0, "TicketURI".to_string());
+            let result = client
+                .call(&ink_e2e::bob(), &purchase)
+                .value(PRICE - 1)
+                .dry_run().await?
+                .return_value();
+            assert_eq!(result, Err(Error::InsufficientPayment));
+
+            let has_ticket = client
+                .call(&ink_e2e::bob(), &call_builder.has_ticket(event_id, bob))
+                .dry_run().await?
+                .return_value();
+            assert!(!has_ticket);
+            let nft = ink_e2e::create_call_builder::<TicketNFT>(nft_address);
+            let owner = client
+                .call(&ink_e2e::bob(), &nft.owner_of(1))
+                .dry_run().await?
+                .return_value();
+            assert_eq!(owner, None);
+            Ok(())
         }
     }
 }
@@ -0,0 +1,1445 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::ticket_marketplace::{
+    Error as MarketplaceError,
+    TicketMarketplace,
+    TicketMarketplaceRef,
+};
+
+/// Resale market for BlockPass tickets. A listed or auctioned ticket sits in escrow here
+/// until it is sold or handed back, so what a buyer pays for is always deliverable.
+#[ink::contract]
+mod ticket_marketplace {
+    use ink::storage::Mapping;
+    use ticket_nft::PSP34Error;
+
+    /// Basis points denominator, i.e. 100%.
+    const MAX_BPS: u16 = 10_000;
+
+    #[ink(storage)]
+    pub struct TicketMarketplace {
+        owner: AccountId,
+        /// The marketplace's cut of every sale.
+        fee_bps: u16,
+        /// Collected fees the owner hasn't withdrawn yet.
+        fees: Balance,
+        /// Escrowed tickets for sale, keyed by `(collection, token_id)`.
+        listings: Mapping<(AccountId, u64), Listing>,
+        /// Escrowed tickets under auction, keyed like `listings`.
+        auctions: Mapping<(AccountId, u64), Auction>,
+        /// Escrowed offers on tickets, listed or not, keyed by `(collection, token_id, bidder)`.
+        offers: Mapping<(AccountId, u64, AccountId), Offer>,
+        /// Proposed ticket-for-ticket trades, keyed by the `(collection, token_id)` offered.
+        swaps: Mapping<(AccountId, u64), Swap>,
+        /// Escrowed tickets the collection wouldn't release when their sale ended, keyed
+        /// like `listings`, with the seller who may reclaim them.
+        unclaimed: Mapping<(AccountId, u64), AccountId>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Listing {
+        seller: AccountId,
+        price: Balance,
+    }
+
+    /// A timed English auction. The top bid is held here, and returned when outbid.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Auction {
+        seller: AccountId,
+        /// Lowest acceptable bid.
+        reserve: Balance,
+        end: Timestamp,
+        /// Current top bidder and their bid.
+        top_bid: Option<(AccountId, Balance)>,
+    }
+
+    /// An offer to buy a ticket for `amount`, held here until it is accepted or withdrawn.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Offer {
+        amount: Balance,
+        /// The holder can accept until then, and the bidder withdraw only after.
+        expiry: Timestamp,
+    }
+
+    /// `proposer` offers their ticket for the holder's `wanted` `(collection, token_id)`.
+    /// Neither ticket is escrowed, so both stay usable until the swap happens.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Swap {
+        proposer: AccountId,
+        wanted: (AccountId, u64),
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        NotOwner,
+        InvalidFee,
+        InvalidPrice,
+        /// Only the ticket's holder may list it.
+        NotTicketHolder,
+        AlreadyListed,
+        NotListed,
+        NotSeller,
+        /// Purchases must pay exactly the listed price.
+        IncorrectPayment,
+        /// The collection refused to move the ticket, e.g. because the marketplace
+        /// isn't approved for it.
+        TicketTransferFailed(PSP34Error),
+        /// The price is above the ticket's `max_resale_price`.
+        AboveResaleCap,
+        /// The ticket's collection bans the account from holding it.
+        Banned,
+        InvalidDuration,
+        NotAuctioned,
+        AuctionEnded,
+        AuctionNotEnded,
+        /// Bids must reach the reserve and beat the current top bid.
+        BidTooLow,
+        InvalidExpiry,
+        /// The bidder already has an offer on this ticket.
+        OfferExists,
+        NoOffer,
+        OfferExpired,
+        OfferNotExpired,
+        /// The ticket is already offered in another swap.
+        SwapExists,
+        NoSwap,
+        NotProposer,
+        /// No ticket is waiting here to be reclaimed by the caller.
+        NotUnclaimed,
+        TransferFailed,
+        NothingToWithdraw,
+        Overflow,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct TicketListed {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        seller: AccountId,
+        price: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ListingCancelled {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        seller: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TicketSold {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+        seller: AccountId,
+        price: Balance,
+        fee: Balance,
+        /// Paid to the collection's royalty beneficiary, e.g. the event host.
+        royalty: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AuctionCreated {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        seller: AccountId,
+        reserve: Balance,
+        end: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct BidPlaced {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+    }
+
+    /// `winner` is `None` when nobody bid or the collection refused the winner the ticket,
+    /// which then went back to, or waits here for, its seller.
+    #[ink(event)]
+    pub struct AuctionSettled {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        winner: Option<AccountId>,
+        price: Balance,
+    }
+
+    /// The collection refused to release an escrowed ticket to `to`, e.g. because it is
+    /// soulbound, locked or `to` is banned. Any bid was refunded and the seller can
+    /// `reclaim_ticket` once the collection allows it.
+    #[ink(event)]
+    pub struct TicketUnclaimed {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        seller: AccountId,
+        to: AccountId,
+        reason: PSP34Error,
+    }
+
+    #[ink(event)]
+    pub struct OfferPlaced {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+        expiry: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct OfferAccepted {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        bidder: AccountId,
+        seller: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct OfferCancelled {
+        #[ink(topic)]
+        collection: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        bidder: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct SwapProposed {
+        #[ink(topic)]
+        proposer: AccountId,
+        offered: (AccountId, u64),
+        wanted: (AccountId, u64),
+    }
+
+    #[ink(event)]
+    pub struct SwapAccepted {
+        #[ink(topic)]
+        proposer: AccountId,
+        #[ink(topic)]
+        acceptor: AccountId,
+        offered: (AccountId, u64),
+        wanted: (AccountId, u64),
+    }
+
+    #[ink(event)]
+    pub struct SwapCancelled {
+        #[ink(topic)]
+        proposer: AccountId,
+        offered: (AccountId, u64),
+    }
+
+    #[ink(event)]
+    pub struct FeesWithdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    impl TicketMarketplace {
+        /// Opens a marketplace taking `fee_bps` of every sale, capped at 100%.
+        #[ink(constructor)]
+        pub fn new(fee_bps: u16) -> Self {
+            Self {
+                owner: Self::env().caller(),
+                fee_bps: fee_bps.min(MAX_BPS),
+                fees: 0,
+                listings: Mapping::default(),
+                auctions: Mapping::default(),
+                offers: Mapping::default(),
+                swaps: Mapping::default(),
+                unclaimed: Mapping::default(),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        #[ink(message)]
+        pub fn get_fee_bps(&self) -> u16 {
+            self.fee_bps
+        }
+
+        #[ink(message)]
+        pub fn set_fee(&mut self, fee_bps: u16) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            if fee_bps > MAX_BPS {
+                return Err(Error::InvalidFee);
+            }
+            self.fee_bps = fee_bps;
+            Ok(())
+        }
+
+        /// Lists the caller's ticket for `price`, moving it into escrow here. The caller
+        /// must have approved the marketplace for the token on its collection first.
+        #[ink(message)]
+        pub fn list_ticket(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            price: Balance
+        ) -> Result<()> {
+            let seller = self.env().caller();
+            if price == 0 {
+                return Err(Error::InvalidPrice);
+            }
+            if self.is_for_sale(collection, token_id) {
+                return Err(Error::AlreadyListed);
+            }
+            if Self::ticket_owner(collection, token_id) != Some(seller) {
+                return Err(Error::NotTicketHolder);
+            }
+            if Self::is_banned_holder(collection, token_id, seller) {
+                return Err(Error::Banned);
+            }
+            if Self::max_resale_price(collection, token_id).map_or(false, |max| price > max) {
+                return Err(Error::AboveResaleCap);
+            }
+
+            // An `Err` reverts the listing along with the rest of the call
+            self.listings.insert((collection, token_id), &Listing { seller, price });
+            let escrow = self.env().account_id();
+            self.transfer_ticket(collection, escrow, token_id, None)
+                .map_err(Error::TicketTransferFailed)?;
+
+            self.env().emit_event(TicketListed {
+                collection,
+                token_id,
+                seller,
+                price,
+            });
+            Ok(())
+        }
+
+        /// Takes a ticket off sale and hands it back to its seller, or leaves it to be
+        /// reclaimed if the collection won't release it right now.
+        #[ink(message)]
+        pub fn cancel_listing(&mut self, collection: AccountId, token_id: u64) -> Result<()> {
+            let seller = self.env().caller();
+            let listing = self.listings.get((collection, token_id)).ok_or(Error::NotListed)?;
+            if listing.seller != seller {
+                return Err(Error::NotSeller);
+            }
+
+            self.listings.remove((collection, token_id));
+            self.return_ticket(collection, token_id, seller, seller);
+
+            self.env().emit_event(ListingCancelled {
+                collection,
+                token_id,
+                seller,
+            });
+            Ok(())
+        }
+
+        /// Buys a listed ticket: it goes to the caller and the price, less the
+        /// marketplace fee and the collection's royalty, to the seller, in one step.
+        #[ink(message, payable)]
+        pub fn buy_ticket(&mut self, collection: AccountId, token_id: u64) -> Result<()> {
+            let buyer = self.env().caller();
+            let listing = self.listings.get((collection, token_id)).ok_or(Error::NotListed)?;
+            if self.env().transferred_value() != listing.price {
+                return Err(Error::IncorrectPayment);
+            }
+
+            self.listings.remove((collection, token_id));
+            self.complete_sale(collection, token_id, listing.seller, buyer, listing.price)
+        }
+
+        /// Auctions the caller's ticket for `duration` milliseconds, starting at `reserve`.
+        /// Like a listing, the ticket moves into escrow and needs the marketplace approved.
+        #[ink(message)]
+        pub fn create_auction(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            reserve: Balance,
+            duration: Timestamp
+        ) -> Result<()> {
+            let seller = self.env().caller();
+            if reserve == 0 {
+                return Err(Error::InvalidPrice);
+            }
+            if duration == 0 {
+                return Err(Error::InvalidDuration);
+            }
+            if self.is_for_sale(collection, token_id) {
+                return Err(Error::AlreadyListed);
+            }
+            if Self::ticket_owner(collection, token_id) != Some(seller) {
+                return Err(Error::NotTicketHolder);
+            }
+            if Self::is_banned_holder(collection, token_id, seller) {
+                return Err(Error::Banned);
+            }
+            if Self::max_resale_price(collection, token_id).map_or(false, |max| reserve > max) {
+                return Err(Error::AboveResaleCap);
+            }
+
+            let end = self.env().block_timestamp().saturating_add(duration);
+            self.auctions.insert((collection, token_id), &Auction {
+                seller,
+                reserve,
+                end,
+                top_bid: None,
+            });
+            let escrow = self.env().account_id();
+            self.transfer_ticket(collection, escrow, token_id, None)
+                .map_err(Error::TicketTransferFailed)?;
+
+            self.env().emit_event(AuctionCreated {
+                collection,
+                token_id,
+                seller,
+                reserve,
+                end,
+            });
+            Ok(())
+        }
+
+        /// Bids the transferred value. The previous top bidder gets their bid back.
+        #[ink(message, payable)]
+        pub fn bid(&mut self, collection: AccountId, token_id: u64) -> Result<()> {
+            let bidder = self.env().caller();
+            let amount = self.env().transferred_value();
+            let key = (collection, token_id);
+            let mut auction = self.auctions.get(key).ok_or(Error::NotAuctioned)?;
+            if self.env().block_timestamp() >= auction.end {
+                return Err(Error::AuctionEnded);
+            }
+            let to_beat = auction.top_bid.map_or(0, |(_, top)| top);
+            if amount < auction.reserve || amount <= to_beat {
+                return Err(Error::BidTooLow);
+            }
+            // The collection would refuse a banned winner the ticket at settlement
+            if Self::is_banned_holder(collection, token_id, bidder) {
+                return Err(Error::Banned);
+            }
+            // A bid the collection would refuse to settle at would lock the ticket here
+            if Self::max_resale_price(collection, token_id).map_or(false, |max| amount > max) {
+                return Err(Error::AboveResaleCap);
+            }
+
+            let outbid = auction.top_bid.replace((bidder, amount));
+            self.auctions.insert(key, &auction);
+            if let Some((previous, refund)) = outbid {
+                self.env().transfer(previous, refund).map_err(|_| Error::TransferFailed)?;
+            }
+
+            self.env().emit_event(BidPlaced {
+                collection,
+                token_id,
+                bidder,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Closes an auction once it has ended; anyone may call it. The ticket goes to the
+        /// top bidder and their bid to the seller as in `buy_ticket`, or back to the seller
+        /// if nobody bid. Should the collection refuse the winner the ticket, the bid is
+        /// refunded and the ticket waits here for the seller to reclaim, so a settlement
+        /// can't get stuck.
+        #[ink(message)]
+        pub fn settle_auction(&mut self, collection: AccountId, token_id: u64) -> Result<()> {
+            let key = (collection, token_id);
+            let auction = self.auctions.get(key).ok_or(Error::NotAuctioned)?;
+            if self.env().block_timestamp() < auction.end {
+                return Err(Error::AuctionNotEnded);
+            }
+
+            self.auctions.remove(key);
+            let mut sold = auction.top_bid;
+            match auction.top_bid {
+                Some((winner, price)) => {
+                    match self.complete_sale(collection, token_id, auction.seller, winner, price) {
+                        Ok(()) => {}
+                        Err(Error::TicketTransferFailed(reason)) => {
+                            sold = None;
+                            self.env().transfer(winner, price).map_err(|_| Error::TransferFailed)?;
+                            self.park_ticket(collection, token_id, auction.seller, winner, reason);
+                        }
+                        Err(error) => {
+                            return Err(error);
+                        }
+                    }
+                }
+                None => {
+                    self.return_ticket(collection, token_id, auction.seller, auction.seller);
+                }
+            }
+
+            self.env().emit_event(AuctionSettled {
+                collection,
+                token_id,
+                winner: sold.map(|(winner, _)| winner),
+                price: sold.map_or(0, |(_, price)| price),
+            });
+            Ok(())
+        }
+
+        /// Sends the seller's ticket the collection refused to release earlier to `to`,
+        /// e.g. the seller themself once a transfer lock has passed.
+        #[ink(message)]
+        pub fn reclaim_ticket(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            to: AccountId
+        ) -> Result<()> {
+            let key = (collection, token_id);
+            if self.unclaimed.get(key) != Some(self.env().caller()) {
+                return Err(Error::NotUnclaimed);
+            }
+            self.unclaimed.remove(key);
+            self.transfer_ticket(collection, to, token_id, None)
+                .map_err(Error::TicketTransferFailed)
+        }
+
+        #[ink(message)]
+        pub fn get_unclaimed(&self, collection: AccountId, token_id: u64) -> Option<AccountId> {
+            self.unclaimed.get((collection, token_id))
+        }
+
+        #[ink(message)]
+        pub fn get_auction(&self, collection: AccountId, token_id: u64) -> Option<Auction> {
+            self.auctions.get((collection, token_id))
+        }
+
+        /// Offers the transferred value for a ticket, whether or not it is for sale. The
+        /// holder can accept until `expiry`, after which the caller may withdraw it.
+        #[ink(message, payable)]
+        pub fn make_offer(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            expiry: Timestamp
+        ) -> Result<()> {
+            let bidder = self.env().caller();
+            let amount = self.env().transferred_value();
+            let key = (collection, token_id, bidder);
+            if amount == 0 {
+                return Err(Error::InvalidPrice);
+            }
+            if expiry <= self.env().block_timestamp() {
+                return Err(Error::InvalidExpiry);
+            }
+            if self.offers.contains(key) {
+                return Err(Error::OfferExists);
+            }
+            match Self::ticket_owner(collection, token_id) {
+                None => {
+                    return Err(Error::NotTicketHolder);
+                }
+                Some(holder) if holder == bidder => {
+                    return Err(Error::NotTicketHolder);
+                }
+                Some(_) => {}
+            }
+            if Self::is_banned_holder(collection, token_id, bidder) {
+                return Err(Error::Banned);
+            }
+            if Self::max_resale_price(collection, token_id).map_or(false, |max| amount > max) {
+                return Err(Error::AboveResaleCap);
+            }
+
+            self.offers.insert(key, &Offer { amount, expiry });
+
+            self.env().emit_event(OfferPlaced {
+                collection,
+                token_id,
+                bidder,
+                amount,
+                expiry,
+            });
+            Ok(())
+        }
+
+        /// Sells the caller's ticket to `bidder` for their offer, settled as in
+        /// `buy_ticket`. The marketplace must be approved for the ticket.
+        #[ink(message)]
+        pub fn accept_offer(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            bidder: AccountId
+        ) -> Result<()> {
+            let seller = self.env().caller();
+            let key = (collection, token_id, bidder);
+            let offer = self.offers.get(key).ok_or(Error::NoOffer)?;
+            if self.env().block_timestamp() >= offer.expiry {
+                return Err(Error::OfferExpired);
+            }
+            // Escrowed tickets are held by the marketplace, so they can't be accepted here
+            if Self::ticket_owner(collection, token_id) != Some(seller) {
+                return Err(Error::NotTicketHolder);
+            }
+            // Either side may have been banned since the offer was made
+            let banned = Self::is_banned_holder(collection, token_id, seller) ||
+                Self::is_banned_holder(collection, token_id, bidder);
+            if banned {
+                return Err(Error::Banned);
+            }
+
+            self.offers.remove(key);
+            self.complete_sale(collection, token_id, seller, bidder, offer.amount)?;
+
+            self.env().emit_event(OfferAccepted {
+                collection,
+                token_id,
+                bidder,
+                seller,
+                amount: offer.amount,
+            });
+            Ok(())
+        }
+
+        /// Returns an expired offer's funds to the caller.
+        #[ink(message)]
+        pub fn withdraw_offer(&mut self, collection: AccountId, token_id: u64) -> Result<Balance> {
+            let bidder = self.env().caller();
+            let key = (collection, token_id, bidder);
+            let offer = self.offers.get(key).ok_or(Error::NoOffer)?;
+            if self.env().block_timestamp() < offer.expiry {
+                return Err(Error::OfferNotExpired);
+            }
+
+            self.offers.remove(key);
+            self.env().transfer(bidder, offer.amount).map_err(|_| Error::TransferFailed)?;
+
+            self.env().emit_event(OfferCancelled {
+                collection,
+                token_id,
+                bidder,
+            });
+            Ok(offer.amount)
+        }
+
+        #[ink(message)]
+        pub fn get_offer(
+            &self,
+            collection: AccountId,
+            token_id: u64,
+            bidder: AccountId
+        ) -> Option<Offer> {
+            self.offers.get((collection, token_id, bidder))
+        }
+
+        /// Proposes trading the caller's `my_token` on `my_collection` for `their_token` on
+        /// `their_collection`, e.g. tickets to different dates of a tour. Both holders
+        /// must have approved the marketplace by the time the swap is accepted.
+        #[ink(message)]
+        pub fn propose_swap(
+            &mut self,
+            my_collection: AccountId,
+            my_token: u64,
+            their_collection: AccountId,
+            their_token: u64
+        ) -> Result<()> {
+            let proposer = self.env().caller();
+            let offered = (my_collection, my_token);
+            let wanted = (their_collection, their_token);
+            if self.swaps.contains(offered) {
+                return Err(Error::SwapExists);
+            }
+            if Self::ticket_owner(my_collection, my_token) != Some(proposer) {
+                return Err(Error::NotTicketHolder);
+            }
+            let holder = match Self::ticket_owner(their_collection, their_token) {
+                Some(holder) if holder != proposer => holder,
+                _ => {
+                    return Err(Error::NotTicketHolder);
+                }
+            };
+            // Either leg would be refused when the swap is accepted
+            let banned = Self::is_banned_holder(their_collection, their_token, proposer) ||
+                Self::is_banned_holder(my_collection, my_token, holder);
+            if banned {
+                return Err(Error::Banned);
+            }
+
+            self.swaps.insert(offered, &Swap { proposer, wanted });
+
+            self.env().emit_event(SwapProposed { proposer, offered, wanted });
+            Ok(())
+        }
+
+        /// Accepts the swap offering `(collection, token_id)`: the caller, who must hold
+        /// the ticket it wants, and the proposer exchange tickets in one step. Each
+        /// collection applies its own transfer rules; no money changes hands, so there
+        /// is no price to declare against a resale cap.
+        #[ink(message)]
+        pub fn accept_swap(&mut self, collection: AccountId, token_id: u64) -> Result<()> {
+            let acceptor = self.env().caller();
+            let offered = (collection, token_id);
+            let swap = self.swaps.get(offered).ok_or(Error::NoSwap)?;
+            let (wanted_collection, wanted_token) = swap.wanted;
+            if Self::ticket_owner(wanted_collection, wanted_token) != Some(acceptor) {
+                return Err(Error::NotTicketHolder);
+            }
+            // The proposer may have sold or listed their ticket since
+            if Self::ticket_owner(collection, token_id) != Some(swap.proposer) {
+                return Err(Error::NotTicketHolder);
+            }
+
+            // An `Err` from either leg reverts both
+            self.swaps.remove(offered);
+            self.transfer_ticket(collection, acceptor, token_id, None)
+                .map_err(Error::TicketTransferFailed)?;
+            self.transfer_ticket(wanted_collection, swap.proposer, wanted_token, None)
+                .map_err(Error::TicketTransferFailed)?;
+
+            self.env().emit_event(SwapAccepted {
+                proposer: swap.proposer,
+                acceptor,
+                offered,
+                wanted: swap.wanted,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_swap(&mut self, collection: AccountId, token_id: u64) -> Result<()> {
+            let proposer = self.env().caller();
+            let offered = (collection, token_id);
+            let swap = self.swaps.get(offered).ok_or(Error::NoSwap)?;
+            if swap.proposer != proposer {
+                return Err(Error::NotProposer);
+            }
+            self.swaps.remove(offered);
+
+            self.env().emit_event(SwapCancelled { proposer, offered });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_swap(&self, collection: AccountId, token_id: u64) -> Option<Swap> {
+            self.swaps.get((collection, token_id))
+        }
+
+        /// Hands the escrowed ticket back to `to` for `seller`, or parks it for them if the
+        /// collection refuses.
+        fn return_ticket(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            seller: AccountId,
+            to: AccountId
+        ) {
+            if let Err(reason) = self.transfer_ticket(collection, to, token_id, None) {
+                self.park_ticket(collection, token_id, seller, to, reason);
+            }
+        }
+
+        fn park_ticket(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            seller: AccountId,
+            to: AccountId,
+            reason: PSP34Error
+        ) {
+            self.unclaimed.insert((collection, token_id), &seller);
+            self.env().emit_event(TicketUnclaimed {
+                collection,
+                token_id,
+                seller,
+                to,
+                reason,
+            });
+        }
+
+        fn is_for_sale(&self, collection: AccountId, token_id: u64) -> bool {
+            self.listings.contains((collection, token_id)) ||
+                self.auctions.contains((collection, token_id))
+        }
+
+        /// Hands the escrowed ticket to `buyer` and splits the `price` they paid between
+        /// the marketplace fee, the collection's royalty and the seller.
+        fn complete_sale(
+            &mut self,
+            collection: AccountId,
+            token_id: u64,
+            seller: AccountId,
+            buyer: AccountId,
+            price: Balance
+        ) -> Result<()> {
+            let fee = price.checked_mul(self.fee_bps as Balance).ok_or(Error::Overflow)?;
+            let fee = fee / MAX_BPS as Balance;
+            let (beneficiary, royalty) = Self::royalty_info(collection, token_id, price)
+                .map_or((None, 0), |(beneficiary, royalty)| {
+                    (Some(beneficiary), royalty.min(price - fee))
+                });
+            let proceeds = price - fee - royalty;
+
+            // Declaring the price lets the collection enforce its resale cap itself. The
+            // transfer goes first, so a refusal leaves nothing else changed
+            self.transfer_ticket(collection, buyer, token_id, Some(price))
+                .map_err(Error::TicketTransferFailed)?;
+            self.fees = self.fees.checked_add(fee).ok_or(Error::Overflow)?;
+            if proceeds > 0 {
+                self.env().transfer(seller, proceeds).map_err(|_| Error::TransferFailed)?;
+            }
+            if let Some(beneficiary) = beneficiary.filter(|_| royalty > 0) {
+                self.env().transfer(beneficiary, royalty).map_err(|_| Error::TransferFailed)?;
+            }
+
+            self.env().emit_event(TicketSold {
+                collection,
+                token_id,
+                buyer,
+                seller,
+                price,
+                fee,
+                royalty,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_listing(&self, collection: AccountId, token_id: u64) -> Option<Listing> {
+            self.listings.get((collection, token_id))
+        }
+
+        #[ink(message)]
+        pub fn get_fees(&self) -> Balance {
+            self.fees
+        }
+
+        #[ink(message)]
+        pub fn withdraw_fees(&mut self) -> Result<Balance> {
+            let owner = self.env().caller();
+            if owner != self.owner {
+                return Err(Error::NotOwner);
+            }
+            let amount = self.fees;
+            if amount == 0 {
+                return Err(Error::NothingToWithdraw);
+            }
+
+            self.fees = 0;
+            self.env().transfer(owner, amount).map_err(|_| Error::TransferFailed)?;
+
+            self.env().emit_event(FeesWithdrawn { to: owner, amount });
+            Ok(amount)
+        }
+
+        /// Looks up who holds `token_id`. Unit tests can't make cross-contract calls and
+        /// ask the `MockPSP34` deployed at `collection` instead.
+        fn ticket_owner(collection: AccountId, token_id: u64) -> Option<AccountId> {
+            #[cfg(not(test))]
+            {
+                let nft: ink::contract_ref!(ticket_nft::PSP34) = collection.into();
+                nft.owner_of(ticket_nft::Id::U64(token_id))
+            }
+            #[cfg(test)]
+            {
+                mock_psp34::with(collection, |nft| nft.owner_of(token_id))
+            }
+        }
+
+        fn max_resale_price(collection: AccountId, token_id: u64) -> Option<Balance> {
+            #[cfg(not(test))]
+            {
+                let nft: ink::contract_ref!(ticket_nft::TicketResale) = collection.into();
+                nft.max_resale_price(token_id)
+            }
+            #[cfg(test)]
+            {
+                mock_psp34::with(collection, |nft| nft.max_resale_price(token_id))
+            }
+        }
+
+        fn is_banned_holder(collection: AccountId, token_id: u64, account: AccountId) -> bool {
+            #[cfg(not(test))]
+            {
+                let nft: ink::contract_ref!(ticket_nft::TicketResale) = collection.into();
+                nft.is_banned_holder(token_id, account)
+            }
+            #[cfg(test)]
+            {
+                mock_psp34::with(collection, |nft| nft.is_banned(account))
+            }
+        }
+
+        /// The collection's royalty on selling `token_id` for `sale_price`, if it has one.
+        fn royalty_info(
+            collection: AccountId,
+            token_id: u64,
+            sale_price: Balance
+        ) -> Option<(AccountId, Balance)> {
+            #[cfg(not(test))]
+            {
+                let nft: ink::contract_ref!(ticket_nft::PSP34Royalty) = collection.into();
+                nft.royalty_info(ticket_nft::Id::U64(token_id), sale_price)
+            }
+            #[cfg(test)]
+            {
+                mock_psp34::with(collection, |nft| nft.royalty_info(sale_price))
+            }
+        }
+
+        /// Moves `token_id` to `to` as an approved operator, or as its holder while it is
+        /// in escrow. A sale passes its `price` along as the transfer's data.
+        fn transfer_ticket(
+            &self,
+            collection: AccountId,
+            to: AccountId,
+            token_id: u64,
+            price: Option<Balance>
+        ) -> core::result::Result<(), PSP34Error> {
+            #[cfg(not(test))]
+            {
+                use ink::scale::Encode;
+                let data = price.map(|price| price.encode()).unwrap_or_default();
+                let mut nft: ink::contract_ref!(ticket_nft::PSP34) = collection.into();
+                nft.transfer(to, ticket_nft::Id::U64(token_id), data)
+            }
+            #[cfg(test)]
+            {
+                let operator = self.env().account_id();
+                mock_psp34::with(collection, |nft| nft.transfer(operator, to, token_id, price))
+            }
+        }
+    }
+
+    /// Off-chain stand-in for a PSP34 collection, keyed by the address it is "deployed"
+    /// at. Only owners and operators approved for all of an owner's tokens can move them.
+    #[cfg(test)]
+    mod mock_psp34 {
+        use super::{ AccountId, Balance, PSP34Error, MAX_BPS };
+        use std::{ cell::RefCell, collections::{ BTreeMap, BTreeSet } };
+
+        thread_local! {
+            static COLLECTIONS: RefCell<BTreeMap<AccountId, MockPSP34>> =
+                RefCell::new(BTreeMap::new());
+        }
+
+        #[derive(Default)]
+        pub struct MockPSP34 {
+            owners: BTreeMap<u64, AccountId>,
+            operators: BTreeSet<(AccountId, AccountId)>,
+            max_prices: BTreeMap<u64, Balance>,
+            royalty: Option<(AccountId, u16)>,
+            locked: bool,
+            banned: BTreeSet<AccountId>,
+        }
+
+        impl MockPSP34 {
+            pub fn owner_of(&self, token_id: u64) -> Option<AccountId> {
+                self.owners.get(&token_id).copied()
+            }
+
+            pub fn mint(&mut self, to: AccountId, token_id: u64) {
+                self.owners.insert(token_id, to);
+            }
+
+            pub fn approve_all(&mut self, owner: AccountId, operator: AccountId) {
+                self.operators.insert((owner, operator));
+            }
+
+            pub fn max_resale_price(&self, token_id: u64) -> Option<Balance> {
+                self.max_prices.get(&token_id).copied()
+            }
+
+            pub fn set_max_resale_price(&mut self, token_id: u64, max_price: Balance) {
+                self.max_prices.insert(token_id, max_price);
+            }
+
+            pub fn royalty_info(&self, sale_price: Balance) -> Option<(AccountId, Balance)> {
+                self.royalty.map(|(beneficiary, royalty_bps)| {
+                    (beneficiary, sale_price * royalty_bps as Balance / MAX_BPS as Balance)
+                })
+            }
+
+            pub fn set_royalty(&mut self, beneficiary: AccountId, royalty_bps: u16) {
+                self.royalty = Some((beneficiary, royalty_bps));
+            }
+
+            pub fn is_banned(&self, account: AccountId) -> bool {
+                self.banned.contains(&account)
+            }
+
+            pub fn ban(&mut self, account: AccountId) {
+                self.banned.insert(account);
+            }
+
+            /// Refuses every transfer while set, like a soulbound or locked collection.
+            pub fn set_locked(&mut self, locked: bool) {
+                self.locked = locked;
+            }
+
+            pub fn transfer(
+                &mut self,
+                caller: AccountId,
+                to: AccountId,
+                token_id: u64,
+                price: Option<Balance>
+            ) -> Result<(), PSP34Error> {
+                let owner = self.owner_of(token_id).ok_or(PSP34Error::TokenNotExists)?;
+                if caller != owner && !self.operators.contains(&(owner, caller)) {
+                    return Err(PSP34Error::NotApproved);
+                }
+                if self.locked {
+                    return Err(PSP34Error::Custom("TransferLocked".into()));
+                }
+                let max_price = self.max_resale_price(token_id);
+                if price.zip(max_price).map_or(false, |(price, max)| price > max) {
+                    return Err(PSP34Error::Custom("ResalePriceExceeded".into()));
+                }
+                self.owners.insert(token_id, to);
+                Ok(())
+            }
+        }
+
+        /// Deploys an empty collection at `address`, replacing any earlier one.
+        pub fn deploy(address: AccountId) {
+            COLLECTIONS.with(|collections| {
+                collections.borrow_mut().insert(address, MockPSP34::default())
+            });
+        }
+
+        pub fn with<R>(address: AccountId, f: impl FnOnce(&mut MockPSP34) -> R) -> R {
+            COLLECTIONS.with(|collections| {
+                let mut collections = collections.borrow_mut();
+                f(collections.get_mut(&address).expect("no mock collection at this address"))
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const COLLECTION: [u8; 32] = [0x7; 32];
+
+        /// A marketplace taking 5% and a collection where bob holds token 1 and has
+        /// approved the marketplace for all his tokens.
+        fn setup() -> (TicketMarketplace, AccountId) {
+            let marketplace = TicketMarketplace::new(500);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let collection = AccountId::from(COLLECTION);
+            mock_psp34::deploy(collection);
+            mock_psp34::with(collection, |nft| {
+                nft.mint(accounts.bob, 1);
+                nft.approve_all(accounts.bob, marketplace.env().account_id());
+            });
+            (marketplace, collection)
+        }
+
+        #[ink::test]
+        fn test_list_and_buy() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let escrow = marketplace.env().account_id();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(escrow, 10_000_000);
+
+            // Only the holder lists, and the ticket moves into escrow
+            assert_eq!(
+                marketplace.list_ticket(collection, 1, 1_000_000),
+                Err(Error::NotTicketHolder)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.list_ticket(collection, 1, 0), Err(Error::InvalidPrice));
+            assert_eq!(marketplace.list_ticket(collection, 1, 1_000_000), Ok(()));
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(escrow));
+            assert_eq!(
+                marketplace.get_listing(collection, 1),
+                Some(Listing { seller: accounts.bob, price: 1_000_000 })
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(999_999);
+            assert_eq!(marketplace.buy_ticket(collection, 1), Err(Error::IncorrectPayment));
+            let seller_before =
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob)
+                    .unwrap_or(0);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(marketplace.buy_ticket(collection, 1), Ok(()));
+
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.charlie));
+            assert_eq!(marketplace.get_listing(collection, 1), None);
+            let seller_after =
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob)
+                    .unwrap_or(0);
+            assert_eq!(seller_after - seller_before, 950_000);
+            assert_eq!(marketplace.get_fees(), 50_000);
+            assert_eq!(marketplace.buy_ticket(collection, 1), Err(Error::NotListed));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(marketplace.withdraw_fees(), Ok(50_000));
+            assert_eq!(marketplace.withdraw_fees(), Err(Error::NothingToWithdraw));
+        }
+
+        #[ink::test]
+        fn test_cancel_listing() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.list_ticket(collection, 1, 1_000_000), Ok(()));
+            assert_eq!(
+                marketplace.list_ticket(collection, 1, 1_000_000),
+                Err(Error::AlreadyListed)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(marketplace.cancel_listing(collection, 1), Err(Error::NotSeller));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.cancel_listing(collection, 1), Ok(()));
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.bob));
+            assert_eq!(marketplace.cancel_listing(collection, 1), Err(Error::NotListed));
+        }
+
+        #[ink::test]
+        fn test_cancel_listing_refused() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let escrow = marketplace.env().account_id();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.list_ticket(collection, 1, 1_000_000), Ok(()));
+            mock_psp34::with(collection, |nft| nft.set_locked(true));
+            // The listing still closes, and the ticket waits in escrow for bob
+            assert_eq!(marketplace.cancel_listing(collection, 1), Ok(()));
+            assert_eq!(marketplace.get_listing(collection, 1), None);
+            assert_eq!(marketplace.get_unclaimed(collection, 1), Some(accounts.bob));
+            assert_eq!(mock_psp34::with(collection, |nft| nft.owner_of(1)), Some(escrow));
+            assert_eq!(
+                marketplace.reclaim_ticket(collection, 1, accounts.bob),
+                Err(Error::TicketTransferFailed(PSP34Error::Custom("TransferLocked".into())))
+            );
+
+            mock_psp34::with(collection, |nft| nft.set_locked(false));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                marketplace.reclaim_ticket(collection, 1, accounts.charlie),
+                Err(Error::NotUnclaimed)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.reclaim_ticket(collection, 1, accounts.bob), Ok(()));
+            assert_eq!(mock_psp34::with(collection, |nft| nft.owner_of(1)), Some(accounts.bob));
+            assert_eq!(marketplace.get_unclaimed(collection, 1), None);
+        }
+
+        #[ink::test]
+        fn test_resale_cap() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            mock_psp34::with(collection, |nft| nft.set_max_resale_price(1, 1_100_000));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                marketplace.list_ticket(collection, 1, 1_100_001),
+                Err(Error::AboveResaleCap)
+            );
+            assert_eq!(marketplace.list_ticket(collection, 1, 1_100_000), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_royalty() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let escrow = marketplace.env().account_id();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(escrow, 10_000_000);
+            mock_psp34::with(collection, |nft| nft.set_royalty(accounts.django, 1_000));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.list_ticket(collection, 1, 1_000_000), Ok(()));
+            let balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .unwrap_or(0)
+            };
+            let (seller_before, host_before) = (balance(accounts.bob), balance(accounts.django));
+
+            // 5% to the marketplace, 10% to the host and the rest to the seller
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(marketplace.buy_ticket(collection, 1), Ok(()));
+            assert_eq!(balance(accounts.bob) - seller_before, 850_000);
+            assert_eq!(balance(accounts.django) - host_before, 100_000);
+            assert_eq!(marketplace.get_fees(), 50_000);
+        }
+
+        #[ink::test]
+        fn test_english_auction() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let escrow = marketplace.env().account_id();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(escrow, 10_000_000);
+            let balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .unwrap_or(0)
+            };
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                marketplace.create_auction(collection, 1, 1_000_000, 0),
+                Err(Error::InvalidDuration)
+            );
+            assert_eq!(marketplace.create_auction(collection, 1, 1_000_000, 60_000), Ok(()));
+            assert_eq!(
+                marketplace.list_ticket(collection, 1, 1_000_000),
+                Err(Error::AlreadyListed)
+            );
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(escrow));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(999_999);
+            assert_eq!(marketplace.bid(collection, 1), Err(Error::BidTooLow));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(marketplace.bid(collection, 1), Ok(()));
+            assert_eq!(marketplace.settle_auction(collection, 1), Err(Error::AuctionNotEnded));
+
+            // Outbidding refunds the previous top bid
+            let charlie_before = balance(accounts.charlie);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(marketplace.bid(collection, 1), Err(Error::BidTooLow));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000_000);
+            assert_eq!(marketplace.bid(collection, 1), Ok(()));
+            assert_eq!(balance(accounts.charlie) - charlie_before, 1_000_000);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(60_000);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(3_000_000);
+            assert_eq!(marketplace.bid(collection, 1), Err(Error::AuctionEnded));
+            let seller_before = balance(accounts.bob);
+            assert_eq!(marketplace.settle_auction(collection, 1), Ok(()));
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.django));
+            assert_eq!(balance(accounts.bob) - seller_before, 1_900_000);
+            assert_eq!(marketplace.get_fees(), 100_000);
+            assert_eq!(marketplace.settle_auction(collection, 1), Err(Error::NotAuctioned));
+        }
+
+        #[ink::test]
+        fn test_refused_auction_settlement() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let escrow = marketplace.env().account_id();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(escrow, 10_000_000);
+            let balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .unwrap_or(0)
+            };
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.create_auction(collection, 1, 1_000_000, 60_000), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(marketplace.bid(collection, 1), Ok(()));
+
+            // The host lowers the resale cap below the winning bid before settlement
+            mock_psp34::with(collection, |nft| nft.set_max_resale_price(1, 500_000));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(60_000);
+            let (bidder_before, seller_before) = (balance(accounts.charlie), balance(accounts.bob));
+            assert_eq!(marketplace.settle_auction(collection, 1), Ok(()));
+            assert_eq!(balance(accounts.charlie) - bidder_before, 1_000_000);
+            assert_eq!(balance(accounts.bob), seller_before);
+            assert_eq!(marketplace.get_fees(), 0);
+            assert_eq!(marketplace.get_auction(collection, 1), None);
+            assert_eq!(marketplace.get_unclaimed(collection, 1), Some(accounts.bob));
+
+            // No price is declared on the way back, so the cap doesn't apply
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.reclaim_ticket(collection, 1, accounts.bob), Ok(()));
+            assert_eq!(mock_psp34::with(collection, |nft| nft.owner_of(1)), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn test_auction_without_bids() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.create_auction(collection, 1, 1_000_000, 60_000), Ok(()));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(60_000);
+            assert_eq!(marketplace.settle_auction(collection, 1), Ok(()));
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.bob));
+            assert_eq!(marketplace.get_auction(collection, 1), None);
+        }
+
+        #[ink::test]
+        fn test_offers() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let escrow = marketplace.env().account_id();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(escrow, 10_000_000);
+            let balance = |account| {
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account)
+                    .unwrap_or(0)
+            };
+
+            // Offers on a ticket bob never listed
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(marketplace.make_offer(collection, 1, 0), Err(Error::InvalidExpiry));
+            assert_eq!(marketplace.make_offer(collection, 1, 60_000), Ok(()));
+            assert_eq!(marketplace.make_offer(collection, 1, 60_000), Err(Error::OfferExists));
+            assert_eq!(marketplace.withdraw_offer(collection, 1), Err(Error::OfferNotExpired));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(2_000_000);
+            assert_eq!(marketplace.make_offer(collection, 1, 30_000), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let not_holder = marketplace.accept_offer(collection, 1, accounts.django);
+            assert_eq!(not_holder, Err(Error::NotTicketHolder));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let seller_before = balance(accounts.bob);
+            assert_eq!(marketplace.accept_offer(collection, 1, accounts.django), Ok(()));
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.django));
+            assert_eq!(balance(accounts.bob) - seller_before, 1_900_000);
+            assert_eq!(marketplace.get_offer(collection, 1, accounts.django), None);
+
+            // The losing offer can only come back once it expires
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(60_000);
+            let expired = marketplace.accept_offer(collection, 1, accounts.charlie);
+            assert_eq!(expired, Err(Error::OfferExpired));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let charlie_before = balance(accounts.charlie);
+            assert_eq!(marketplace.withdraw_offer(collection, 1), Ok(1_000_000));
+            assert_eq!(balance(accounts.charlie) - charlie_before, 1_000_000);
+            assert_eq!(marketplace.withdraw_offer(collection, 1), Err(Error::NoOffer));
+        }
+
+        #[ink::test]
+        fn test_swap() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let other_date = AccountId::from([0x8; 32]);
+            mock_psp34::deploy(other_date);
+            mock_psp34::with(other_date, |nft| {
+                nft.mint(accounts.charlie, 1);
+                nft.approve_all(accounts.charlie, marketplace.env().account_id());
+            });
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let not_holder = marketplace.propose_swap(collection, 1, other_date, 1);
+            assert_eq!(not_holder, Err(Error::NotTicketHolder));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.propose_swap(collection, 1, other_date, 1), Ok(()));
+            let again = marketplace.propose_swap(collection, 1, other_date, 1);
+            assert_eq!(again, Err(Error::SwapExists));
+            assert_eq!(marketplace.accept_swap(collection, 1), Err(Error::NotTicketHolder));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(marketplace.cancel_swap(collection, 1), Err(Error::NotProposer));
+            assert_eq!(marketplace.accept_swap(collection, 1), Ok(()));
+            let owner = mock_psp34::with(collection, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.charlie));
+            let owner = mock_psp34::with(other_date, |nft| nft.owner_of(1));
+            assert_eq!(owner, Some(accounts.bob));
+            assert_eq!(marketplace.get_swap(collection, 1), None);
+        }
+
+        #[ink::test]
+        fn test_banned_accounts() {
+            let (mut marketplace, collection) = setup();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let other_date = AccountId::from([0x8; 32]);
+            mock_psp34::deploy(other_date);
+            mock_psp34::with(other_date, |nft| nft.mint(accounts.charlie, 1));
+            mock_psp34::with(collection, |nft| {
+                nft.mint(accounts.eve, 2);
+                nft.mint(accounts.bob, 3);
+            });
+
+            // Offers made before the bans below
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.frank);
+            assert_eq!(marketplace.make_offer(collection, 2, 60_000), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(marketplace.make_offer(collection, 3, 60_000), Ok(()));
+            mock_psp34::with(collection, |nft| {
+                nft.ban(accounts.charlie);
+                nft.ban(accounts.django);
+                nft.ban(accounts.eve);
+            });
+
+            // Charlie couldn't receive bob's ticket, so the swap is refused up front
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let swap = marketplace.propose_swap(collection, 1, other_date, 1);
+            assert_eq!(swap, Err(Error::Banned));
+            assert_eq!(marketplace.create_auction(collection, 1, 1_000_000, 60_000), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(1_000_000);
+            assert_eq!(marketplace.bid(collection, 1), Err(Error::Banned));
+            assert_eq!(marketplace.make_offer(collection, 1, 60_000), Err(Error::Banned));
+            assert_eq!(marketplace.get_auction(collection, 1).unwrap().top_bid, None);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(marketplace.list_ticket(collection, 2, 1_000_000), Err(Error::Banned));
+            let auction = marketplace.create_auction(collection, 2, 1_000_000, 60_000);
+            assert_eq!(auction, Err(Error::Banned));
+            let accepted = marketplace.accept_offer(collection, 2, accounts.frank);
+            assert_eq!(accepted, Err(Error::Banned));
+
+            // A bidder banned after offering can't be sold to either
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let accepted = marketplace.accept_offer(collection, 3, accounts.django);
+            assert_eq!(accepted, Err(Error::Banned));
+            assert!(marketplace.get_offer(collection, 3, accounts.django).is_some());
+        }
+
+        #[ink::test]
+        fn test_set_fee() {
+            let (mut marketplace, _) = setup();
+            assert_eq!(marketplace.set_fee(10_001), Err(Error::InvalidFee));
+            assert_eq!(marketplace.set_fee(250), Ok(()));
+            assert_eq!(marketplace.get_fee_bps(), 250);
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(marketplace.set_fee(0), Err(Error::NotOwner));
+        }
+    }
+}
@@ -0,0 +1,2265 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::ticket_nft::{ Error as TicketNftError, TicketNFT, TicketNFTRef };
+
+use ink::prelude::{ string::String, vec::Vec };
+use ink::primitives::{ AccountId, Balance };
+
+/// What a ticket is for, kept on-chain so verifiers can check a ticket's event without
+/// trusting its off-chain JSON.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct TicketData {
+    pub event_id: u64,
+    pub tier: u32,
+    pub seat: Option<u32>,
+    /// Block timestamp of the mint.
+    pub issued_at: u64,
+    /// When the event takes place, as supplied at mint; the transfer lock counts from it.
+    pub event_date: u64,
+    /// After this the ticket is no longer valid and can't be redeemed.
+    pub expires_at: Option<u64>,
+}
+
+/// One event's slice of a collection shared between several events. Its tickets are the
+/// ones whose `TicketData` names the event.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct EventCollection {
+    pub name: String,
+    /// Most tickets ever minted for the event, burned ones included; `None` for no cap.
+    pub max_supply: Option<u64>,
+    pub minted: u64,
+}
+
+/// PSP34 token id. Tickets are always `U64`; the other variants exist for compatibility
+/// with the standard and never match a token.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum Id {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum PSP34Error {
+    Custom(String),
+    SelfApprove,
+    NotApproved,
+    TokenExists,
+    TokenNotExists,
+    SafeTransferCheckFailed(String),
+}
+
+/// The PSP34 NFT standard. Selectors derive from the `PSP34::` prefix, so wallets,
+/// explorers and marketplaces can read and move tickets of any collection.
+#[ink::trait_definition]
+pub trait PSP34 {
+    /// Identifies the collection: its contract address, as bytes.
+    #[ink(message)]
+    fn collection_id(&self) -> Id;
+
+    /// Number of tokens `owner` holds, badges included.
+    #[ink(message)]
+    fn balance_of(&self, owner: AccountId) -> u32;
+
+    #[ink(message)]
+    fn owner_of(&self, id: Id) -> Option<AccountId>;
+
+    /// Whether `operator` may move `owner`'s token `id`, or all of them with `None`.
+    #[ink(message)]
+    fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool;
+
+    /// Lets `operator` move the caller's token `id`, or all of them with `None`.
+    #[ink(message)]
+    fn approve(
+        &mut self,
+        operator: AccountId,
+        id: Option<Id>,
+        approved: bool
+    ) -> core::result::Result<(), PSP34Error>;
+
+    /// Moves token `id` from its owner to `to`. The caller must be the owner or approved.
+    /// Sales may declare their price as SCALE-encoded `Balance` in `data`; BlockPass
+    /// collections reject declared prices above their resale cap.
+    #[ink(message)]
+    fn transfer(
+        &mut self,
+        to: AccountId,
+        id: Id,
+        data: Vec<u8>
+    ) -> core::result::Result<(), PSP34Error>;
+
+    /// Tokens in existence; burnt tokens don't count.
+    #[ink(message)]
+    fn total_supply(&self) -> u128;
+}
+
+/// Implemented by contracts that can hold tickets. `safe_transfer` checks in with a
+/// receiving contract first and doesn't deliver to one that rejects the token or lacks
+/// the hook, so tickets don't end up stuck where nobody can move them.
+#[ink::trait_definition]
+pub trait PSP34Receiver {
+    /// Accepts token `id`, sent from `from` by `operator`, or rejects it with a reason.
+    #[ink(message)]
+    fn before_received(
+        &mut self,
+        operator: AccountId,
+        from: AccountId,
+        id: Id,
+        data: Vec<u8>
+    ) -> core::result::Result<(), PSP34Error>;
+}
+
+/// Told about every ticket that changes hands, so it can keep its own records in step.
+/// `EventManager` implements it; a transfer the observer rejects doesn't happen.
+#[ink::trait_definition]
+pub trait TicketObserver {
+    #[ink(message)]
+    fn on_ticket_transfer(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        token_id: u64
+    ) -> core::result::Result<(), TicketNftError>;
+
+    /// Told when a holder or their operator burns a ticket. Burns the observer makes
+    /// itself, e.g. for refunds, aren't reported back.
+    #[ink(message)]
+    fn on_ticket_burn(
+        &mut self,
+        owner: AccountId,
+        token_id: u64
+    ) -> core::result::Result<(), TicketNftError>;
+
+    /// Whether `account` is banned from holding the calling collection's `token_id`.
+    #[ink(message)]
+    fn is_banned_holder(&self, token_id: u64, account: AccountId) -> bool;
+}
+
+/// What `EventManager` needs from a ticket collection. Message selectors derive from the
+/// trait, so any contract implementing it can back an event, not just `TicketNFT`.
+#[ink::trait_definition]
+pub trait TicketMinter {
+    /// Mints a ticket of `tier` to `recipient` and returns its token id.
+    #[ink(message)]
+    fn mint(
+        &mut self,
+        recipient: AccountId,
+        token_uri: String,
+        tier: u32
+    ) -> core::result::Result<u64, TicketNftError>;
+
+    /// Mints one ticket of `data.tier` per URI to `recipient`, in one call, each recording
+    /// `data` with the collection stamping `issued_at`. Token ids are consecutive and
+    /// returned in order.
+    #[ink(message)]
+    fn mint_batch(
+        &mut self,
+        recipient: AccountId,
+        token_uris: Vec<String>,
+        data: TicketData
+    ) -> core::result::Result<Vec<u64>, TicketNftError>;
+
+    #[ink(message)]
+    fn owner_of(&self, token_id: u64) -> Option<AccountId>;
+
+    /// Destroys a ticket. The collection owner burns for refunds and cancellations; the
+    /// holder or an approved operator may also give a ticket up.
+    #[ink(message)]
+    fn burn(&mut self, token_id: u64) -> core::result::Result<(), TicketNftError>;
+
+    /// Marks a ticket as used for entry. Each ticket can be redeemed once.
+    #[ink(message)]
+    fn redeem(&mut self, token_id: u64) -> core::result::Result<(), TicketNftError>;
+
+    #[ink(message)]
+    fn is_redeemed(&self, token_id: u64) -> bool;
+
+    /// Mints a proof-of-attendance badge to `recipient`. Badges share the token id space
+    /// with tickets but can never change hands.
+    #[ink(message)]
+    fn mint_badge(&mut self, recipient: AccountId) -> core::result::Result<u64, TicketNftError>;
+
+    #[ink(message)]
+    fn is_badge(&self, token_id: u64) -> bool;
+
+    /// Caps resales at `cap_bps` of each tier's face value, `face_values` listed by tier,
+    /// or lifts the cap with `None`. Tickets outside those tiers, like comps, can't be
+    /// resold for anything while a cap is set.
+    #[ink(message)]
+    fn set_resale_cap(
+        &mut self,
+        cap_bps: Option<u16>,
+        face_values: Vec<Balance>
+    ) -> core::result::Result<(), TicketNftError>;
+
+    /// Pays `royalty_bps` of every resale to `beneficiary`; 0 turns royalties off.
+    #[ink(message)]
+    fn set_royalty(
+        &mut self,
+        beneficiary: AccountId,
+        royalty_bps: u16
+    ) -> core::result::Result<(), TicketNftError>;
+
+    /// Sets a ticket attribute such as its seat, read back through `PSP34Metadata`.
+    #[ink(message)]
+    fn set_attribute(
+        &mut self,
+        token_id: u64,
+        key: Vec<u8>,
+        value: Vec<u8>
+    ) -> core::result::Result<(), TicketNftError>;
+
+    /// Freezes every token's attributes for good, e.g. once sales open.
+    #[ink(message)]
+    fn freeze_metadata(&mut self) -> core::result::Result<(), TicketNftError>;
+
+    /// Binds every ticket to its holder with `false`: transfers and approvals fail, while
+    /// burning and redemption keep working.
+    #[ink(message)]
+    fn set_transferable(&mut self, transferable: bool) -> core::result::Result<(), TicketNftError>;
+
+    /// Locks tickets from transfer until `delay` milliseconds after their event date, or
+    /// lifts the lock with `None`. Tickets minted without `TicketData` aren't locked.
+    #[ink(message)]
+    fn set_transfer_lock(&mut self, delay: Option<u64>) -> core::result::Result<(), TicketNftError>;
+
+    /// Opens a scope for `event_id`'s tickets on a collection shared between events, with
+    /// its own name, supply cap and minters.
+    #[ink(message)]
+    fn create_collection(
+        &mut self,
+        event_id: u64,
+        name: String,
+        max_supply: Option<u64>
+    ) -> core::result::Result<(), TicketNftError>;
+
+    /// Records `seat` in the ticket's `TicketData`, or clears it with `None`.
+    #[ink(message)]
+    fn assign_seat(
+        &mut self,
+        token_id: u64,
+        seat: Option<u32>
+    ) -> core::result::Result<(), TicketNftError>;
+
+    /// Reissues a ticket stuck where nobody can move it: burns `old_token_id` and mints a
+    /// replacement to `new_owner` with the same URI, tier and `TicketData`. Only the
+    /// collection's owner may recover, and redeemed tickets can't be. Returns the new
+    /// token id.
+    #[ink(message)]
+    fn recover_ticket(
+        &mut self,
+        old_token_id: u64,
+        new_owner: AccountId
+    ) -> core::result::Result<u64, TicketNftError>;
+
+    /// Completes a `transfer_ownership` to the caller and makes it the observer as well,
+    /// so an `EventManager` can take over a collection deployed by someone else.
+    #[ink(message)]
+    fn adopt_collection(&mut self) -> core::result::Result<(), TicketNftError>;
+}
+
+/// Resale restrictions secondary markets are expected to check before selling a ticket.
+#[ink::trait_definition]
+pub trait TicketResale {
+    /// Highest price `token_id` may be resold for, or `None` if resales are uncapped.
+    #[ink(message)]
+    fn max_resale_price(&self, token_id: u64) -> Option<Balance>;
+
+    /// Whether `account` is banned from holding `token_id`, so a transfer to them would
+    /// be refused. Always `false` without an observer to ask.
+    #[ink(message)]
+    fn is_banned_holder(&self, token_id: u64, account: AccountId) -> bool;
+}
+
+/// Royalty extension to PSP34, in the manner of ERC-2981: marketplaces selling token `id`
+/// for `sale_price` pay the returned amount to the returned beneficiary out of it.
+#[ink::trait_definition]
+pub trait PSP34Royalty {
+    /// Who gets how much of a `sale_price` sale, or `None` if the token carries no royalty.
+    #[ink(message)]
+    fn royalty_info(&self, id: Id, sale_price: Balance) -> Option<(AccountId, Balance)>;
+}
+
+/// Metadata extension to PSP34. The collection's own id answers the `name` and `symbol`
+/// keys; tokens answer whatever attributes were set on them.
+#[ink::trait_definition]
+pub trait PSP34Metadata {
+    #[ink(message)]
+    fn get_attribute(&self, id: Id, key: Vec<u8>) -> Option<Vec<u8>>;
+}
+
+/// Lets callers check what a collection implements before relying on it, in the manner
+/// of ERC-165. An interface's id is the XOR of its message selectors.
+#[ink::trait_definition]
+pub trait InterfaceDetection {
+    #[ink(message)]
+    fn supports_interface(&self, interface_id: u32) -> bool;
+}
+
+pub const INTERFACE_DETECTION_ID: u32 = ink::selector_id!("InterfaceDetection::supports_interface");
+
+pub const PSP34_INTERFACE_ID: u32 =
+    ink::selector_id!("PSP34::collection_id") ^
+    ink::selector_id!("PSP34::balance_of") ^
+    ink::selector_id!("PSP34::owner_of") ^
+    ink::selector_id!("PSP34::allowance") ^
+    ink::selector_id!("PSP34::approve") ^
+    ink::selector_id!("PSP34::transfer") ^
+    ink::selector_id!("PSP34::total_supply");
+
+pub const PSP34_METADATA_INTERFACE_ID: u32 = ink::selector_id!("PSP34Metadata::get_attribute");
+
+pub const PSP34_ROYALTY_INTERFACE_ID: u32 = ink::selector_id!("PSP34Royalty::royalty_info");
+
+/// The BlockPass ticket extension: everything `EventManager` calls through `TicketMinter`.
+pub const TICKET_MINTER_INTERFACE_ID: u32 =
+    ink::selector_id!("TicketMinter::mint") ^
+    ink::selector_id!("TicketMinter::mint_batch") ^
+    ink::selector_id!("TicketMinter::owner_of") ^
+    ink::selector_id!("TicketMinter::burn") ^
+    ink::selector_id!("TicketMinter::redeem") ^
+    ink::selector_id!("TicketMinter::is_redeemed") ^
+    ink::selector_id!("TicketMinter::mint_badge") ^
+    ink::selector_id!("TicketMinter::is_badge") ^
+    ink::selector_id!("TicketMinter::set_resale_cap") ^
+    ink::selector_id!("TicketMinter::set_royalty") ^
+    ink::selector_id!("TicketMinter::set_attribute") ^
+    ink::selector_id!("TicketMinter::freeze_metadata") ^
+    ink::selector_id!("TicketMinter::set_transferable") ^
+    ink::selector_id!("TicketMinter::set_transfer_lock") ^
+    ink::selector_id!("TicketMinter::create_collection") ^
+    ink::selector_id!("TicketMinter::assign_seat") ^
+    ink::selector_id!("TicketMinter::recover_ticket") ^
+    ink::selector_id!("TicketMinter::adopt_collection");
+
+#[ink::contract]
+mod ticket_nft {
+    use super::{
+        EventCollection,
+        Id,
+        InterfaceDetection,
+        INTERFACE_DETECTION_ID,
+        PSP34_INTERFACE_ID,
+        PSP34_METADATA_INTERFACE_ID,
+        PSP34_ROYALTY_INTERFACE_ID,
+        PSP34Error,
+        PSP34Metadata,
+        PSP34Receiver,
+        PSP34Royalty,
+        TicketData,
+        TicketMinter,
+        TicketObserver,
+        TicketResale,
+        PSP34,
+        TICKET_MINTER_INTERFACE_ID,
+    };
+    use ink::prelude::{ string::String, vec::Vec };
+    use ink::storage::Mapping;
+
+    /// Basis points denominator, i.e. 100%.
+    const MAX_BPS: u16 = 10_000;
+    /// Most token ids an enumeration query returns, or a batch transfer moves, at once.
+    const MAX_PAGE_SIZE: u32 = 100;
+
+    #[ink(storage)]
+    pub struct TicketNFT {
+        owner: AccountId,
+        /// Nominated by `transfer_ownership` and owner once they accept.
+        pending_owner: Option<AccountId>,
+        /// Stops all minting while set, e.g. while a compromised minter is removed.
+        paused: bool,
+        name: String,
+        symbol: String,
+        token_id_counter: u64,
+        tokens: Mapping<u64, AccountId>, // Maps token_id to the owner
+        balances: Mapping<AccountId, u32>, // Maps an owner to how many tokens they hold
+        total_supply: u64,
+        /// Each owner's tokens by position `0..balance`, and each token's position there.
+        owned_tokens: Mapping<(AccountId, u32), u64>,
+        owned_index: Mapping<u64, u32>,
+        /// Every token in existence by position `0..total_supply`, and the reverse.
+        all_tokens: Mapping<u64, u64>,
+        all_index: Mapping<u64, u64>,
+        token_uris: Mapping<u64, String>, // Maps token_id to a URI
+        token_tiers: Mapping<u64, u32>, // Maps token_id to the event's ticket tier
+        /// Tickets minted for an event through `mint_batch`.
+        ticket_data: Mapping<u64, TicketData>,
+        /// Accounts besides the owner allowed to mint tickets and badges.
+        minters: Mapping<AccountId, ()>,
+        /// Per-event scopes of a shared collection, and the accounts allowed to mint and
+        /// manage tickets in just that scope.
+        collections: Mapping<u64, EventCollection>,
+        collection_minters: Mapping<(u64, AccountId), ()>,
+        /// Accounts besides the owner allowed to redeem, e.g. an event's door staff.
+        redeemers: Mapping<AccountId, ()>,
+        redeemed: Mapping<u64, ()>,
+        /// `(redeemed, outstanding)` tickets of each event, counting only live tickets
+        /// minted with `TicketData` as outstanding.
+        redemption_counts: Mapping<u64, (u64, u64)>,
+        /// Non-transferable attendance badges, as opposed to tickets.
+        badges: Mapping<u64, ()>,
+        /// The one account besides the owner approved to move each token.
+        token_approvals: Mapping<u64, AccountId>,
+        /// Operators approved for all of an owner's tokens, keyed by `(owner, operator)`.
+        operator_approvals: Mapping<(AccountId, AccountId), ()>,
+        /// Contract notified of transfers: the `EventManager` that instantiated us, if any.
+        observer: Option<AccountId>,
+        /// Resale cap in basis points of face value, set by the event host through the owner.
+        resale_cap_bps: Option<u16>,
+        /// Face value of each ticket tier, for the resale cap.
+        face_values: Vec<Balance>,
+        /// Who is owed `royalty_bps` of every resale, normally the event host.
+        royalty_beneficiary: Option<AccountId>,
+        royalty_bps: u16,
+        /// Per-token attributes, keyed by `(token_id, key)`.
+        attributes: Mapping<(u64, Vec<u8>), Vec<u8>>,
+        metadata_frozen: bool,
+        /// Soulbound collections keep every ticket with the account it was minted to.
+        transferable: bool,
+        /// How long after its event date a ticket stays locked from transfer.
+        transfer_lock: Option<u64>,
+        /// Until when each event's tickets can't change hands, e.g. while doors are open.
+        transfers_frozen_until: Mapping<u64, u64>,
+    }
+
+    /// Why a collection call failed. Shared with `EventManager`, which surfaces it as
+    /// `Error::MintFailed(reason)`.
+    #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// The caller may not mint on this collection.
+        Unauthorized,
+        /// The collection isn't accepting mints right now.
+        Paused,
+        Overflow,
+        TokenNotFound,
+        AlreadyRedeemed,
+        /// Royalties can't exceed the sale price.
+        InvalidRoyalty,
+        MetadataFrozen,
+        /// The ticket is past its `expires_at`.
+        Expired,
+        CollectionExists,
+        CollectionNotFound,
+        /// The event's collection has minted its `max_supply`.
+        SupplyCapReached,
+        /// The observer has banned an account the ticket would move from or to.
+        Banned,
+        /// The ticket's event has frozen transfers for now.
+        TransfersFrozen,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        #[ink(topic)]
+        id: Id,
+    }
+
+    /// Tokens moved by one `transfer_batch`, each as `(from, to, token_id)`.
+    #[ink(event)]
+    pub struct TransferBatch {
+        #[ink(topic)]
+        operator: AccountId,
+        transfers: Vec<(AccountId, AccountId, u64)>,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        operator: AccountId,
+        #[ink(topic)]
+        id: Option<Id>,
+        approved: bool,
+    }
+
+    #[ink(event)]
+    pub struct AttributeSet {
+        #[ink(topic)]
+        id: Id,
+        key: Vec<u8>,
+        data: Vec<u8>,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct PauseSet {
+        paused: bool,
+    }
+
+    #[ink(event)]
+    pub struct TicketBurned {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct TicketRecovered {
+        #[ink(topic)]
+        old_token_id: u64,
+        #[ink(topic)]
+        new_token_id: u64,
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TicketRedeemed {
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        redeemer: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct BadgeMinted {
+        #[ink(topic)]
+        recipient: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct TicketMinted {
+        #[ink(topic)]
+        recipient: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        token_uri: String,
+        tier: u32,
+    }
+
+    impl TicketNFT {
+        #[ink(constructor)]
+        pub fn new(name: String, symbol: String) -> Self {
+            let caller = Self::env().caller();
+            Self {
+                owner: caller,
+                pending_owner: None,
+                paused: false,
+                name,
+                symbol,
+                token_id_counter: 1,
+                tokens: Mapping::default(),
+                balances: Mapping::default(),
+                total_supply: 0,
+                owned_tokens: Mapping::default(),
+                owned_index: Mapping::default(),
+                all_tokens: Mapping::default(),
+                all_index: Mapping::default(),
+                token_uris: Mapping::default(),
+                token_tiers: Mapping::default(),
+                ticket_data: Mapping::default(),
+                minters: Mapping::default(),
+                collections: Mapping::default(),
+                collection_minters: Mapping::default(),
+                redeemers: Mapping::default(),
+                redeemed: Mapping::default(),
+                redemption_counts: Mapping::default(),
+                badges: Mapping::default(),
+                token_approvals: Mapping::default(),
+                operator_approvals: Mapping::default(),
+                observer: Self::env().is_contract(&caller).then_some(caller),
+                resale_cap_bps: None,
+                face_values: Vec::new(),
+                royalty_beneficiary: None,
+                royalty_bps: 0,
+                attributes: Mapping::default(),
+                metadata_frozen: false,
+                transferable: true,
+                transfer_lock: None,
+                transfers_frozen_until: Mapping::default(),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_observer(&self) -> Option<AccountId> {
+            self.observer
+        }
+
+        /// Points transfer and burn notifications at `observer`, or stops them with `None`.
+        #[ink(message)]
+        pub fn set_observer(&mut self, observer: Option<AccountId>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.observer = observer;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        #[ink(message)]
+        pub fn pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Nominates `new_owner`, who takes over once they call `accept_ownership`, so a
+        /// mistyped address can't strand the collection. The `EventManager` configures the
+        /// collection as its owner, so its events lose that once ownership moves.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.pending_owner = Some(new_owner);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+            let previous_owner = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+
+            self.env().emit_event(OwnershipTransferred { previous_owner, new_owner: caller });
+            Ok(())
+        }
+
+        /// Stops or resumes minting of tickets and badges. Holders can still transfer,
+        /// burn and redeem what they have.
+        #[ink(message)]
+        pub fn set_paused(&mut self, paused: bool) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.paused = paused;
+
+            self.env().emit_event(PauseSet { paused });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Tickets are addressed as `Id::U64`; anything else names no token.
+        fn token_id(id: &Id) -> Option<u64> {
+            match id {
+                Id::U64(token_id) => Some(*token_id),
+                _ => None,
+            }
+        }
+
+        /// Records a new `token_id` for `to`, keeping balances and supply in step.
+        fn mint_to(&mut self, to: AccountId, token_id: u64) -> Result<()> {
+            self.add_owned(to, token_id)?;
+            let index = self.total_supply;
+            self.total_supply = index.checked_add(1).ok_or(Error::Overflow)?;
+            self.all_tokens.insert(index, &token_id);
+            self.all_index.insert(token_id, &index);
+            self.tokens.insert(token_id, &to);
+
+            self.env().emit_event(Transfer { from: None, to: Some(to), id: Id::U64(token_id) });
+            Ok(())
+        }
+
+        fn move_token(&mut self, from: AccountId, to: AccountId, token_id: u64) -> Result<()> {
+            self.remove_owned(from, token_id);
+            self.add_owned(to, token_id)?;
+            self.tokens.insert(token_id, &to);
+            Ok(())
+        }
+
+        /// Drops a burnt token from the enumerations, filling its gaps with the last
+        /// entries like `remove_owned`.
+        fn remove_token(&mut self, owner: AccountId, token_id: u64) {
+            self.remove_owned(owner, token_id);
+            let last = self.total_supply.saturating_sub(1);
+            if let Some(index) = self.all_index.get(token_id) {
+                if index != last {
+                    if let Some(moved) = self.all_tokens.get(last) {
+                        self.all_tokens.insert(index, &moved);
+                        self.all_index.insert(moved, &index);
+                    }
+                }
+            }
+            self.all_tokens.remove(last);
+            self.all_index.remove(token_id);
+            self.total_supply = last;
+            self.tokens.remove(token_id);
+        }
+
+        fn add_owned(&mut self, owner: AccountId, token_id: u64) -> Result<()> {
+            let balance = self.balances.get(owner).unwrap_or(0);
+            self.balances.insert(owner, &balance.checked_add(1).ok_or(Error::Overflow)?);
+            self.owned_tokens.insert((owner, balance), &token_id);
+            self.owned_index.insert(token_id, &balance);
+            Ok(())
+        }
+
+        /// Takes `token_id` out of `owner`'s list, moving their last token into its place.
+        fn remove_owned(&mut self, owner: AccountId, token_id: u64) {
+            let last = self.balances.get(owner).unwrap_or(0).saturating_sub(1);
+            if let Some(index) = self.owned_index.get(token_id) {
+                if index != last {
+                    if let Some(moved) = self.owned_tokens.get((owner, last)) {
+                        self.owned_tokens.insert((owner, index), &moved);
+                        self.owned_index.insert(moved, &index);
+                    }
+                }
+            }
+            self.owned_tokens.remove((owner, last));
+            self.owned_index.remove(token_id);
+            self.balances.insert(owner, &last);
+        }
+
+        /// Page of `owner`'s token ids, at most `MAX_PAGE_SIZE` from position `offset`.
+        /// Order is stable except that a token leaving takes over from the last one.
+        #[ink(message)]
+        pub fn tokens_of_owner(&self, owner: AccountId, offset: u32, limit: u32) -> Vec<u64> {
+            let balance = self.balances.get(owner).unwrap_or(0);
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(balance);
+            (offset..end).filter_map(|index| self.owned_tokens.get((owner, index))).collect()
+        }
+
+        /// The token at position `index` among all `total_supply` tokens.
+        #[ink(message)]
+        pub fn token_by_index(&self, index: u64) -> Option<u64> {
+            self.all_tokens.get(index)
+        }
+
+        /// Authorizes `minter` besides the owner, normally the `EventManager`, to mint.
+        #[ink(message)]
+        pub fn add_minter(&mut self, minter: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.minters.insert(minter, &());
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_minter(&mut self, minter: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.minters.remove(minter);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_minter(&self, account: AccountId) -> bool {
+            account == self.owner || self.minters.contains(account)
+        }
+
+        /// Lets `minter` mint `event_id`'s tickets and set their attributes and seats, but
+        /// nothing outside that event's collection.
+        #[ink(message)]
+        pub fn add_collection_minter(&mut self, event_id: u64, minter: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            if !self.collections.contains(event_id) {
+                return Err(Error::CollectionNotFound);
+            }
+            self.collection_minters.insert((event_id, minter), &());
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_collection_minter(
+            &mut self,
+            event_id: u64,
+            minter: AccountId
+        ) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.collection_minters.remove((event_id, minter));
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_collection_minter(&self, event_id: u64, account: AccountId) -> bool {
+            self.is_minter(account) || self.collection_minters.contains((event_id, account))
+        }
+
+        #[ink(message)]
+        pub fn get_collection(&self, event_id: u64) -> Option<EventCollection> {
+            self.collections.get(event_id)
+        }
+
+        /// Mints the next token id to `recipient` once the caller has been authorized.
+        fn issue(&mut self, recipient: AccountId, token_uri: String, tier: u32) -> Result<u64> {
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            let token_id = self.token_id_counter;
+            self.token_id_counter = token_id.checked_add(1).ok_or(Error::Overflow)?;
+            self.mint_to(recipient, token_id)?;
+            self.token_uris.insert(token_id, &token_uri);
+            self.token_tiers.insert(token_id, &tier);
+
+            self.env().emit_event(TicketMinted {
+                recipient,
+                token_id,
+                token_uri,
+                tier,
+            });
+            Ok(token_id)
+        }
+
+        /// Whether the caller may change `token_id`'s metadata: any minter, or a minter of
+        /// the event collection the ticket belongs to.
+        fn can_manage(&self, token_id: u64) -> bool {
+            let caller = self.env().caller();
+            self.is_minter(caller) ||
+                self.ticket_data
+                    .get(token_id)
+                    .map_or(false, |data| self.collection_minters.contains((data.event_id, caller)))
+        }
+
+        #[ink(message)]
+        pub fn add_redeemer(&mut self, redeemer: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.redeemers.insert(redeemer, &());
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_redeemer(&mut self, redeemer: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.redeemers.remove(redeemer);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_redeemer(&self, account: AccountId) -> bool {
+            account == self.owner || self.redeemers.contains(account)
+        }
+
+        #[ink(message)]
+        pub fn get_token_tier(&self, token_id: u64) -> Option<u32> {
+            self.token_tiers.get(token_id)
+        }
+
+        #[ink(message)]
+        pub fn get_token_uri(&self, token_id: u64) -> Option<String> {
+            self.token_uris.get(token_id)
+        }
+
+        #[ink(message)]
+        pub fn get_ticket_data(&self, token_id: u64) -> Option<TicketData> {
+            self.ticket_data.get(token_id)
+        }
+
+        #[ink(message)]
+        pub fn name(&self) -> String {
+            self.name.clone()
+        }
+
+        #[ink(message)]
+        pub fn symbol(&self) -> String {
+            self.symbol.clone()
+        }
+
+        #[ink(message)]
+        pub fn is_metadata_frozen(&self) -> bool {
+            self.metadata_frozen
+        }
+
+        #[ink(message)]
+        pub fn is_transferable(&self) -> bool {
+            self.transferable
+        }
+
+        /// Whether `token_id` has to stay with its holder: badges always do, and every
+        /// token of a soulbound collection.
+        fn is_bound(&self, token_id: u64) -> bool {
+            !self.transferable || self.badges.contains(token_id)
+        }
+
+        /// Whether `token_id` exists and hasn't expired. Redeemed tickets stay valid, as
+        /// stubs of the event.
+        #[ink(message)]
+        pub fn is_valid(&self, token_id: u64) -> bool {
+            self.tokens.contains(token_id) && !self.is_expired(token_id)
+        }
+
+        fn is_expired(&self, token_id: u64) -> bool {
+            let expires_at = self.ticket_data.get(token_id).and_then(|data| data.expires_at);
+            expires_at.map_or(false, |expires_at| self.env().block_timestamp() > expires_at)
+        }
+
+        /// How many of `event_id`'s tickets have been redeemed and how many are still out,
+        /// for watching the door live. Burned tickets that were never used drop out.
+        #[ink(message)]
+        pub fn redemption_stats(&self, event_id: u64) -> (u64, u64) {
+            self.redemption_counts.get(event_id).unwrap_or((0, 0))
+        }
+
+        /// Verification code for a scanner app to put in `token_id`'s QR: the blake2x256 of
+        /// this collection's address, the ticket's event, its id and its holder. It changes
+        /// with the holder, so a ticket passed on invalidates the old code.
+        #[ink(message)]
+        pub fn ticket_code(&self, token_id: u64) -> Option<[u8; 32]> {
+            let owner = self.tokens.get(token_id)?;
+            let event_id = self.ticket_data.get(token_id)?.event_id;
+            let input = (self.env().account_id(), event_id, token_id, owner);
+            let mut output = [0u8; 32];
+            ink::env::hash_encoded::<ink::env::hash::Blake2x256, _>(&input, &mut output);
+            Some(output)
+        }
+
+        /// The valid ticket to `event_id` that `owner` holds and `code` was derived from,
+        /// if any. Whether it was already used is up to `is_redeemed`.
+        #[ink(message)]
+        pub fn verify_code(&self, event_id: u64, code: [u8; 32], owner: AccountId) -> Option<u64> {
+            let balance = self.balances.get(owner).unwrap_or(0);
+            let token_id = (0..balance)
+                .filter_map(|index| self.owned_tokens.get((owner, index)))
+                .find(|token_id| self.ticket_code(*token_id) == Some(code))?;
+            let data = self.ticket_data.get(token_id)?;
+            (data.event_id == event_id && self.is_valid(token_id)).then_some(token_id)
+        }
+
+        /// When `token_id` may change hands again, if it is locked at all.
+        #[ink(message)]
+        pub fn transfer_unlocks_at(&self, token_id: u64) -> Option<u64> {
+            let delay = self.transfer_lock?;
+            let data = self.ticket_data.get(token_id)?;
+            Some(data.event_date.saturating_add(delay))
+        }
+
+        /// Freezes transfers of `event_id`'s tickets until `frozen_until`, or lifts the
+        /// freeze with `None`. Minting and redemption carry on as usual.
+        #[ink(message)]
+        pub fn set_transfer_freeze(
+            &mut self,
+            event_id: u64,
+            frozen_until: Option<u64>
+        ) -> Result<()> {
+            if !self.is_collection_minter(event_id, self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            match frozen_until {
+                Some(frozen_until) => self.transfers_frozen_until.insert(event_id, &frozen_until),
+                None => self.transfers_frozen_until.remove(event_id),
+            };
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn transfers_frozen_until(&self, event_id: u64) -> Option<u64> {
+            self.transfers_frozen_until.get(event_id)
+        }
+
+        fn ensure_not_frozen(&self, token_id: u64) -> Result<()> {
+            let Some(data) = self.ticket_data.get(token_id) else { return Ok(()) };
+            let frozen_until = self.transfers_frozen_until.get(data.event_id);
+            if frozen_until.map_or(false, |until| self.env().block_timestamp() < until) {
+                return Err(Error::TransfersFrozen);
+            }
+            Ok(())
+        }
+
+        /// Approves or revokes `operator` for all of the caller's tokens; shorthand for
+        /// `PSP34::approve` without a token id. Single tokens go through `PSP34::approve`.
+        #[ink(message)]
+        pub fn set_approval_for_all(
+            &mut self,
+            operator: AccountId,
+            approved: bool
+        ) -> core::result::Result<(), PSP34Error> {
+            PSP34::approve(self, operator, None, approved)
+        }
+
+        /// `PSP34::transfer` that, when `to` is a contract, delivers only with the consent
+        /// of its `PSP34Receiver` hook. Accounts receive as with a plain transfer.
+        #[ink(message)]
+        pub fn safe_transfer(
+            &mut self,
+            to: AccountId,
+            id: Id,
+            data: Vec<u8>
+        ) -> core::result::Result<(), PSP34Error> {
+            let from = PSP34::owner_of(self, id.clone()).ok_or(PSP34Error::TokenNotExists)?;
+            PSP34::transfer(self, to, id.clone(), data.clone())?;
+            if !self.env().is_contract(&to) {
+                return Ok(());
+            }
+
+            // An `Err` reverts the transfer above along with the rest of the call
+            let operator = self.env().caller();
+            let mut receiver: ink::contract_ref!(PSP34Receiver) = to.into();
+            let accepted = ink::codegen::TraitCallBuilder::call_mut(&mut receiver)
+                .before_received(operator, from, id, data)
+                .try_invoke();
+            let reason = match accepted {
+                Ok(Ok(Ok(()))) => {
+                    return Ok(());
+                }
+                Ok(Ok(Err(PSP34Error::SafeTransferCheckFailed(reason)))) => reason,
+                Ok(Ok(Err(_))) => String::from("Rejected"),
+                // No hook to call, or it trapped
+                _ => String::from("NotReceiver"),
+            };
+            Err(PSP34Error::SafeTransferCheckFailed(reason))
+        }
+
+        /// Moves token `id` to `to` for the caller after every check a transfer is subject
+        /// to, and returns who held it. Callers emit the `Transfer` event.
+        fn move_checked(
+            &mut self,
+            to: AccountId,
+            id: Id,
+            data: Vec<u8>
+        ) -> core::result::Result<AccountId, PSP34Error> {
+            let caller = self.env().caller();
+            let token_id = Self::token_id(&id).ok_or(PSP34Error::TokenNotExists)?;
+            let from = self.tokens.get(token_id).ok_or(PSP34Error::TokenNotExists)?;
+            if caller != from && !self.allowance(from, caller, Some(id.clone())) {
+                return Err(PSP34Error::NotApproved);
+            }
+            // Proof of attendance stays with whoever attended, as do soulbound tickets
+            if self.is_bound(token_id) {
+                return Err(PSP34Error::Custom(String::from("NonTransferable")));
+            }
+            let unlocks_at = self.transfer_unlocks_at(token_id);
+            if unlocks_at.map_or(false, |unlocks_at| self.env().block_timestamp() < unlocks_at) {
+                return Err(PSP34Error::Custom(String::from("TransferLocked")));
+            }
+            self.ensure_not_frozen(token_id)
+                .map_err(|_| PSP34Error::Custom(String::from("TransfersFrozen")))?;
+            let max_price = self.max_resale_price(token_id).filter(|_| !data.is_empty());
+            if let Some(max_price) = max_price {
+                let price = <Balance as ink::scale::Decode>::decode(&mut &data[..])
+                    .map_err(|_| PSP34Error::Custom(String::from("InvalidPrice")))?;
+                if price > max_price {
+                    return Err(PSP34Error::Custom(String::from("ResalePriceExceeded")));
+                }
+            }
+            self.move_token(from, to, token_id)
+                .map_err(|_| PSP34Error::Custom(String::from("Overflow")))?;
+            self.token_approvals.remove(token_id);
+
+            // An `Err` reverts the transfer above along with the rest of the call
+            if let Some(observer) = self.observer {
+                let mut observer: ink::contract_ref!(TicketObserver) = observer.into();
+                // Banned accounts are told so, e.g. when the marketplace moves a ticket
+                observer.on_ticket_transfer(from, to, token_id).map_err(|error| {
+                    let reason = match error {
+                        Error::Banned => "Banned",
+                        _ => "ObserverRejected",
+                    };
+                    PSP34Error::Custom(String::from(reason))
+                })?;
+            }
+            Ok(from)
+        }
+
+        /// Moves several tokens, each `(to, token_id)`, in one call: all of them or, since
+        /// an `Err` reverts the call, none. Emits a single `TransferBatch` rather than a
+        /// `Transfer` per token.
+        #[ink(message)]
+        pub fn transfer_batch(
+            &mut self,
+            recipients: Vec<(AccountId, u64)>
+        ) -> core::result::Result<(), PSP34Error> {
+            if recipients.len() > MAX_PAGE_SIZE as usize {
+                return Err(PSP34Error::Custom(String::from("BatchTooLarge")));
+            }
+            let mut transfers = Vec::new();
+            for (to, token_id) in recipients.iter().copied() {
+                let from = self.move_checked(to, Id::U64(token_id), Vec::new())?;
+                transfers.push((from, to, token_id));
+            }
+
+            self.env().emit_event(TransferBatch { operator: self.env().caller(), transfers });
+            Ok(())
+        }
+
+        /// The one account approved to move `token_id` besides its owner's operators.
+        #[ink(message)]
+        pub fn get_approved(&self, token_id: u64) -> Option<AccountId> {
+            self.token_approvals.get(token_id)
+        }
+
+        #[ink(message)]
+        pub fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
+            self.operator_approvals.contains((owner, operator))
+        }
+    }
+
+    impl TicketMinter for TicketNFT {
+        #[ink(message)]
+        fn mint(&mut self, recipient: AccountId, token_uri: String, tier: u32) -> Result<u64> {
+            if !self.is_minter(self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            self.issue(recipient, token_uri, tier)
+        }
+
+        #[ink(message)]
+        fn mint_batch(
+            &mut self,
+            recipient: AccountId,
+            token_uris: Vec<String>,
+            data: TicketData
+        ) -> Result<Vec<u64>> {
+            if !self.is_collection_minter(data.event_id, self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            // Events without a scope of their own mint straight into the collection
+            if let Some(mut collection) = self.collections.get(data.event_id) {
+                let minted = collection.minted
+                    .checked_add(token_uris.len() as u64)
+                    .ok_or(Error::Overflow)?;
+                if collection.max_supply.map_or(false, |max_supply| minted > max_supply) {
+                    return Err(Error::SupplyCapReached);
+                }
+                collection.minted = minted;
+                self.collections.insert(data.event_id, &collection);
+            }
+
+            let (redeemed, outstanding) = self.redemption_stats(data.event_id);
+            let outstanding = outstanding
+                .checked_add(token_uris.len() as u64)
+                .ok_or(Error::Overflow)?;
+            self.redemption_counts.insert(data.event_id, &(redeemed, outstanding));
+
+            let data = TicketData { issued_at: self.env().block_timestamp(), ..data };
+            // A failed mint reverts the whole batch
+            let mut token_ids = Vec::new();
+            for token_uri in token_uris {
+                let token_id = self.issue(recipient, token_uri, data.tier)?;
+                self.ticket_data.insert(token_id, &data);
+                token_ids.push(token_id);
+            }
+            Ok(token_ids)
+        }
+
+        #[ink(message)]
+        fn owner_of(&self, token_id: u64) -> Option<AccountId> {
+            self.tokens.get(token_id)
+        }
+
+        #[ink(message)]
+        fn burn(&mut self, token_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+            let owner = self.tokens.get(token_id).ok_or(Error::TokenNotFound)?;
+            let by_holder = caller == owner ||
+                self.allowance(owner, caller, Some(Id::U64(token_id)));
+            if caller != self.owner && !by_holder {
+                return Err(Error::Unauthorized);
+            }
+            if let Some(data) = self.ticket_data.get(token_id) {
+                if !self.redeemed.contains(token_id) {
+                    let (redeemed, outstanding) = self.redemption_stats(data.event_id);
+                    let counts = (redeemed, outstanding.saturating_sub(1));
+                    self.redemption_counts.insert(data.event_id, &counts);
+                }
+            }
+            self.remove_token(owner, token_id);
+            self.token_uris.remove(token_id);
+            self.token_tiers.remove(token_id);
+            self.ticket_data.remove(token_id);
+            self.token_approvals.remove(token_id);
+
+            // An `Err` reverts the burn above along with the rest of the call
+            if let Some(observer) = self.observer.filter(|_| caller != self.owner) {
+                let mut observer: ink::contract_ref!(TicketObserver) = observer.into();
+                observer.on_ticket_burn(owner, token_id)?;
+            }
+
+            self.env().emit_event(Transfer { from: Some(owner), to: None, id: Id::U64(token_id) });
+            self.env().emit_event(TicketBurned { owner, token_id });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn redeem(&mut self, token_id: u64) -> Result<()> {
+            let redeemer = self.env().caller();
+            if !self.is_redeemer(redeemer) {
+                return Err(Error::Unauthorized);
+            }
+            if !self.tokens.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            if self.redeemed.contains(token_id) {
+                return Err(Error::AlreadyRedeemed);
+            }
+            if self.is_expired(token_id) {
+                return Err(Error::Expired);
+            }
+            self.redeemed.insert(token_id, &());
+            if let Some(data) = self.ticket_data.get(token_id) {
+                let (redeemed, outstanding) = self.redemption_stats(data.event_id);
+                let counts = (redeemed.saturating_add(1), outstanding.saturating_sub(1));
+                self.redemption_counts.insert(data.event_id, &counts);
+            }
+
+            self.env().emit_event(TicketRedeemed { token_id, redeemer });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn is_redeemed(&self, token_id: u64) -> bool {
+            self.redeemed.contains(token_id)
+        }
+
+        #[ink(message)]
+        fn mint_badge(&mut self, recipient: AccountId) -> Result<u64> {
+            if !self.is_minter(self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            let token_id = self.token_id_counter;
+            self.token_id_counter = token_id.checked_add(1).ok_or(Error::Overflow)?;
+            self.mint_to(recipient, token_id)?;
+            self.badges.insert(token_id, &());
+
+            self.env().emit_event(BadgeMinted { recipient, token_id });
+            Ok(token_id)
+        }
+
+        #[ink(message)]
+        fn is_badge(&self, token_id: u64) -> bool {
+            self.badges.contains(token_id)
+        }
+
+        #[ink(message)]
+        fn set_resale_cap(
+            &mut self,
+            cap_bps: Option<u16>,
+            face_values: Vec<Balance>
+        ) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.resale_cap_bps = cap_bps;
+            self.face_values = face_values;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_royalty(&mut self, beneficiary: AccountId, royalty_bps: u16) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            if royalty_bps > MAX_BPS {
+                return Err(Error::InvalidRoyalty);
+            }
+            self.royalty_beneficiary = (royalty_bps > 0).then_some(beneficiary);
+            self.royalty_bps = royalty_bps;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_attribute(&mut self, token_id: u64, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
+            if !self.can_manage(token_id) {
+                return Err(Error::Unauthorized);
+            }
+            if self.metadata_frozen {
+                return Err(Error::MetadataFrozen);
+            }
+            if !self.tokens.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            self.attributes.insert((token_id, key.clone()), &value);
+
+            self.env().emit_event(AttributeSet { id: Id::U64(token_id), key, data: value });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn freeze_metadata(&mut self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.metadata_frozen = true;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_transferable(&mut self, transferable: bool) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.transferable = transferable;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_transfer_lock(&mut self, delay: Option<u64>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            self.transfer_lock = delay;
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn create_collection(
+            &mut self,
+            event_id: u64,
+            name: String,
+            max_supply: Option<u64>
+        ) -> Result<()> {
+            if !self.is_minter(self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            if self.collections.contains(event_id) {
+                return Err(Error::CollectionExists);
+            }
+            self.collections.insert(event_id, &EventCollection { name, max_supply, minted: 0 });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn assign_seat(&mut self, token_id: u64, seat: Option<u32>) -> Result<()> {
+            if !self.can_manage(token_id) {
+                return Err(Error::Unauthorized);
+            }
+            if self.metadata_frozen {
+                return Err(Error::MetadataFrozen);
+            }
+            let mut data = self.ticket_data.get(token_id).ok_or(Error::TokenNotFound)?;
+            data.seat = seat;
+            self.ticket_data.insert(token_id, &data);
+            Ok(())
+        }
+
+        /// Per-token attributes stay behind with the burnt id; they can't be enumerated.
+        /// The observer isn't told, so recovery is left to the owner, which is the manager
+        /// keeping the records and moves them itself. Minters would leave them stale.
+        #[ink(message)]
+        fn recover_ticket(&mut self, old_token_id: u64, new_owner: AccountId) -> Result<u64> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            let owner = self.tokens.get(old_token_id).ok_or(Error::TokenNotFound)?;
+            if self.redeemed.contains(old_token_id) {
+                return Err(Error::AlreadyRedeemed);
+            }
+            let token_uri = self.token_uris.take(old_token_id).unwrap_or_default();
+            let tier = self.token_tiers.take(old_token_id).unwrap_or_default();
+            let data = self.ticket_data.take(old_token_id);
+            self.remove_token(owner, old_token_id);
+            self.token_approvals.remove(old_token_id);
+            self.env().emit_event(Transfer {
+                from: Some(owner),
+                to: None,
+                id: Id::U64(old_token_id),
+            });
+
+            // One ticket out and one in, so the redemption counts stand
+            let new_token_id = self.issue(new_owner, token_uri, tier)?;
+            if let Some(data) = data {
+                self.ticket_data.insert(new_token_id, &data);
+            }
+            self.env().emit_event(TicketRecovered { old_token_id, new_token_id, new_owner });
+            Ok(new_token_id)
+        }
+
+        #[ink(message)]
+        fn adopt_collection(&mut self) -> Result<()> {
+            self.accept_ownership()?;
+            self.observer = Some(self.env().caller());
+            Ok(())
+        }
+    }
+
+    impl PSP34Metadata for TicketNFT {
+        #[ink(message)]
+        fn get_attribute(&self, id: Id, key: Vec<u8>) -> Option<Vec<u8>> {
+            if id == self.collection_id() {
+                return match key.as_slice() {
+                    b"name" => Some(self.name.clone().into_bytes()),
+                    b"symbol" => Some(self.symbol.clone().into_bytes()),
+                    _ => None,
+                };
+            }
+            let token_id = Self::token_id(&id)?;
+            self.attributes.get((token_id, key))
+        }
+    }
+
+    impl InterfaceDetection for TicketNFT {
+        #[ink(message)]
+        fn supports_interface(&self, interface_id: u32) -> bool {
+            matches!(
+                interface_id,
+                INTERFACE_DETECTION_ID |
+                    PSP34_INTERFACE_ID |
+                    PSP34_METADATA_INTERFACE_ID |
+                    PSP34_ROYALTY_INTERFACE_ID |
+                    TICKET_MINTER_INTERFACE_ID
+            )
+        }
+    }
+
+    impl PSP34Royalty for TicketNFT {
+        #[ink(message)]
+        fn royalty_info(&self, id: Id, sale_price: Balance) -> Option<(AccountId, Balance)> {
+            let beneficiary = self.royalty_beneficiary?;
+            // Badges are never sold, so only tickets carry a royalty
+            let token_id = Self::token_id(&id).filter(|id| !self.badges.contains(id))?;
+            self.tokens.get(token_id)?;
+            let royalty = sale_price.saturating_mul(self.royalty_bps as Balance);
+            Some((beneficiary, royalty / MAX_BPS as Balance))
+        }
+    }
+
+    impl TicketResale for TicketNFT {
+        #[ink(message)]
+        fn max_resale_price(&self, token_id: u64) -> Option<Balance> {
+            let cap_bps = self.resale_cap_bps?;
+            let tier = self.token_tiers.get(token_id).unwrap_or(u32::MAX);
+            let face_value = self.face_values.get(tier as usize).copied().unwrap_or(0);
+            Some(face_value.saturating_mul(cap_bps as Balance) / MAX_BPS as Balance)
+        }
+
+        #[ink(message)]
+        fn is_banned_holder(&self, token_id: u64, account: AccountId) -> bool {
+            let Some(observer) = self.observer else { return false };
+            let observer: ink::contract_ref!(TicketObserver) = observer.into();
+            observer.is_banned_holder(token_id, account)
+        }
+    }
+
+    impl PSP34 for TicketNFT {
+        #[ink(message)]
+        fn collection_id(&self) -> Id {
+            let account_id = self.env().account_id();
+            Id::Bytes(<AccountId as AsRef<[u8]>>::as_ref(&account_id).to_vec())
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u32 {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        fn owner_of(&self, id: Id) -> Option<AccountId> {
+            Self::token_id(&id).and_then(|token_id| self.tokens.get(token_id))
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool {
+            if self.operator_approvals.contains((owner, operator)) {
+                return true;
+            }
+            id.as_ref().and_then(Self::token_id).map_or(false, |token_id| {
+                self.tokens.get(token_id) == Some(owner) &&
+                    self.token_approvals.get(token_id) == Some(operator)
+            })
+        }
+
+        #[ink(message)]
+        fn approve(
+            &mut self,
+            operator: AccountId,
+            id: Option<Id>,
+            approved: bool
+        ) -> core::result::Result<(), PSP34Error> {
+            let caller = self.env().caller();
+            if operator == caller {
+                return Err(PSP34Error::SelfApprove);
+            }
+            let mut owner = caller;
+            match id.as_ref() {
+                Some(id) => {
+                    let token_id = Self::token_id(id).ok_or(PSP34Error::TokenNotExists)?;
+                    owner = self.tokens.get(token_id).ok_or(PSP34Error::TokenNotExists)?;
+                    if owner != caller && !self.operator_approvals.contains((owner, caller)) {
+                        return Err(PSP34Error::NotApproved);
+                    }
+                    if approved && self.is_bound(token_id) {
+                        return Err(PSP34Error::Custom(String::from("NonTransferable")));
+                    }
+                    if approved {
+                        self.token_approvals.insert(token_id, &operator);
+                    } else {
+                        self.token_approvals.remove(token_id);
+                    }
+                }
+                None if approved && !self.transferable => {
+                    return Err(PSP34Error::Custom(String::from("NonTransferable")));
+                }
+                None if approved => {
+                    self.operator_approvals.insert((caller, operator), &());
+                }
+                None => {
+                    self.operator_approvals.remove((caller, operator));
+                }
+            }
+
+            self.env().emit_event(Approval { owner, operator, id, approved });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn transfer(
+            &mut self,
+            to: AccountId,
+            id: Id,
+            data: Vec<u8>
+        ) -> core::result::Result<(), PSP34Error> {
+            let from = self.move_checked(to, id.clone(), data)?;
+            self.env().emit_event(Transfer { from: Some(from), to: Some(to), id });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn total_supply(&self) -> u128 {
+            self.total_supply as u128
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn ticket(event_id: u64, tier: u32, event_date: u64) -> TicketData {
+            TicketData {
+                event_id,
+                tier,
+                seat: None,
+                issued_at: 0,
+                event_date,
+                expires_at: None,
+            }
+        }
+
+        #[ink::test]
+        fn test_mint_ticket() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let recipient = AccountId::from([0x1; 32]);
+            let token_uri = "https://example.com/nft/1".to_string();
+            let token_id = nft_contract.mint(recipient, token_uri.clone(), 1).unwrap();
+            assert_eq!(TicketMinter::owner_of(&nft_contract, token_id), Some(recipient));
+            assert_eq!(nft_contract.get_token_uri(token_id), Some(token_uri));
+            assert_eq!(nft_contract.get_token_tier(token_id), Some(1));
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 2); // Transfer, TicketMinted
+
+            // Only the collection owner mints
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                nft_contract.mint(recipient, token_uri.clone(), 1),
+                Err(Error::Unauthorized)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+
+            // The counter refuses to wrap around
+            nft_contract.token_id_counter = u64::MAX;
+            assert_eq!(
+                nft_contract.mint(recipient, token_uri, 1),
+                Err(Error::Overflow)
+            );
+        }
+
+        #[ink::test]
+        fn test_mint_batch() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let recipient = AccountId::from([0x1; 32]);
+            let uris = vec!["TicketURI".to_string(), "TicketURI".to_string()];
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            let token_ids = nft_contract.mint_batch(recipient, uris, ticket(7, 2, 5_000)).unwrap();
+            assert_eq!(token_ids, vec![1, 2]);
+            assert_eq!(nft_contract.balance_of(recipient), 2);
+            assert_eq!(nft_contract.get_token_tier(2), Some(2));
+            let data = TicketData { issued_at: 1_000, ..ticket(7, 2, 5_000) };
+            assert_eq!(nft_contract.get_ticket_data(2), Some(data.clone()));
+
+            assert_eq!(nft_contract.assign_seat(2, Some(12)), Ok(()));
+            let seated = TicketData { seat: Some(12), ..data };
+            assert_eq!(nft_contract.get_ticket_data(2), Some(seated));
+            assert_eq!(nft_contract.assign_seat(3, Some(12)), Err(Error::TokenNotFound));
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 4); // Transfer, TicketMinted per token
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let uris = vec!["TicketURI".to_string()];
+            let forged = nft_contract.mint_batch(recipient, uris, ticket(7, 0, 0));
+            assert_eq!(forged, Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_burn() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let recipient = AccountId::from([0x1; 32]);
+            let token_id = nft_contract.mint(recipient, "TicketURI".to_string(), 0).unwrap();
+
+            assert_eq!(nft_contract.burn(token_id), Ok(()));
+            assert_eq!(TicketMinter::owner_of(&nft_contract, token_id), None);
+            assert_eq!(nft_contract.get_token_uri(token_id), None);
+            assert_eq!(nft_contract.burn(token_id), Err(Error::TokenNotFound));
+
+            // Holders and their operators may burn too, but nobody else
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(nft_contract.burn(token_id), Err(Error::Unauthorized));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.set_approval_for_all(accounts.charlie, true), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(nft_contract.burn(token_id), Ok(()));
+            assert_eq!(nft_contract.balance_of(accounts.bob), 0);
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            // Transfer and TicketMinted per mint, Transfer and TicketBurned per burn, Approval
+            assert_eq!(emitted_events.len(), 9);
+        }
+
+        #[ink::test]
+        fn test_redeem() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let recipient = AccountId::from([0x1; 32]);
+            let token_id = nft_contract.mint(recipient, "TicketURI".to_string(), 0).unwrap();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Door staff need the owner's blessing
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.redeem(token_id), Err(Error::Unauthorized));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.add_redeemer(accounts.bob), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(!nft_contract.is_redeemed(token_id));
+            assert_eq!(nft_contract.redeem(token_id), Ok(()));
+            assert!(nft_contract.is_redeemed(token_id));
+            assert_eq!(nft_contract.redeem(token_id), Err(Error::AlreadyRedeemed));
+            assert_eq!(nft_contract.redeem(token_id + 1), Err(Error::TokenNotFound));
+        }
+
+        #[ink::test]
+        fn test_transfer() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            let id = Id::U64(token_id);
+            assert_eq!(nft_contract.get_observer(), None);
+
+            // Strangers can't move it until the holder approves them
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                nft_contract.transfer(accounts.charlie, id.clone(), vec![]),
+                Err(PSP34Error::NotApproved)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                nft_contract.approve(accounts.bob, Some(id.clone()), true),
+                Err(PSP34Error::SelfApprove)
+            );
+            assert_eq!(nft_contract.approve(accounts.charlie, Some(id.clone()), true), Ok(()));
+            assert!(nft_contract.allowance(accounts.bob, accounts.charlie, Some(id.clone())));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(nft_contract.transfer(accounts.django, id.clone(), vec![]), Ok(()));
+            assert_eq!(PSP34::owner_of(&nft_contract, id.clone()), Some(accounts.django));
+            // The approval doesn't survive the transfer
+            assert!(!nft_contract.allowance(accounts.django, accounts.charlie, Some(id.clone())));
+            assert_eq!(
+                nft_contract.transfer(accounts.charlie, id.clone(), vec![]),
+                Err(PSP34Error::NotApproved)
+            );
+
+            // Operators approved for everything can move any of the holder's tokens
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(nft_contract.approve(accounts.eve, None, true), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(nft_contract.transfer(accounts.bob, id, vec![]), Ok(()));
+            assert_eq!(
+                nft_contract.transfer(accounts.bob, Id::U8(1), vec![]),
+                Err(PSP34Error::TokenNotExists)
+            );
+        }
+
+        #[ink::test]
+        fn test_approvals() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let approved = nft_contract.approve(accounts.charlie, Some(Id::U64(token_id)), true);
+            assert_eq!(approved, Ok(()));
+            assert_eq!(nft_contract.get_approved(token_id), Some(accounts.charlie));
+            assert_eq!(nft_contract.set_approval_for_all(accounts.django, true), Ok(()));
+            assert!(nft_contract.is_approved_for_all(accounts.bob, accounts.django));
+            assert_eq!(
+                nft_contract.set_approval_for_all(accounts.bob, true),
+                Err(PSP34Error::SelfApprove)
+            );
+
+            // An operator can move the token, which clears the single-token approval
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(nft_contract.transfer(accounts.eve, Id::U64(token_id), vec![]), Ok(()));
+            assert_eq!(nft_contract.get_approved(token_id), None);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.set_approval_for_all(accounts.django, false), Ok(()));
+            assert!(!nft_contract.is_approved_for_all(accounts.bob, accounts.django));
+        }
+
+        #[ink::test]
+        fn test_balances_and_supply() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let ticket = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            nft_contract.mint_badge(accounts.charlie).unwrap();
+            assert_eq!(nft_contract.balance_of(accounts.bob), 2);
+            assert_eq!(nft_contract.balance_of(accounts.charlie), 1);
+            assert_eq!(nft_contract.total_supply(), 3);
+            // Mints show up as transfers from nobody
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 6); // Transfer, TicketMinted or BadgeMinted each
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.transfer(accounts.charlie, Id::U64(ticket), vec![]), Ok(()));
+            assert_eq!(nft_contract.balance_of(accounts.bob), 1);
+            assert_eq!(nft_contract.balance_of(accounts.charlie), 2);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.burn(ticket), Ok(()));
+            assert_eq!(nft_contract.balance_of(accounts.charlie), 1);
+            assert_eq!(nft_contract.total_supply(), 2);
+            let account_id = nft_contract.env().account_id();
+            assert_eq!(nft_contract.collection_id(), Id::Bytes(account_id.as_ref().to_vec()));
+        }
+
+        #[ink::test]
+        fn test_enumeration() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string(); 3];
+            let minted = nft_contract.mint_batch(accounts.bob, uris, ticket(1, 0, 0));
+            assert_eq!(minted, Ok(vec![1, 2, 3]));
+            assert_eq!(nft_contract.tokens_of_owner(accounts.bob, 0, 10), vec![1, 2, 3]);
+            assert_eq!(nft_contract.tokens_of_owner(accounts.bob, 1, 1), vec![2]);
+
+            // Bob's last token fills the gap the transferred one leaves
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.transfer(accounts.charlie, Id::U64(1), vec![]), Ok(()));
+            assert_eq!(nft_contract.tokens_of_owner(accounts.bob, 0, 10), vec![3, 2]);
+            assert_eq!(nft_contract.tokens_of_owner(accounts.charlie, 0, 10), vec![1]);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.burn(2), Ok(()));
+            assert_eq!(nft_contract.tokens_of_owner(accounts.bob, 0, 10), vec![3]);
+            assert_eq!(nft_contract.token_by_index(0), Some(1));
+            assert_eq!(nft_contract.token_by_index(1), Some(3));
+            assert_eq!(nft_contract.token_by_index(2), None);
+        }
+
+        #[ink::test]
+        fn test_metadata() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            assert_eq!(nft_contract.name(), "BlockPassNFT");
+            let collection = nft_contract.collection_id();
+            let symbol = nft_contract.get_attribute(collection, b"symbol".to_vec());
+            assert_eq!(symbol, Some(b"BPNT".to_vec()));
+
+            let seat = nft_contract.set_attribute(token_id, b"seat".to_vec(), b"A12".to_vec());
+            assert_eq!(seat, Ok(()));
+            let seat = nft_contract.get_attribute(Id::U64(token_id), b"seat".to_vec());
+            assert_eq!(seat, Some(b"A12".to_vec()));
+            let missing = nft_contract.set_attribute(token_id + 1, b"seat".to_vec(), vec![]);
+            assert_eq!(missing, Err(Error::TokenNotFound));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let forged = nft_contract.set_attribute(token_id, b"seat".to_vec(), b"VIP".to_vec());
+            assert_eq!(forged, Err(Error::Unauthorized));
+            assert_eq!(nft_contract.freeze_metadata(), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.freeze_metadata(), Ok(()));
+            assert!(nft_contract.is_metadata_frozen());
+            let frozen = nft_contract.set_attribute(token_id, b"seat".to_vec(), b"B1".to_vec());
+            assert_eq!(frozen, Err(Error::MetadataFrozen));
+        }
+
+        #[ink::test]
+        fn test_resale_cap() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 1).unwrap();
+            let comp_id = nft_contract.mint(accounts.bob, String::new(), u32::MAX).unwrap();
+            assert_eq!(nft_contract.max_resale_price(token_id), None);
+
+            // 110% of the tier's face value; comps belong to no tier
+            assert_eq!(nft_contract.set_resale_cap(Some(11_000), vec![500, 1_000]), Ok(()));
+            assert_eq!(nft_contract.max_resale_price(token_id), Some(1_100));
+            assert_eq!(nft_contract.max_resale_price(comp_id), Some(0));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.set_resale_cap(None, vec![]), Err(Error::Unauthorized));
+            let price = |price: Balance| ink::scale::Encode::encode(&price);
+            assert_eq!(
+                nft_contract.transfer(accounts.charlie, Id::U64(token_id), price(1_101)),
+                Err(PSP34Error::Custom("ResalePriceExceeded".to_string()))
+            );
+            let result = nft_contract.transfer(accounts.charlie, Id::U64(token_id), price(1_100));
+            assert_eq!(result, Ok(()));
+        }
+
+        #[ink::test]
+        fn test_royalty_info() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            assert_eq!(nft_contract.royalty_info(Id::U64(token_id), 1_000), None);
+
+            let too_high = nft_contract.set_royalty(accounts.alice, 10_001);
+            assert_eq!(too_high, Err(Error::InvalidRoyalty));
+            assert_eq!(nft_contract.set_royalty(accounts.alice, 500), Ok(()));
+            let royalty = nft_contract.royalty_info(Id::U64(token_id), 1_000);
+            assert_eq!(royalty, Some((accounts.alice, 50)));
+            assert_eq!(nft_contract.royalty_info(Id::U64(token_id + 1), 1_000), None);
+
+            assert_eq!(nft_contract.set_royalty(accounts.alice, 0), Ok(()));
+            assert_eq!(nft_contract.royalty_info(Id::U64(token_id), 1_000), None);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.set_royalty(accounts.bob, 500), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_badges_are_not_transferable() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let badge_id = nft_contract.mint_badge(accounts.bob).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let non_transferable = Err(PSP34Error::Custom("NonTransferable".to_string()));
+            assert_eq!(
+                nft_contract.transfer(accounts.charlie, Id::U64(badge_id), vec![]),
+                non_transferable
+            );
+            assert_eq!(
+                nft_contract.approve(accounts.charlie, Some(Id::U64(badge_id)), true),
+                non_transferable
+            );
+        }
+
+        #[ink::test]
+        fn test_soulbound() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            assert_eq!(nft_contract.set_transferable(false), Ok(()));
+            assert!(!nft_contract.is_transferable());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.set_transferable(true), Err(Error::Unauthorized));
+            let non_transferable = Err(PSP34Error::Custom("NonTransferable".to_string()));
+            assert_eq!(
+                nft_contract.transfer(accounts.charlie, Id::U64(token_id), vec![]),
+                non_transferable
+            );
+            assert_eq!(nft_contract.set_approval_for_all(accounts.charlie, true), non_transferable);
+
+            // Redemption and burning still work
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.redeem(token_id), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.burn(token_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_transfer_lock() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string()];
+            let minted = nft_contract.mint_batch(accounts.bob, uris, ticket(1, 0, 10_000));
+            let token_id = minted.unwrap()[0];
+            assert_eq!(nft_contract.set_transfer_lock(Some(3_600_000)), Ok(()));
+            assert_eq!(nft_contract.transfer_unlocks_at(token_id), Some(3_610_000));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.set_transfer_lock(None), Err(Error::Unauthorized));
+            assert_eq!(
+                nft_contract.transfer(accounts.charlie, Id::U64(token_id), vec![]),
+                Err(PSP34Error::Custom("TransferLocked".to_string()))
+            );
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(3_610_000);
+            assert_eq!(nft_contract.transfer(accounts.charlie, Id::U64(token_id), vec![]), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_transfer_freeze() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string(); 2];
+            let token_ids = nft_contract.mint_batch(accounts.bob, uris, ticket(1, 0, 10_000));
+            let token_ids = token_ids.unwrap();
+            let other = nft_contract.mint_batch(accounts.bob, vec![String::new()], ticket(2, 0, 0));
+            let other = other.unwrap()[0];
+            assert_eq!(nft_contract.set_transfer_freeze(1, Some(12_000)), Ok(()));
+            assert_eq!(nft_contract.transfers_frozen_until(1), Some(12_000));
+
+            // Doors open: event 1's tickets stay put, but can still be minted and redeemed
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.set_transfer_freeze(1, None), Err(Error::Unauthorized));
+            assert_eq!(
+                nft_contract.transfer(accounts.charlie, Id::U64(token_ids[0]), vec![]),
+                Err(PSP34Error::Custom("TransfersFrozen".to_string()))
+            );
+            assert_eq!(nft_contract.transfer(accounts.charlie, Id::U64(other), vec![]), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let uris = vec![String::new()];
+            assert!(nft_contract.mint_batch(accounts.bob, uris, ticket(1, 0, 0)).is_ok());
+            assert_eq!(nft_contract.redeem(token_ids[1]), Ok(()));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(12_000);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = nft_contract.transfer(accounts.charlie, Id::U64(token_ids[0]), vec![]);
+            assert_eq!(result, Ok(()));
+        }
+
+        #[ink::test]
+        fn test_expiry() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let uris = vec!["TicketURI".to_string(); 2];
+            let data = TicketData { expires_at: Some(20_000), ..ticket(1, 0, 10_000) };
+            let token_ids = nft_contract.mint_batch(AccountId::from([0x1; 32]), uris, data);
+            let token_ids = token_ids.unwrap();
+            assert!(nft_contract.is_valid(token_ids[0]));
+            assert_eq!(nft_contract.redeem(token_ids[0]), Ok(()));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(20_001);
+            assert!(!nft_contract.is_valid(token_ids[1]));
+            assert_eq!(nft_contract.redeem(token_ids[1]), Err(Error::Expired));
+            assert_eq!(nft_contract.burn(token_ids[1]), Ok(()));
+            assert!(!nft_contract.is_valid(token_ids[1]));
+        }
+
+        #[ink::test]
+        fn test_event_collections() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = |count| vec!["TicketURI".to_string(); count];
+            assert_eq!(nft_contract.create_collection(7, "Concert".to_string(), Some(2)), Ok(()));
+            let result = nft_contract.create_collection(7, "Concert".to_string(), None);
+            assert_eq!(result, Err(Error::CollectionExists));
+            assert_eq!(nft_contract.add_collection_minter(7, accounts.bob), Ok(()));
+            let result = nft_contract.add_collection_minter(8, accounts.bob);
+            assert_eq!(result, Err(Error::CollectionNotFound));
+
+            // Bob mints for event 7 only, and only up to its cap
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let minted = nft_contract.mint_batch(accounts.charlie, uris(2), ticket(7, 0, 0));
+            assert_eq!(minted, Ok(vec![1, 2]));
+            let minted = nft_contract.mint_batch(accounts.charlie, uris(1), ticket(7, 0, 0));
+            assert_eq!(minted, Err(Error::SupplyCapReached));
+            let minted = nft_contract.mint_batch(accounts.charlie, uris(1), ticket(8, 0, 0));
+            assert_eq!(minted, Err(Error::Unauthorized));
+            assert_eq!(nft_contract.assign_seat(1, Some(3)), Ok(()));
+            let collection = nft_contract.get_collection(7).unwrap();
+            assert_eq!((collection.name.as_str(), collection.minted), ("Concert", 2));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let minted = nft_contract.mint_batch(accounts.charlie, uris(1), ticket(8, 0, 0));
+            assert_eq!(minted, Ok(vec![3]));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.assign_seat(3, Some(3)), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_ownership_transfer() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(nft_contract.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(nft_contract.owner(), accounts.alice);
+            assert_eq!(nft_contract.pending_owner(), Some(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(nft_contract.accept_ownership(), Err(Error::Unauthorized));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.accept_ownership(), Ok(()));
+            assert_eq!(nft_contract.owner(), accounts.bob);
+            assert_eq!(nft_contract.pending_owner(), None);
+            assert_eq!(nft_contract.set_paused(true), Ok(()));
+
+            // The previous owner lost every owner-only message
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.set_paused(false), Err(Error::Unauthorized));
+            assert_eq!(nft_contract.add_minter(accounts.alice), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_adopt_collection() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.charlie, "TicketURI".to_string(), 0).unwrap();
+            assert_eq!(nft_contract.set_observer(Some(accounts.eve)), Ok(()));
+            assert_eq!(nft_contract.get_observer(), Some(accounts.eve));
+
+            // Only a pending owner can adopt, and only the owner moves the observer
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.adopt_collection(), Err(Error::Unauthorized));
+            assert_eq!(nft_contract.set_observer(None), Err(Error::Unauthorized));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.transfer_ownership(accounts.bob), Ok(()));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.adopt_collection(), Ok(()));
+            assert_eq!(nft_contract.owner(), accounts.bob);
+            assert_eq!(nft_contract.get_observer(), Some(accounts.bob));
+            // As owner the adopter can burn tickets it doesn't hold, e.g. for refunds
+            assert_eq!(TicketMinter::burn(&mut nft_contract, token_id), Ok(()));
+            assert_eq!(TicketMinter::owner_of(&nft_contract, token_id), None);
+        }
+
+        #[ink::test]
+        fn test_pause() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            assert_eq!(nft_contract.set_paused(true), Ok(()));
+            assert!(nft_contract.is_paused());
+            let minted = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0);
+            assert_eq!(minted, Err(Error::Paused));
+            let uris = vec!["TicketURI".to_string()];
+            let minted = nft_contract.mint_batch(accounts.bob, uris, ticket(1, 0, 0));
+            assert_eq!(minted, Err(Error::Paused));
+            assert_eq!(nft_contract.mint_badge(accounts.bob), Err(Error::Paused));
+
+            // Tickets already out keep working
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let transferred = nft_contract.transfer(accounts.charlie, Id::U64(token_id), vec![]);
+            assert_eq!(transferred, Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.set_paused(false), Ok(()));
+            assert!(nft_contract.mint_badge(accounts.bob).is_ok());
+        }
+
+        #[ink::test]
+        fn test_safe_transfer() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token_id = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0).unwrap();
+            let id = Id::U64(token_id);
+
+            // Plain accounts have no hook to consult and receive as usual
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.safe_transfer(accounts.charlie, id.clone(), vec![]), Ok(()));
+            assert_eq!(PSP34::owner_of(&nft_contract, id.clone()), Some(accounts.charlie));
+            assert_eq!(
+                nft_contract.safe_transfer(accounts.django, id.clone(), vec![]),
+                Err(PSP34Error::NotApproved)
+            );
+            assert_eq!(
+                nft_contract.safe_transfer(accounts.django, Id::U64(99), vec![]),
+                Err(PSP34Error::TokenNotExists)
+            );
+        }
+
+        #[ink::test]
+        fn test_ticket_code() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string(); 2];
+            let minted = nft_contract.mint_batch(accounts.bob, uris, ticket(7, 0, 0)).unwrap();
+            let code = nft_contract.ticket_code(minted[0]).unwrap();
+            assert_eq!(nft_contract.ticket_code(minted[0]), Some(code));
+            assert_ne!(nft_contract.ticket_code(minted[1]), Some(code));
+            assert_eq!(nft_contract.ticket_code(99), None);
+            assert_eq!(nft_contract.verify_code(7, code, accounts.bob), Some(minted[0]));
+            assert_eq!(nft_contract.verify_code(8, code, accounts.bob), None);
+            assert_eq!(nft_contract.verify_code(7, code, accounts.charlie), None);
+
+            // Passing the ticket on retires its old code
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let id = Id::U64(minted[0]);
+            assert_eq!(nft_contract.transfer(accounts.charlie, id, vec![]), Ok(()));
+            assert_eq!(nft_contract.verify_code(7, code, accounts.charlie), None);
+            let code = nft_contract.ticket_code(minted[0]).unwrap();
+            assert_eq!(nft_contract.verify_code(7, code, accounts.charlie), Some(minted[0]));
+        }
+
+        #[ink::test]
+        fn test_supports_interface() {
+            let nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            assert!(nft_contract.supports_interface(INTERFACE_DETECTION_ID));
+            assert!(nft_contract.supports_interface(PSP34_INTERFACE_ID));
+            assert!(nft_contract.supports_interface(PSP34_METADATA_INTERFACE_ID));
+            assert!(nft_contract.supports_interface(PSP34_ROYALTY_INTERFACE_ID));
+            assert!(nft_contract.supports_interface(TICKET_MINTER_INTERFACE_ID));
+            // A single selector isn't the whole interface
+            assert!(!nft_contract.supports_interface(ink::selector_id!("PSP34::transfer")));
+            assert!(!nft_contract.supports_interface(0xffff_ffff));
+        }
+
+        #[ink::test]
+        fn test_transfer_batch() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string(); 3];
+            let minted = nft_contract.mint_batch(accounts.bob, uris, ticket(1, 0, 0)).unwrap();
+            let before = ink::env::test::recorded_events().count();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let recipients = vec![(accounts.charlie, minted[0]), (accounts.django, minted[1])];
+            assert_eq!(nft_contract.transfer_batch(recipients), Ok(()));
+            assert_eq!(TicketMinter::owner_of(&nft_contract, minted[0]), Some(accounts.charlie));
+            assert_eq!(TicketMinter::owner_of(&nft_contract, minted[1]), Some(accounts.django));
+            assert_eq!(nft_contract.balance_of(accounts.bob), 1);
+            // One event for the whole batch
+            assert_eq!(ink::env::test::recorded_events().count(), before + 1);
+
+            // Bob no longer holds the first ticket, so the batch fails
+            let recipients = vec![(accounts.charlie, minted[2]), (accounts.eve, minted[0])];
+            let result = nft_contract.transfer_batch(recipients);
+            assert_eq!(result, Err(PSP34Error::NotApproved));
+            let recipients = vec![(accounts.charlie, minted[2]); 101];
+            let result = nft_contract.transfer_batch(recipients);
+            assert_eq!(result, Err(PSP34Error::Custom(String::from("BatchTooLarge"))));
+        }
+
+        #[ink::test]
+        fn test_redemption_stats() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string(); 3];
+            let minted = nft_contract.mint_batch(accounts.bob, uris, ticket(7, 0, 0)).unwrap();
+            assert_eq!(nft_contract.redemption_stats(7), (0, 3));
+            assert_eq!(nft_contract.redeem(minted[0]), Ok(()));
+            assert_eq!(nft_contract.redemption_stats(7), (1, 2));
+
+            // Used tickets stay counted when burned; unused ones leave the outstanding count
+            assert_eq!(nft_contract.burn(minted[0]), Ok(()));
+            assert_eq!(nft_contract.burn(minted[1]), Ok(()));
+            assert_eq!(nft_contract.redemption_stats(7), (1, 1));
+            assert_eq!(nft_contract.redemption_stats(8), (0, 0));
+        }
+
+        #[ink::test]
+        fn test_recover_ticket() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string()];
+            let data = TicketData { seat: Some(12), ..ticket(1, 2, 10_000) };
+            let old = nft_contract.mint_batch(accounts.django, uris, data.clone()).unwrap()[0];
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let result = nft_contract.recover_ticket(old, accounts.bob);
+            assert_eq!(result, Err(Error::Unauthorized));
+
+            // Minters can issue tickets but not reissue them
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.add_minter(accounts.charlie), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            let result = nft_contract.recover_ticket(old, accounts.bob);
+            assert_eq!(result, Err(Error::Unauthorized));
+            assert_eq!(TicketMinter::owner_of(&nft_contract, old), Some(accounts.django));
+
+            // The replacement is the same ticket under a new id and holder
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let new = nft_contract.recover_ticket(old, accounts.bob).unwrap();
+            assert_ne!(new, old);
+            assert_eq!(TicketMinter::owner_of(&nft_contract, old), None);
+            assert_eq!(TicketMinter::owner_of(&nft_contract, new), Some(accounts.bob));
+            assert_eq!(nft_contract.get_token_uri(new), Some("TicketURI".to_string()));
+            assert_eq!(nft_contract.get_token_tier(new), Some(2));
+            assert_eq!(nft_contract.get_ticket_data(new), Some(data));
+            assert_eq!(nft_contract.redemption_stats(1), (0, 1));
+            assert_eq!(nft_contract.total_supply(), 1);
+            let result = nft_contract.recover_ticket(old, accounts.bob);
+            assert_eq!(result, Err(Error::TokenNotFound));
+        }
+
+        #[ink::test]
+        fn test_recover_redeemed_ticket() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let uris = vec!["TicketURI".to_string()];
+            let token_id = nft_contract.mint_batch(accounts.bob, uris, ticket(1, 0, 0)).unwrap()[0];
+            assert_eq!(nft_contract.redeem(token_id), Ok(()));
+
+            // Used tickets would let someone in twice
+            let result = nft_contract.recover_ticket(token_id, accounts.charlie);
+            assert_eq!(result, Err(Error::AlreadyRedeemed));
+            assert_eq!(TicketMinter::owner_of(&nft_contract, token_id), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn test_mint_badge() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let recipient = AccountId::from([0x1; 32]);
+            let ticket_id = nft_contract.mint(recipient, "TicketURI".to_string(), 0).unwrap();
+            let badge_id = nft_contract.mint_badge(recipient).unwrap();
+            assert_eq!(TicketMinter::owner_of(&nft_contract, badge_id), Some(recipient));
+            assert!(nft_contract.is_badge(badge_id));
+            assert!(!nft_contract.is_badge(ticket_id));
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(nft_contract.mint_badge(recipient), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_minters() {
+            let mut nft_contract = TicketNFT::new("BlockPassNFT".to_string(), "BPNT".to_string());
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            // Forged mints by anyone not on the allowlist fail
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let forged = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0);
+            assert_eq!(forged, Err(Error::Unauthorized));
+            assert_eq!(nft_contract.add_minter(accounts.bob), Err(Error::Unauthorized));
+            assert_eq!(nft_contract.total_supply(), 0);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.add_minter(accounts.bob), Ok(()));
+            assert!(nft_contract.is_minter(accounts.bob));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(nft_contract.mint(accounts.charlie, "TicketURI".to_string(), 0).is_ok());
+            assert!(nft_contract.mint_badge(accounts.charlie).is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(nft_contract.remove_minter(accounts.bob), Ok(()));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let revoked = nft_contract.mint(accounts.bob, "TicketURI".to_string(), 0);
+            assert_eq!(revoked, Err(Error::Unauthorized));
+        }
+    }
+}